@@ -0,0 +1,129 @@
+use std::io;
+use std::path::Path;
+
+use crate::math::color::Color;
+use crate::math::vec3::Vec3;
+use crate::utils::common::{random, random_int, DEG_TO_RAD, PI};
+use crate::utils::pgm::GrayImage;
+
+/// Rejection sampling gives up after this many tries and falls back to the lens center.
+const MAX_SAMPLE_ATTEMPTS: usize = 64;
+
+/// Outline of the lens opening, scaled to the defocus disk given by `defocus_angle`.
+#[derive(Clone, Debug)]
+pub enum ApertureShape {
+    Circle,
+    /// Regular polygon inscribed in the defocus disk, as formed by straight diaphragm blades.
+    /// The rotation is in degrees. Fewer than 3 blades are taken as 3.
+    Polygon { blades: u32, rotation: f64 },
+    /// Arbitrary transmission mask covering the square around the defocus disk.
+    Mask(ApertureMask),
+}
+
+/// Grayscale image whose brightness is the transmission through the aperture.
+#[derive(Clone, Debug)]
+pub struct ApertureMask {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f64>, // Row by row, top row first, in [0, 1]
+}
+
+impl ApertureMask {
+    /// Loads a mask from a PGM image (plain "P2" or binary "P5").
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let GrayImage { width, height, values } = GrayImage::load(path)?;
+        if width == 0 || height == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty aperture mask"));
+        }
+        Ok(ApertureMask { width, height, values })
+    }
+
+    /// Transmission at (x, y) in [-1, 1], with y pointing up.
+    fn transmission(&self, x: f64, y: f64) -> f64 {
+        let i = (((x + 1.0) / 2.0) * self.width as f64) as usize;
+        let j = (((1.0 - y) / 2.0) * self.height as f64) as usize;
+        self.values[j.min(self.height - 1) * self.width + i.min(self.width - 1)]
+    }
+}
+
+/// Lens aperture model used for depth of field.
+#[derive(Clone, Debug)]
+pub struct Aperture {
+    pub shape: ApertureShape,
+    pub cat_eye: f64, // Optical vignetting toward the image corners; 0 disables, 1 halves the corner bokeh
+    pub axial_chromatic_aberration: f64, // Relative focus distance difference of red and blue
+    pub lateral_chromatic_aberration: f64, // Relative magnification difference of red and blue
+}
+
+impl Default for Aperture {
+    fn default() -> Self {
+        Aperture {
+            shape: ApertureShape::Circle,
+            cat_eye: 0.0,
+            axial_chromatic_aberration: 0.0,
+            lateral_chromatic_aberration: 0.0,
+        }
+    }
+}
+
+impl Aperture {
+    /// Returns a point (x, y) on the aperture in defocus disk units. `screen` is the sample's
+    /// image position in [-1, 1], with y pointing up, used for cat's-eye vignetting.
+    pub fn sample(&self, screen: (f64, f64)) -> (f64, f64) {
+        // The lens barrel clips the aperture with a circle that moves toward the image center
+        // for off-axis pixels, turning round bokeh into cat's eyes near the corners.
+        let barrel = (-self.cat_eye * screen.0, -self.cat_eye * screen.1);
+        for _ in 0..MAX_SAMPLE_ATTEMPTS {
+            let Some((x, y)) = self.sample_shape() else { continue };
+            let (dx, dy) = (x - barrel.0, y - barrel.1);
+            if self.cat_eye <= 0.0 || dx * dx + dy * dy <= 1.0 {
+                return (x, y);
+            }
+        }
+        (0.0, 0.0)
+    }
+
+    /// Samples the aperture shape, or returns None for a rejected mask sample.
+    fn sample_shape(&self) -> Option<(f64, f64)> {
+        match &self.shape {
+            ApertureShape::Circle => {
+                let p = Vec3::random_on_unit_disk();
+                Some((p.x, p.y))
+            }
+            ApertureShape::Polygon { blades, rotation } => {
+                // Pick one of the congruent triangles fanning out from the center, then a
+                // uniform point inside it.
+                let blades = (*blades).max(3);
+                let step = 2.0 * PI / blades as f64;
+                let k = random_int(0, blades as i32) as f64;
+                let a0 = DEG_TO_RAD * rotation + k * step;
+                let a1 = a0 + step;
+                let (mut s, mut t) = (random(), random());
+                if s + t > 1.0 {
+                    s = 1.0 - s;
+                    t = 1.0 - t;
+                }
+                Some((s * a0.cos() + t * a1.cos(), s * a0.sin() + t * a1.sin()))
+            }
+            ApertureShape::Mask(mask) => {
+                let (x, y) = (2.0 * random() - 1.0, 2.0 * random() - 1.0);
+                (random() < mask.transmission(x, y)).then_some((x, y))
+            }
+        }
+    }
+
+    pub fn has_chromatic_aberration(&self) -> bool {
+        self.axial_chromatic_aberration != 0.0 || self.lateral_chromatic_aberration != 0.0
+    }
+
+    /// Picks the color channel a chromatic sample carries. Returns the channel's weight, which
+    /// keeps the estimate unbiased, and its position from -1 (red) to 1 (blue) for scaling the
+    /// aberrations.
+    pub fn sample_channel(&self) -> (Color, f64) {
+        match random_int(0, 3) {
+            0 => (Color::new(3.0, 0.0, 0.0), -1.0),
+            1 => (Color::new(0.0, 3.0, 0.0), 0.0),
+            _ => (Color::new(0.0, 0.0, 3.0), 1.0),
+        }
+    }
+}
@@ -1,20 +1,26 @@
-use std::{fs::File, io::{self, BufWriter, Write}, time::Instant};
-use indicatif::{ProgressBar, ProgressStyle};
+use std::{fs::File, io::{self, BufWriter}, time::{Duration, Instant}};
 use rayon::prelude::*;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::math::{vec3::{Point3, Vec3}};
 use crate::math::ray::Ray;
-use crate::math::color::{Color, WritableColor};
+use crate::math::color::Color;
 use crate::math::interval::Interval;
 use crate::core::hittable_list::HittableList;
-use crate::core::hittable::HitRecord;
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::aperture::Aperture;
+use crate::core::framebuffer::{Framebuffer, PixelAccumulator, Tile};
+use crate::core::projection::{self, Projection};
+use crate::core::shutter::Shutter;
+use crate::core::stereo::{Convergence, Stereo};
+use crate::core::progress::{CancellationToken, RenderObserver, RenderPlan, TerminalProgress};
+use crate::core::stats::{self, Counter, CounterTotals, RenderStats};
 use crate::utils::common::*;
 
 const ASPECT_RATIO: f64 = 16.0 / 9.0;
 const IMAGE_WIDTH: u64 = 1028;
 const IMAGE_HEIGHT: u64 = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as u64;
-const MAX_COLOR: u8 = 255;
+const TILE_SIZE: u64 = 16;
 
 const VIEWPORT_HEIGHT: f64 = 2.0;
 const VIEWPORT_WIDTH: f64 = VIEWPORT_HEIGHT * (IMAGE_WIDTH as f64 / IMAGE_HEIGHT as f64);
@@ -24,29 +30,49 @@ const CAMERA_CENTER: Point3 = Point3 { x: 0.0, y: 0.0, z: 0.0 };
 pub struct Camera {
     pub aspect_ratio: f64, // Ratio of image width over height
     pub image_width: u64, // Rendered image width in pixel count
-    pub samples_per_pixel: u64, // Count of random samples for each pixel
+    pub samples_per_pixel: u64, // Count of random samples for each pixel; progressive mode ignores it
+    pub samples_per_pass: u64, // Samples added to every pixel by each progressive pass
+    pub max_samples_per_pixel: Option<u64>, // Progressive mode: optional cap on the samples of each pixel
+    pub time_budget: Option<Duration>, // Progressive mode: stop once the next pass would exceed this wall-clock time
+    pub target_error: Option<f64>, // Progressive mode: stop once the mean relative error drops below this
     pub max_depth: u64, // Maximum depth of ray recursion
     pub background: Color, // Scene background color
 
     pub defocus_angle: f64, // The angle of the defocus disk
     pub focus_distance: f64, // The distance to the focal plane
+    pub aperture: Aperture, // Shape and optical defects of the defocus disk
+    pub shutter: Shutter, // Interval over which ray times are sampled for motion blur
     pub vfov: f64,
+    pub projection: Projection,
     pub eye: Vec3,
     pub look_at: Vec3,
     pub up: Vec3,
-    image_height: u64, // Rendered image height
+    pub stereo: Option<Stereo>, // Render left and right eyes packed into one image
+    pub stats: RenderStats, // Statistics of the last render
+    image_height: u64, // Rendered image height (per eye)
+    views: Vec<View>, // One view, or left and right eye views in stereo
+}
+
+/// Ray generation parameters derived from the camera settings for one viewpoint.
+#[derive(Clone, Copy, Default)]
+struct View {
     center: Point3, // Camera center
     pixel_delta_u: Vec3, // Offset to pixel to the right
     pixel_delta_v: Vec3, // Offset to pixel below
     pixel00_loc: Point3, // Location of pixel 0,0
-    pixel_samples_scale: f64, // Color scale factor for a sum of pixel samples
-
-
     defocus_disk_u: Vec3,
     defocus_disk_v: Vec3,
     w: Vec3,
     u: Vec3,
     v: Vec3,
+    ods_offset: f64, // Signed eye offset on the interocular circle of a stereo panorama
+}
+
+impl View {
+    fn defocus_disk_sample(&self, p: (f64, f64)) -> Vec3 {
+        // Returns the point of the camera defocus disk at aperture coordinates p.
+        self.center + (p.0 * self.defocus_disk_u) + (p.1 * self.defocus_disk_v)
+    }
 }
 
 impl Camera {
@@ -55,118 +81,233 @@ impl Camera {
             aspect_ratio: ASPECT_RATIO,
             image_width: IMAGE_WIDTH,
             samples_per_pixel: 100,
+            samples_per_pass: 4,
+            max_samples_per_pixel: None,
+            time_budget: None,
+            target_error: None,
             image_height: IMAGE_HEIGHT,
-            center: CAMERA_CENTER,
             max_depth: 10,
             defocus_angle: 0.0,
             focus_distance: 1.0,
-            pixel_delta_u: Vec3::default(),
-            pixel_delta_v: Vec3::default(),
-            pixel00_loc: Point3::default(),
-            pixel_samples_scale: 1.0 / 16.0,
+            aperture: Aperture::default(),
+            shutter: Shutter::default(),
             vfov: 90.0,
+            projection: Projection::Perspective,
             eye: Vec3::new(0.0, 0.0, -1.0),
             look_at: Vec3::default(),
             up: Vec3::new(0.0, 1.0, 0.0),
-            defocus_disk_u: Vec3::default(),
-            defocus_disk_v: Vec3::default(),
-            w: Vec3::default(),
-            u: Vec3::default(),
-            v: Vec3::default(),
+            stereo: None,
+            views: vec![View { center: CAMERA_CENTER, ..View::default() }],
             background: Color::default(),
+            stats: RenderStats::default(),
         }
     }
 
     pub fn render(&mut self, world: &HittableList, writer: &mut BufWriter<File>) -> io::Result<()> {
-        self.initialize();
+        let framebuffer = self.render_with(world, &TerminalProgress::new(), &CancellationToken::new());
 
-        writeln!(writer, "P3\n{} {}\n{}", self.image_width, self.image_height, MAX_COLOR)?;
+        let output_start = Instant::now();
+        framebuffer.write_ppm(writer)?;
+        self.stats.output_seconds = output_start.elapsed().as_secs_f64();
+        Ok(())
+    }
 
-        let pb = ProgressBar::new(((self.image_width * self.image_height)).into());
-        pb.set_style(ProgressStyle::with_template("{spinner:.green} {msg} [{bar:20.cyan/blue}] {percent}% [{elapsed_precise}]").unwrap());
+    /// Renders the world into a framebuffer, reporting progress to the observer. If the token is
+    /// cancelled, rendering stops promptly and the partially accumulated framebuffer is returned.
+    pub fn render_with(&mut self, world: &HittableList, observer: &dyn RenderObserver, cancel: &CancellationToken) -> Framebuffer {
+        self.initialize();
 
-        // Prepare all pixel coordinates
-        let image_width = self.image_width;
-        let image_height = self.image_height;
-        let samples_per_pixel = self.samples_per_pixel;
+        let (image_width, image_height) = self.frame_size();
         let max_depth = self.max_depth;
-        let pixel_samples_scale = self.pixel_samples_scale;
-        let world = world;
-        // Collect all pixel coordinates
-        let pixels: Vec<(u64, u64)> = (0..image_height)
-            .flat_map(|j| (0..image_width).map(move |i| (i, j)))
-            .collect();
+
+        // In progressive mode every pass adds a few samples to each pixel until the time budget
+        // or noise target stops it, so the image can be stopped after any pass. Otherwise a
+        // single pass traces all samples at once.
+        let progressive = self.is_progressive();
+        let samples_per_pixel = if progressive { self.max_samples_per_pixel.map(|max| max.max(1)) } else { Some(self.samples_per_pixel) };
+        let samples_per_pass = if progressive {
+            self.samples_per_pass.clamp(1, samples_per_pixel.unwrap_or(u64::MAX))
+        } else {
+            self.samples_per_pixel
+        };
+        let max_passes = samples_per_pixel.map(|samples_per_pixel| samples_per_pixel.div_ceil(samples_per_pass));
+        observer.render_started(&RenderPlan { image_width, image_height, samples_per_pixel, max_passes });
+
+        let tiles = Tile::grid(image_width, image_height, TILE_SIZE);
+        let mut framebuffer = Framebuffer::new(image_width, image_height);
+        self.stats = RenderStats { image_width, image_height, ..RenderStats::default() };
         // Start time
         let start_time = Instant::now();
-        let total_samples = (image_width * image_height * samples_per_pixel) as u64;
         let samples_rendered = AtomicU64::new(0);
+        let counters = CounterTotals::default();
+        let mut samples_done = 0;
+        let mut passes = 0;
+
+        while samples_per_pixel.is_none_or(|samples_per_pixel| samples_done < samples_per_pixel) && !cancel.is_cancelled() {
+            let pass_samples = samples_per_pixel.map_or(samples_per_pass, |samples_per_pixel| samples_per_pass.min(samples_per_pixel - samples_done));
+            let pass = passes + 1;
+
+            // Parallel rendering, one tile at a time per worker
+            let rendered: Vec<Vec<PixelAccumulator>> = tiles.par_iter().map(|tile| {
+                let mut pixels = framebuffer.tile_pixels(tile);
+                if cancel.is_cancelled() {
+                    return pixels;
+                }
+                stats::discard();
+                for (pixel, (i, j)) in pixels.iter_mut().zip(tile.pixels()) {
+                    if cancel.is_cancelled() {
+                        break;
+                    }
+                    for _ in 0..pass_samples {
+                        let color = match self.get_ray(i, j) {
+                            Some((ray, weight)) => weight * self.ray_color(ray, max_depth, world),
+                            None => Color::black(),
+                        };
+                        pixel.add(color);
+                    }
+                    samples_rendered.fetch_add(pass_samples, Ordering::Relaxed);
+                }
+                counters.flush();
 
-        // Parallel rendering
-        let pixel_colors: Vec<Color> = pixels.par_iter().map(|&(i, j)| {
-            let mut pixel_color = Color::default();
-            for _ in 0..samples_per_pixel {
-                let ray = self.get_ray(i, j);
-                pixel_color += self.ray_color(ray, max_depth, world);
-                samples_rendered.fetch_add(1, Ordering::Relaxed);
+                let mut running = self.stats.clone();
+                running.samples = samples_rendered.load(Ordering::Relaxed);
+                running.passes = passes;
+                running.render_seconds = start_time.elapsed().as_secs_f64();
+                running.collect_counters(&counters);
+                observer.tile_completed(tile, pass);
+                observer.stats_updated(&running);
+                pixels
+            }).collect();
+
+            for (tile, pixels) in tiles.iter().zip(rendered) {
+                framebuffer.store_tile(tile, pixels);
+            }
+            if cancel.is_cancelled() {
+                break;
+            }
+            samples_done += pass_samples;
+            passes += 1;
+            observer.pass_completed(passes, &framebuffer);
+
+            if progressive && self.budget_reached(&framebuffer, start_time.elapsed(), samples_rendered.load(Ordering::Relaxed)) {
+                break;
             }
-            // Update progress bar
-            let elapsed_time = start_time.elapsed().as_secs_f64();
-            let rendered_samples = samples_rendered.load(Ordering::Relaxed);
-            let render_speed = rendered_samples as f64 / (elapsed_time * 1000.0); // Convert seconds to milliseconds
-            pb.set_message(format!(
-                "{} samples/ms | {}/{} ",
-                fmt_samples(render_speed as u64), fmt_samples(rendered_samples as u64), fmt_samples(total_samples),
-            ));
-            pb.inc(1);
-            pixel_color * pixel_samples_scale
-        }).collect();
-
-        // Write pixels sequentially and update progress bar per pixel
-        for color in pixel_colors.iter() {
-            color.write_color(writer).unwrap();
         }
-        let total_time = start_time.elapsed().as_secs_f64();
-        let average_speed = total_samples as f64 / (total_time * 1000.0);
-        pb.finish_with_message("Rendering complete!");
-        println!("Total samples: {}, Total Time: {}, Average speed: {} samples/ms",
-            fmt_samples(total_samples), fmt_time(total_time), average_speed as u64);
-        Ok(())
+
+        self.stats.samples = samples_rendered.load(Ordering::Relaxed);
+        self.stats.passes = passes;
+        self.stats.samples_per_pixel = samples_done;
+        self.stats.mean_relative_error = framebuffer.mean_relative_error();
+        self.stats.cancelled = cancel.is_cancelled();
+        self.stats.render_seconds = start_time.elapsed().as_secs_f64();
+        self.stats.acceleration_bytes = world.acceleration_bytes();
+        self.stats.collect_counters(&counters);
+        observer.render_finished(&self.stats);
+        framebuffer
+    }
+
+    /// Progressive rendering is used whenever a time budget or a noise target is set.
+    pub fn is_progressive(&self) -> bool {
+        self.time_budget.is_some() || self.target_error.is_some()
+    }
+
+    /// Decides after a progressive pass whether rendering should stop. The time budget check
+    /// uses the measured samples/ms speed to predict whether another pass still fits.
+    fn budget_reached(&self, framebuffer: &Framebuffer, elapsed: Duration, samples_rendered: u64) -> bool {
+        if self.target_error.is_some_and(|target_error| framebuffer.mean_relative_error() <= target_error) {
+            return true;
+        }
+        if let Some(time_budget) = self.time_budget {
+            let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+            let samples_per_ms = samples_rendered as f64 / elapsed_ms.max(f64::EPSILON);
+            let next_pass_samples = framebuffer.width * framebuffer.height * self.samples_per_pass;
+            let next_pass_ms = next_pass_samples as f64 / samples_per_ms;
+            if elapsed_ms + next_pass_ms > time_budget.as_secs_f64() * 1000.0 {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Size of the rendered image; stereo packs both eyes into one image.
+    pub fn frame_size(&self) -> (u64, u64) {
+        match self.stereo {
+            Some(stereo) => stereo.frame_size(self.image_width, self.image_height),
+            None => (self.image_width, self.image_height),
+        }
     }
 
     fn initialize(&mut self) {
         self.image_height = (self.image_width as f64 / self.aspect_ratio) as u64;
         self.image_height = self.image_height.max(1);
-        self.pixel_samples_scale = 1.0 / self.samples_per_pixel as f64;
+        self.samples_per_pixel = self.samples_per_pixel.max(1);
+
+        self.views = match self.stereo {
+            None => vec![self.view(self.eye, self.look_at, 0.0, 0.0)],
+            Some(stereo) => [-0.5, 0.5].iter().map(|side| {
+                let offset = side * stereo.interocular_distance;
+                let w = Vec3::unit_vector(self.eye - self.look_at);
+                let u = Vec3::unit_vector(Vec3::cross_two(self.up, w));
+                let eye = self.eye + offset * u;
+                if self.projection == Projection::Equirectangular {
+                    return self.view(self.eye, self.look_at, 0.0, offset);
+                }
+                match stereo.convergence {
+                    Convergence::Parallel => self.view(eye, self.look_at + offset * u, 0.0, 0.0),
+                    Convergence::ToeIn => self.view(eye, self.eye - stereo.convergence_distance * w, 0.0, 0.0),
+                    Convergence::OffAxis => {
+                        // Shift the viewport against the eye so both frusta share the image
+                        // rectangle at the convergence distance.
+                        let shift = -offset * self.focus_distance / stereo.convergence_distance;
+                        self.view(eye, self.look_at + offset * u, shift, 0.0)
+                    }
+                }
+            }).collect(),
+        };
+    }
 
-        self.center = self.eye;
+    /// Computes the view from `eye` toward `look_at`, with the viewport shifted by
+    /// `viewport_shift` along u.
+    fn view(&self, eye: Point3, look_at: Point3, viewport_shift: f64, ods_offset: f64) -> View {
+        let mut view = View { center: eye, ods_offset, ..View::default() };
 
-        let theta = DEG_TO_RAD * self.vfov;
-        let h = (theta / 2.0).tan();
-        let viewport_height = 2.0 * h * self.focus_distance;
-        let viewport_width = viewport_height * (self.image_width as f64 / self.image_height as f64);
+        let (viewport_width, viewport_height, viewport_distance) = match self.projection {
+            Projection::Perspective => {
+                let theta = DEG_TO_RAD * self.vfov;
+                let h = (theta / 2.0).tan();
+                let viewport_height = 2.0 * h * self.focus_distance;
+                let viewport_width = viewport_height * (self.image_width as f64 / self.image_height as f64);
+                (viewport_width, viewport_height, self.focus_distance)
+            }
+            // The view rectangle passes through the eye; rays leave it in parallel.
+            Projection::Orthographic { view_width, view_height } => (view_width, view_height, 0.0),
+            // Panoramic projections map pixels to directions without a viewport, see get_ray.
+            _ => (1.0, 1.0, 1.0),
+        };
 
-        self.w = Vec3::unit_vector(self.eye - self.look_at);
-        self.u = Vec3::unit_vector(Vec3::cross_two(self.up, self.w));
-        self.v = Vec3::cross(self.w, self.u);
+        view.w = Vec3::unit_vector(eye - look_at);
+        view.u = Vec3::unit_vector(Vec3::cross_two(self.up, view.w));
+        view.v = Vec3::cross(view.w, view.u);
 
-        let viewport_u = viewport_width * self.u;
-        let viewport_v = viewport_height * -self.v;
+        let viewport_u = viewport_width * view.u;
+        let viewport_v = viewport_height * -view.v;
 
-        self.pixel_delta_u = viewport_u / self.image_width as f64;
-        self.pixel_delta_v = viewport_v / self.image_height as f64;
+        view.pixel_delta_u = viewport_u / self.image_width as f64;
+        view.pixel_delta_v = viewport_v / self.image_height as f64;
 
-        let viewport_upper_left = self.center - (self.focus_distance * self.w) - (viewport_u / 2.0) - (viewport_v / 2.0);
-        self.pixel00_loc = viewport_upper_left + 0.5 * (self.pixel_delta_u + self.pixel_delta_v);
+        let viewport_upper_left = view.center - (viewport_distance * view.w) - (viewport_u / 2.0) - (viewport_v / 2.0)
+            + viewport_shift * view.u;
+        view.pixel00_loc = viewport_upper_left + 0.5 * (view.pixel_delta_u + view.pixel_delta_v);
 
         let defocus_radius = self.focus_distance * ((DEG_TO_RAD * self.defocus_angle) / 2.0).tan();
-        self.defocus_disk_u = defocus_radius * self.u;
-        self.defocus_disk_v = defocus_radius * self.v;
+        view.defocus_disk_u = defocus_radius * view.u;
+        view.defocus_disk_v = defocus_radius * view.v;
+        view
     }
 
     pub fn defocus_disk_sample(&self) -> Vec3 {
-        // Returns a random point in the camera defocus disk.
-        let p = Vec3::random_on_unit_disk();
-        self.center + (p.x * self.defocus_disk_u) + (p.y * self.defocus_disk_v)
+        // Returns a random point in the defocus disk of the (first) view.
+        self.views[0].defocus_disk_sample(self.aperture.sample((0.0, 0.0)))
     }
 
     fn ray_color(&self, r: Ray, depth: u64, world: &HittableList) -> Color {
@@ -175,30 +316,83 @@ impl Camera {
 
         let mut rec = HitRecord::default();
 
+        stats::count(Counter::RaysCast);
         if !world.hit(r, Interval::new(0.0001, INFINITY), &mut rec) {
-            return self.background;
+            return self.through_media(r, INFINITY, world, self.background);
         }
         let mut scattered = Ray::default();
         let mut attenuation = Color::default();
         let color_from_emission = rec.material.map_or(Color::default(), |mat| mat.emitted(rec.u, rec.v, rec.point));
         if !rec.material.map_or(false, |mat| mat.scatter(r, &rec, &mut attenuation, &mut scattered)) {
-            return color_from_emission;
+            return self.through_media(r, rec.t, world, color_from_emission);
         }
         let ray_color = self.ray_color(scattered, depth - 1, world);
         let color_from_scatter = attenuation * ray_color;
-        return color_from_emission + color_from_scatter;
+        self.through_media(r, rec.t, world, color_from_emission + color_from_scatter)
     }
 
-    fn get_ray(&self, i: u64, j: u64) -> Ray {
+    /// Attenuates the light arriving from distance `t_max` along the ray by the non-scattering
+    /// media in front of it, and adds the light they emit. Media are composited front to back
+    /// in the order the ray enters them.
+    fn through_media(&self, r: Ray, t_max: f64, world: &HittableList, color: Color) -> Color {
+        if !world.contains_media() {
+            return color;
+        }
+        let mut segments = Vec::new();
+        world.media_segments(r, Interval::new(0.0001, t_max), &mut segments);
+        segments.sort_by(|a, b| a.t.total_cmp(&b.t));
+        segments.iter().rev().fold(color, |color, segment| segment.emission + segment.transmittance * color)
+    }
+
+    fn get_ray(&self, i: u64, j: u64) -> Option<(Ray, Color)> {
         // Construct a camera ray originating from the defocus disk and directed at a randomly
-        // sampled point around the pixel location i, j.
+        // sampled point around the pixel location i, j. Also returns the sample's color weight,
+        // which restricts it to one channel when simulating chromatic aberration. Returns None
+        // where a panoramic projection covers no directions.
+        let (view, i, j) = match self.stereo {
+            Some(stereo) => {
+                let (eye, i, j) = stereo.eye_pixel(i, j, self.image_width, self.image_height);
+                (&self.views[eye], i, j)
+            }
+            None => (&self.views[0], i, j),
+        };
         let offset = self.sample_square();
-        let pixel_sample = self.pixel00_loc
-            + ((i as f64 + offset.x) * self.pixel_delta_u)
-            + ((j as f64 + offset.y) * self.pixel_delta_v);
-        let ray_origin = if self.defocus_angle <= 0.0 { self.center } else { self.defocus_disk_sample() };
-        let ray_direction = pixel_sample - ray_origin;
-        Ray { origin: ray_origin, direction: ray_direction }
+        let time = self.shutter.sample();
+        if self.projection.is_panoramic() {
+            let s = (i as f64 + 0.5 + offset.x) / self.image_width as f64;
+            let t = (j as f64 + 0.5 + offset.y) / self.image_height as f64;
+            let aspect = self.image_width as f64 / self.image_height as f64;
+            let direction = self.projection.panoramic_direction(s, t, aspect, view.u, view.v, view.w)?;
+            let origin = view.center + view.ods_offset * projection::equirectangular_tangent(s, view.u, view.w);
+            return Some((Ray { origin, direction, time }, Color::white()));
+        }
+
+        let (weight, channel) = if self.aperture.has_chromatic_aberration() {
+            self.aperture.sample_channel()
+        } else {
+            (Color::white(), 0.0)
+        };
+        // Pixel position relative to the image center; lateral chromatic aberration scales it
+        // per channel.
+        let half_width = self.image_width as f64 / 2.0;
+        let half_height = self.image_height as f64 / 2.0;
+        let magnification = 1.0 + 0.5 * channel * self.aperture.lateral_chromatic_aberration;
+        let x = (i as f64 + 0.5 + offset.x - half_width) * magnification;
+        let y = (j as f64 + 0.5 + offset.y - half_height) * magnification;
+        let pixel_sample = view.pixel00_loc
+            + ((x + half_width - 0.5) * view.pixel_delta_u)
+            + ((y + half_height - 0.5) * view.pixel_delta_v);
+        if let Projection::Orthographic { .. } = self.projection {
+            return Some((Ray { origin: pixel_sample, direction: -view.w, time }, weight));
+        }
+        if self.defocus_angle <= 0.0 {
+            return Some((Ray { origin: view.center, direction: pixel_sample - view.center, time }, weight));
+        }
+        let ray_origin = view.defocus_disk_sample(self.aperture.sample((x / half_width, -y / half_height)));
+        // Axial chromatic aberration moves each channel's focal plane.
+        let focus = 1.0 + 0.5 * channel * self.aperture.axial_chromatic_aberration;
+        let ray_direction = view.center + focus * (pixel_sample - view.center) - ray_origin;
+        Some((Ray { origin: ray_origin, direction: ray_direction, time }, weight))
     }
 
     fn sample_disk(&self, radius: f64) -> Vec3 {
@@ -210,3 +404,10 @@ impl Camera {
         Vec3::new(random() - 0.5, random() - 0.5, 0.0)
     }
 }
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera::new()
+    }
+}
+
@@ -0,0 +1,135 @@
+use std::{fs::File, io::{self, BufWriter, Write}};
+
+use crate::math::color::{Color, WritableColor};
+use crate::utils::common::INFINITY;
+
+const MAX_COLOR: u8 = 255;
+
+/// Running sums of the samples taken for one pixel across progressive passes.
+#[derive(Clone, Default)]
+pub struct PixelAccumulator {
+    sum: Color,
+    luminance_sum: f64,
+    luminance_sq_sum: f64,
+    samples: u64,
+}
+
+impl PixelAccumulator {
+    pub fn add(&mut self, sample: Color) {
+        let luminance = sample.luminance();
+        self.sum += sample;
+        self.luminance_sum += luminance;
+        self.luminance_sq_sum += luminance * luminance;
+        self.samples += 1;
+    }
+
+    /// Average of all samples taken so far.
+    pub fn color(&self) -> Color {
+        if self.samples == 0 {
+            return Color::default();
+        }
+        self.sum / self.samples as f64
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Standard error of the mean luminance relative to the mean itself. A small offset keeps
+    /// near-black pixels from dominating the estimate.
+    pub fn relative_error(&self) -> f64 {
+        if self.samples < 2 {
+            return INFINITY;
+        }
+        let n = self.samples as f64;
+        let mean = self.luminance_sum / n;
+        let variance = (self.luminance_sq_sum / n - mean * mean).max(0.0);
+        (variance / (n - 1.0)).sqrt() / (mean + 0.01)
+    }
+}
+
+/// A rectangular block of pixels rendered as one unit of parallel work.
+#[derive(Clone, Copy, Debug)]
+pub struct Tile {
+    pub x: u64,
+    pub y: u64,
+    pub width: u64,
+    pub height: u64,
+}
+
+impl Tile {
+    /// Splits an image into tiles of at most `size` x `size` pixels, row by row.
+    pub fn grid(image_width: u64, image_height: u64, size: u64) -> Vec<Tile> {
+        let mut tiles = Vec::new();
+        for y in (0..image_height).step_by(size as usize) {
+            for x in (0..image_width).step_by(size as usize) {
+                let width = size.min(image_width - x);
+                let height = size.min(image_height - y);
+                tiles.push(Tile { x, y, width, height });
+            }
+        }
+        tiles
+    }
+
+    pub fn pixel_count(&self) -> u64 {
+        self.width * self.height
+    }
+
+    /// Image coordinates (i, j) of the tile's pixels, row by row.
+    pub fn pixels(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        (self.y..self.y + self.height).flat_map(move |j| (self.x..self.x + self.width).map(move |i| (i, j)))
+    }
+}
+
+/// Accumulated image of a render. Pixels that were never sampled (e.g. after cancellation) are black.
+#[derive(Clone)]
+pub struct Framebuffer {
+    pub width: u64,
+    pub height: u64,
+    pixels: Vec<PixelAccumulator>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u64, height: u64) -> Self {
+        Framebuffer { width, height, pixels: vec![PixelAccumulator::default(); (width * height) as usize] }
+    }
+
+    pub fn pixel(&self, i: u64, j: u64) -> &PixelAccumulator {
+        &self.pixels[(j * self.width + i) as usize]
+    }
+
+    /// Average color of the pixel at column i, row j.
+    pub fn color(&self, i: u64, j: u64) -> Color {
+        self.pixel(i, j).color()
+    }
+
+    /// Copies the accumulators covered by a tile, row by row.
+    pub fn tile_pixels(&self, tile: &Tile) -> Vec<PixelAccumulator> {
+        tile.pixels().map(|(i, j)| self.pixel(i, j).clone()).collect()
+    }
+
+    /// Stores accumulators previously obtained from `tile_pixels`.
+    pub fn store_tile(&mut self, tile: &Tile, pixels: Vec<PixelAccumulator>) {
+        for ((i, j), pixel) in tile.pixels().zip(pixels) {
+            self.pixels[(j * self.width + i) as usize] = pixel;
+        }
+    }
+
+    pub fn mean_relative_error(&self) -> f64 {
+        self.pixels.iter().map(PixelAccumulator::relative_error).sum::<f64>() / self.pixels.len() as f64
+    }
+
+    /// Gamma-corrected 8-bit RGB values of all pixels, row by row.
+    pub fn to_bytes(&self) -> Vec<[u8; 3]> {
+        self.pixels.iter().map(|pixel| pixel.color().to_bytes()).collect()
+    }
+
+    /// Writes the image in plain PPM format.
+    pub fn write_ppm(&self, writer: &mut BufWriter<File>) -> io::Result<()> {
+        writeln!(writer, "P3\n{} {}\n{}", self.width, self.height, MAX_COLOR)?;
+        for pixel in self.pixels.iter() {
+            pixel.color().write_color(writer).unwrap();
+        }
+        writer.flush()
+    }
+}
@@ -5,7 +5,10 @@ use std::sync::Arc;
 
 use crate::core::material::Material;
 use crate::math::aabb::Aabb;
+use crate::math::color::Color;
 use crate::math::interval::Interval;
+use crate::math::keyframes::Track;
+use crate::math::matrix::Matrix4;
 use crate::math::ray::Ray;
 use crate::math::vec3::{Point3, Vec3};
 
@@ -18,6 +21,9 @@ pub struct HitRecord {
     pub u: f64,
     pub v: f64,
     pub front_face: bool,
+    pub orbit_trap: f64, // Closest approach of a fractal's orbit to the origin, for coloring
+    pub tangent: Vec3, // Direction of curves along u, set by curves for hair shading (else zero)
+    pub color: Color, // Color stored with the primitive, such as particle or vertex colors (else white)
 }
 
 impl Default for HitRecord {
@@ -31,6 +37,9 @@ impl Default for HitRecord {
             u: 0.0,
             v: 0.0,
             front_face: false,
+            orbit_trap: 0.0,
+            tangent: Vec3::default(),
+            color: Color::white(),
         }
     }
 }
@@ -45,17 +54,37 @@ impl Clone for HitRecord {
             u: self.u,
             v: self.v,
             front_face: self.front_face,
+            orbit_trap: self.orbit_trap,
+            tangent: self.tangent,
+            color: self.color,
         }
     }
 }
 
 impl HitRecord {
     /// Sets the normal and front_face fields based on the ray and outward normal.
-    /// The outward normal is assumed to be unit length.
+    /// The outward normal is assumed to be unit length. Records are reused across candidate
+    /// hits, so this also resets the tangent and color; primitives that have their own set
+    /// them afterwards.
     pub fn set_face_normal(&mut self, r: Ray, outward_normal: Vec3) {
         self.front_face = Vec3::dot_two(r.direction, outward_normal) < 0.0;
         self.normal = if self.front_face { outward_normal } else { -outward_normal };
+        self.clear_primitive_data();
     }
+
+    /// Resets the fields only some primitives fill in to their defaults.
+    pub fn clear_primitive_data(&mut self) {
+        self.tangent = Vec3::default();
+        self.color = Color::white();
+    }
+}
+
+/// Light change along the part of a ray that crosses a non-scattering medium.
+#[derive(Clone, Copy, Debug)]
+pub struct MediumSegment {
+    pub t: f64, // Where the ray enters the medium
+    pub transmittance: Color, // Fraction of light from behind the medium that passes through
+    pub emission: Color, // Light emitted inside the medium towards the ray origin
 }
 
 /// Trait for objects that can be intersected by rays.
@@ -65,40 +94,104 @@ pub trait Hittable: Send + Sync {
 
     /// Returns the axis-aligned bounding box of the object.
     fn bounding_box(&self) -> &Aabb;
+
+    /// Returns the memory in bytes held by acceleration structures inside the object.
+    fn acceleration_bytes(&self) -> u64 {
+        0
+    }
+
+    /// Returns true if the object holds media that absorb or emit light along rays passing
+    /// through them without scattering (see `media_segments`).
+    fn contains_media(&self) -> bool {
+        false
+    }
+
+    /// Appends a segment for each non-scattering medium the ray crosses within the interval.
+    /// Scattering media report their collisions through `hit` instead.
+    fn media_segments(&self, _r: Ray, _interval: Interval, _segments: &mut Vec<MediumSegment>) {}
+
+    /// Appends every intersection of the ray with the object within the interval, in order,
+    /// for constructive solid geometry. The default finds them one at a time with `hit`;
+    /// primitives that can solve for all of them at once override it.
+    fn all_hits(&self, r: Ray, interval: Interval, hits: &mut Vec<HitRecord>) {
+        let mut t_min = interval.min;
+        let mut rec = HitRecord::default();
+        while self.hit(r, Interval::new(t_min, interval.max), &mut rec) {
+            hits.push(rec.clone());
+            // Step past the hit, so surfaces meeting at an edge count once.
+            t_min = rec.t + 1e-9 * rec.t.abs().max(1.0);
+        }
+    }
 }
 
 pub struct Translate {
     pub hittable: Arc<dyn Hittable + Send + Sync>,
     pub offset: Vec3,
+    pub motion: Option<Track<Vec3>>, // Offset over the shutter interval; overrides `offset`
     pub bbox: Aabb,
 }
 
 impl Translate {
     pub fn new(hittable: Arc<dyn Hittable + Send + Sync>, offset: Vec3) -> Self {
         let bbox = hittable.bounding_box().clone() + offset;
-        Translate { hittable, offset, bbox }
+        Translate { hittable, offset, motion: None, bbox }
+    }
+
+    /// Translates by an offset that changes with ray time. The bounding box encloses the
+    /// object at every hull point of the track, and therefore along the whole path.
+    pub fn moving(hittable: Arc<dyn Hittable + Send + Sync>, motion: Track<Vec3>) -> Self {
+        let bbox = motion.hull_points().into_iter().fold(Aabb::empty(), |bbox, offset| {
+            Aabb::from_aabb(&bbox, &(hittable.bounding_box().clone() + offset))
+        });
+        Translate { hittable, offset: motion.sample(0.0), motion: Some(motion), bbox }
     }
 }
 
 impl Hittable for Translate {
     fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
-        let offset_r = Ray { origin: r.origin - self.offset, direction: r.direction };
+        let offset = self.motion.as_ref().map_or(self.offset, |motion| motion.sample(r.time));
+        let offset_r = Ray { origin: r.origin - offset, direction: r.direction, time: r.time };
         if !self.hittable.hit(offset_r, interval, rec) {
             return false;
         }
-        rec.point += self.offset;
+        rec.point += offset;
         true
     }
 
+    fn all_hits(&self, r: Ray, interval: Interval, hits: &mut Vec<HitRecord>) {
+        let offset = self.motion.as_ref().map_or(self.offset, |motion| motion.sample(r.time));
+        let offset_r = Ray { origin: r.origin - offset, direction: r.direction, time: r.time };
+        let first = hits.len();
+        self.hittable.all_hits(offset_r, interval, hits);
+        for rec in &mut hits[first..] {
+            rec.point += offset;
+        }
+    }
+
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.hittable.acceleration_bytes()
+    }
+
+    fn contains_media(&self) -> bool {
+        self.hittable.contains_media()
+    }
+
+    fn media_segments(&self, r: Ray, interval: Interval, segments: &mut Vec<MediumSegment>) {
+        let offset = self.motion.as_ref().map_or(self.offset, |motion| motion.sample(r.time));
+        let offset_r = Ray { origin: r.origin - offset, direction: r.direction, time: r.time };
+        self.hittable.media_segments(offset_r, interval, segments);
+    }
 }
 
 pub struct RotateY {
     pub hittable: Arc<dyn Hittable + Send + Sync>,
     pub sin_theta: f64,
     pub cos_theta: f64,
+    pub motion: Option<Track<f64>>, // Angle in degrees over the shutter interval; overrides the fixed angle
     pub bbox: Aabb,
 }
 
@@ -107,58 +200,123 @@ impl RotateY {
         let radians = angle.to_radians();
         let sin_theta = radians.sin();
         let cos_theta = radians.cos();
-        let mut bbox = hittable.bounding_box().clone();
-
-        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
-        let mut max = Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
-
-        for i in 0..2 {
-            for j in 0..2 {
-                for k in 0..2 {
-                    let x = i as f64 * bbox.x.max + (1 - i) as f64 * bbox.x.min;
-                    let y = j as f64 * bbox.y.max + (1 - j) as f64 * bbox.y.min;
-                    let z = k as f64 * bbox.z.max + (1 - k) as f64 * bbox.z.min;
-
-                    let newx = cos_theta * x + sin_theta * z;
-                    let newz = -sin_theta * x + cos_theta * z;
-
-                    let tester = Vec3::new(newx, y, newz);
+        let bbox = rotated_bbox(hittable.bounding_box(), sin_theta, cos_theta);
+        RotateY { hittable, sin_theta, cos_theta, motion: None, bbox }
+    }
 
-                    for c in 0..3 {
-                        min[c] = min[c].min(tester[c]);
-                        max[c] = max[c].max(tester[c]);
-                    }
-                }
+    /// Rotates by an angle (in degrees) that changes with ray time. The bounding box is the
+    /// union of boxes rotated in small steps, padded by the most the corners can bulge out
+    /// between two steps.
+    pub fn rotating(hittable: Arc<dyn Hittable + Send + Sync>, motion: Track<f64>) -> Self {
+        const MAX_STEP: f64 = 5.0;
+        let object_bbox = hittable.bounding_box().clone();
+        let mut bbox = Aabb::empty();
+        let mut add_angle = |angle: f64| {
+            let (sin_theta, cos_theta) = angle.to_radians().sin_cos();
+            bbox = Aabb::from_aabb(&bbox, &rotated_bbox(&object_bbox, sin_theta, cos_theta));
+        };
+        // Consecutive hull points bound the angles in between, even for Bezier tracks.
+        let angles = motion.hull_points();
+        add_angle(angles[0]);
+        for pair in angles.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let steps = ((to - from).abs() / MAX_STEP).ceil().max(1.0) as usize;
+            for step in 1..=steps {
+                add_angle(from + (to - from) * step as f64 / steps as f64);
             }
         }
 
-        bbox = Aabb::from_points(&min, &max);
+        // Corners sweep arcs around the y axis; between two sampled angles an arc strays from
+        // the sampled boxes by at most its sagitta.
+        let radius = [object_bbox.x.min, object_bbox.x.max].iter()
+            .flat_map(|&x| [object_bbox.z.min, object_bbox.z.max].map(|z| (x * x + z * z).sqrt()))
+            .fold(0.0, f64::max);
+        let padding = 2.0 * radius * (1.0 - (MAX_STEP.to_radians() / 2.0).cos());
+        let bbox = Aabb {
+            x: bbox.x.expand(padding),
+            y: bbox.y,
+            z: bbox.z.expand(padding),
+        };
 
-        RotateY { hittable, sin_theta, cos_theta, bbox }
+        let (sin_theta, cos_theta) = motion.sample(0.0).to_radians().sin_cos();
+        RotateY { hittable, sin_theta, cos_theta, motion: Some(motion), bbox }
     }
-}
 
-impl Hittable for RotateY {
-    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
-        // Transform the ray from world space to object space.
+    /// Sine and cosine of the rotation at the ray's time, and the ray transformed from world
+    /// space to object space.
+    fn object_ray(&self, r: Ray) -> (f64, f64, Ray) {
+        let (sin_theta, cos_theta) = match &self.motion {
+            Some(motion) => motion.sample(r.time).to_radians().sin_cos(),
+            None => (self.sin_theta, self.cos_theta),
+        };
 
         let origin = Point3::new(
-            (self.cos_theta * r.origin.x) - (self.sin_theta * r.origin.z),
+            (cos_theta * r.origin.x) - (sin_theta * r.origin.z),
             r.origin.y,
-            (self.sin_theta * r.origin.x) + (self.cos_theta * r.origin.z),
+            (sin_theta * r.origin.x) + (cos_theta * r.origin.z),
         );
 
         let direction = Vec3::new(
-            (self.cos_theta * r.direction.x) - (self.sin_theta * r.direction.z),
+            (cos_theta * r.direction.x) - (sin_theta * r.direction.z),
             r.direction.y,
-            (self.sin_theta * r.direction.x) + (self.cos_theta * r.direction.z),
+            (sin_theta * r.direction.x) + (cos_theta * r.direction.z),
         );
 
-        let rotated_r = Ray {
-            origin,
-            direction,
-            ..r
-        };
+        (sin_theta, cos_theta, Ray { origin, direction, ..r })
+    }
+}
+
+/// Rotates the point and normal of a hit found in object space back to world space.
+fn rotate_to_world(rec: &mut HitRecord, sin_theta: f64, cos_theta: f64) {
+    rec.point = Point3::new(
+        (cos_theta * rec.point.x) + (sin_theta * rec.point.z),
+        rec.point.y,
+        (-sin_theta * rec.point.x) + (cos_theta * rec.point.z),
+    );
+
+    rec.normal = Vec3::new(
+        (cos_theta * rec.normal.x) + (sin_theta * rec.normal.z),
+        rec.normal.y,
+        (-sin_theta * rec.normal.x) + (cos_theta * rec.normal.z),
+    );
+}
+
+/// Bounding box of `bbox` rotated about the y axis. Unbounded boxes stay unbounded, since
+/// rotating their infinite corners would give NaN.
+fn rotated_bbox(bbox: &Aabb, sin_theta: f64, cos_theta: f64) -> Aabb {
+    if bbox.is_unbounded() {
+        return Aabb::universe();
+    }
+    let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+    let mut max = Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+
+    for i in 0..2 {
+        for j in 0..2 {
+            for k in 0..2 {
+                let x = i as f64 * bbox.x.max + (1 - i) as f64 * bbox.x.min;
+                let y = j as f64 * bbox.y.max + (1 - j) as f64 * bbox.y.min;
+                let z = k as f64 * bbox.z.max + (1 - k) as f64 * bbox.z.min;
+
+                let newx = cos_theta * x + sin_theta * z;
+                let newz = -sin_theta * x + cos_theta * z;
+
+                let tester = Vec3::new(newx, y, newz);
+
+                for c in 0..3 {
+                    min[c] = min[c].min(tester[c]);
+                    max[c] = max[c].max(tester[c]);
+                }
+            }
+        }
+    }
+
+    Aabb::from_points(&min, &max)
+}
+
+impl Hittable for RotateY {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        // Transform the ray from world space to object space.
+        let (sin_theta, cos_theta, rotated_r) = self.object_ray(r);
 
         // Determine whether an intersection exists in object space (and if so, where).
         if !self.hittable.hit(rotated_r, interval, rec) {
@@ -166,22 +324,113 @@ impl Hittable for RotateY {
         }
 
         // Transform the intersection from object space back to world space.
-        rec.point = Point3::new(
-            (self.cos_theta * rec.point.x) + (self.sin_theta * rec.point.z),
-            rec.point.y,
-            (-self.sin_theta * rec.point.x) + (self.cos_theta * rec.point.z),
-        );
+        rotate_to_world(rec, sin_theta, cos_theta);
+        true
+    }
 
-        rec.normal = Vec3::new(
-            (self.cos_theta * rec.normal.x) + (self.sin_theta * rec.normal.z),
-            rec.normal.y,
-            (-self.sin_theta * rec.normal.x) + (self.cos_theta * rec.normal.z),
-        );
+    fn all_hits(&self, r: Ray, interval: Interval, hits: &mut Vec<HitRecord>) {
+        let (sin_theta, cos_theta, rotated_r) = self.object_ray(r);
+        let first = hits.len();
+        self.hittable.all_hits(rotated_r, interval, hits);
+        for rec in &mut hits[first..] {
+            rotate_to_world(rec, sin_theta, cos_theta);
+        }
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.hittable.acceleration_bytes()
+    }
+
+    fn contains_media(&self) -> bool {
+        self.hittable.contains_media()
+    }
+
+    fn media_segments(&self, r: Ray, interval: Interval, segments: &mut Vec<MediumSegment>) {
+        // Rotation keeps the direction's length, so distances along the ray are unchanged.
+        let (_, _, rotated_r) = self.object_ray(r);
+        self.hittable.media_segments(rotated_r, interval, segments);
+    }
+}
 
+
+/// Places an object with an affine transformation, such as the node transforms of imported
+/// scenes. Shared objects can be instanced many times, each under its own transformation.
+pub struct Transform {
+    pub hittable: Arc<dyn Hittable + Send + Sync>,
+    pub matrix: Matrix4, // Object space to world space
+    inverse: Matrix4,
+    bbox: Aabb,
+}
+
+impl Transform {
+    /// Panics if the matrix cannot be inverted, e.g. a scale of zero.
+    pub fn new(hittable: Arc<dyn Hittable + Send + Sync>, matrix: Matrix4) -> Self {
+        let inverse = matrix.inverse().expect("transformation matrix is singular");
+        let object_bbox = hittable.bounding_box();
+        let bbox = if object_bbox.is_unbounded() {
+            Aabb::universe()
+        } else {
+            let corners = (0..8).map(|corner| {
+                let pick = |axis: usize| {
+                    let interval = object_bbox.axis_interval(axis);
+                    if corner >> axis & 1 == 0 { interval.min } else { interval.max }
+                };
+                matrix.transform_point(Point3::new(pick(0), pick(1), pick(2)))
+            });
+            corners.fold(Aabb::empty(), |bbox, p| Aabb::from_aabb(&bbox, &Aabb::from_points(&p, &p)))
+        };
+        Transform { hittable, matrix, inverse, bbox }
+    }
+
+    /// The ray in object space. The direction is not normalized, so distances along the ray
+    /// stay the same in both spaces.
+    fn object_ray(&self, r: Ray) -> Ray {
+        Ray { origin: self.inverse.transform_point(r.origin), direction: self.inverse.transform_vector(r.direction), time: r.time }
+    }
+
+    fn to_world(&self, rec: &mut HitRecord) {
+        rec.point = self.matrix.transform_point(rec.point);
+        rec.normal = Vec3::unit_vector(self.inverse.transform_normal(rec.normal));
+        if !rec.tangent.near_zero() {
+            rec.tangent = Vec3::unit_vector(self.matrix.transform_vector(rec.tangent));
+        }
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        if !self.hittable.hit(self.object_ray(r), interval, rec) {
+            return false;
+        }
+        self.to_world(rec);
         true
     }
 
+    fn all_hits(&self, r: Ray, interval: Interval, hits: &mut Vec<HitRecord>) {
+        let first = hits.len();
+        self.hittable.all_hits(self.object_ray(r), interval, hits);
+        for rec in &mut hits[first..] {
+            self.to_world(rec);
+        }
+    }
+
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.hittable.acceleration_bytes()
+    }
+
+    fn contains_media(&self) -> bool {
+        self.hittable.contains_media()
+    }
+
+    fn media_segments(&self, r: Ray, interval: Interval, segments: &mut Vec<MediumSegment>) {
+        self.hittable.media_segments(self.object_ray(r), interval, segments);
+    }
 }
@@ -1,5 +1,5 @@
 use crate::math::aabb::Aabb;
-use crate::core::hittable::{Hittable, HitRecord};
+use crate::core::hittable::{Hittable, HitRecord, MediumSegment};
 use crate::math::bvh_node::BvhNode;
 use crate::math::ray::Ray;
 use crate::math::interval::Interval;
@@ -40,6 +40,12 @@ impl HittableList {
     }
 }
 
+impl Default for HittableList {
+    fn default() -> Self {
+        HittableList::new()
+    }
+}
+
 impl Hittable for HittableList {
     fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
         self.hit(r, interval, rec)
@@ -48,4 +54,18 @@ impl Hittable for HittableList {
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.objects.iter().map(|object| object.acceleration_bytes()).sum()
+    }
+
+    fn contains_media(&self) -> bool {
+        self.objects.iter().any(|object| object.contains_media())
+    }
+
+    fn media_segments(&self, r: Ray, interval: Interval, segments: &mut Vec<MediumSegment>) {
+        for object in self.objects.iter().filter(|object| object.contains_media()) {
+            object.media_segments(r, interval.clone(), segments);
+        }
+    }
 }
@@ -1,4 +1,13 @@
+pub mod animation;
+pub mod aperture;
 pub mod camera;
+pub mod framebuffer;
 pub mod hittable;
 pub mod hittable_list;
-pub mod material;
\ No newline at end of file
+pub mod material;
+pub mod progress;
+pub mod projection;
+pub mod shutter;
+pub mod stats;
+pub mod stereo;
+pub mod video;
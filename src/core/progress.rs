@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::core::framebuffer::{Framebuffer, Tile};
+use crate::core::stats::RenderStats;
+use crate::utils::common::{fmt_samples, fmt_time};
+
+/// What a render is about to do, reported to observers before the first pass.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderPlan {
+    pub image_width: u64,
+    pub image_height: u64,
+    pub samples_per_pixel: Option<u64>, // None when a progressive render runs until its budget
+    pub max_passes: Option<u64>,
+}
+
+impl RenderPlan {
+    pub fn total_samples(&self) -> Option<u64> {
+        self.samples_per_pixel.map(|samples_per_pixel| self.image_width * self.image_height * samples_per_pixel)
+    }
+}
+
+/// Receives progress events from `Camera::render_with`. Tile events arrive from worker
+/// threads, so implementations must be thread-safe. Every method defaults to doing nothing.
+pub trait RenderObserver: Sync {
+    fn render_started(&self, _plan: &RenderPlan) {}
+
+    /// A tile finished its share of the given pass (counted from 1).
+    fn tile_completed(&self, _tile: &Tile, _pass: u64) {}
+
+    /// Every tile finished the given pass; the framebuffer holds all samples taken so far.
+    fn pass_completed(&self, _pass: u64, _framebuffer: &Framebuffer) {}
+
+    /// Running statistics, sent after every tile.
+    fn stats_updated(&self, _stats: &RenderStats) {}
+
+    fn render_finished(&self, _stats: &RenderStats) {}
+}
+
+/// Observer that ignores every event.
+pub struct NoProgress;
+
+impl RenderObserver for NoProgress {}
+
+/// Terminal progress bar with samples/ms, and a summary once rendering ends. Without a known
+/// pass count the bar tracks the current pass.
+pub struct TerminalProgress {
+    pb: ProgressBar,
+    total_samples: AtomicU64, // 0 when the render has no sample limit
+    per_pass: AtomicBool, // The bar restarts every pass because the pass count is unknown
+}
+
+impl TerminalProgress {
+    pub fn new() -> Self {
+        let pb = ProgressBar::new(0);
+        pb.set_style(ProgressStyle::with_template("{spinner:.green} {msg} [{bar:20.cyan/blue}] {percent}% [{elapsed_precise}]").unwrap());
+        TerminalProgress { pb, total_samples: AtomicU64::new(0), per_pass: AtomicBool::new(false) }
+    }
+}
+
+impl Default for TerminalProgress {
+    fn default() -> Self {
+        TerminalProgress::new()
+    }
+}
+
+impl RenderObserver for TerminalProgress {
+    fn render_started(&self, plan: &RenderPlan) {
+        let pixels = plan.image_width * plan.image_height;
+        match plan.max_passes {
+            Some(max_passes) => self.pb.set_length(pixels * max_passes),
+            None => {
+                self.pb.set_length(pixels);
+                self.per_pass.store(true, Ordering::Relaxed);
+            }
+        }
+        self.total_samples.store(plan.total_samples().unwrap_or(0), Ordering::Relaxed);
+    }
+
+    fn tile_completed(&self, tile: &Tile, _pass: u64) {
+        self.pb.inc(tile.pixel_count());
+    }
+
+    fn pass_completed(&self, _pass: u64, _framebuffer: &Framebuffer) {
+        if self.per_pass.load(Ordering::Relaxed) {
+            self.pb.set_position(0);
+        }
+    }
+
+    fn stats_updated(&self, stats: &RenderStats) {
+        let total_samples = self.total_samples.load(Ordering::Relaxed);
+        let samples = if total_samples > 0 {
+            format!("{}/{}", fmt_samples(stats.samples), fmt_samples(total_samples))
+        } else {
+            fmt_samples(stats.samples)
+        };
+        self.pb.set_message(format!("{} samples/ms | {} ", fmt_samples(stats.samples_per_ms() as u64), samples));
+    }
+
+    fn render_finished(&self, stats: &RenderStats) {
+        if stats.cancelled {
+            self.pb.abandon_with_message("Rendering cancelled");
+        } else {
+            self.pb.finish_with_message("Rendering complete!");
+        }
+        if stats.passes > 1 {
+            println!("Passes: {}, Samples per pixel: {}, Mean relative error: {:.4}",
+                stats.passes, stats.samples_per_pixel, stats.mean_relative_error);
+        }
+        println!("Total samples: {}, Total Time: {}, Average speed: {} samples/ms",
+            fmt_samples(stats.samples), fmt_time(stats.render_seconds), stats.samples_per_ms() as u64);
+    }
+}
+
+/// Shared flag that stops a render after the samples currently in flight. Clones share the flag,
+/// so one can be handed to another thread while the render holds the other.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
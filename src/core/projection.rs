@@ -0,0 +1,108 @@
+use crate::math::vec3::Vec3;
+use crate::utils::common::{DEG_TO_RAD, PI};
+
+/// How camera rays are generated from the eye/look_at/up basis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// Pinhole or thin-lens camera with a frustum given by `vfov`.
+    Perspective,
+    /// Parallel rays along the view direction through a view rectangle of the given size in
+    /// world units, centered on the eye. Depth of field does not apply.
+    Orthographic { view_width: f64, view_height: f64 },
+    /// Full 360° x 180° latitude-longitude panorama; the image center looks toward `look_at`.
+    /// Use a 2:1 aspect ratio.
+    Equirectangular,
+    /// One 90° face of a cube map, relative to the camera basis. Use a 1:1 aspect ratio.
+    CubeFace(CubeFace),
+    /// Fisheye lens with the given field of view in degrees across the image circle, which is
+    /// inscribed in the shorter image side. Pixels outside the circle stay black.
+    Fisheye { fov: f64, mapping: FisheyeMapping },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CubeFace {
+    Front,
+    Back,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [CubeFace::Front, CubeFace::Back, CubeFace::Left, CubeFace::Right, CubeFace::Up, CubeFace::Down];
+
+    /// Returns the face's (forward, right, up) axes expressed in the camera basis.
+    fn axes(self, u: Vec3, v: Vec3, w: Vec3) -> (Vec3, Vec3, Vec3) {
+        match self {
+            CubeFace::Front => (-w, u, v),
+            CubeFace::Back => (w, -u, v),
+            CubeFace::Left => (-u, -w, v),
+            CubeFace::Right => (u, w, v),
+            CubeFace::Up => (v, u, w),
+            CubeFace::Down => (-v, u, -w),
+        }
+    }
+}
+
+/// Relation between the angle off the optical axis and the distance from the image center.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FisheyeMapping {
+    /// r = f * theta
+    Equidistant,
+    /// r = 2f * sin(theta / 2), preserving solid angle
+    Equisolid,
+}
+
+impl Projection {
+    /// Panoramic projections map image coordinates straight to directions from the eye
+    /// instead of going through a viewport rectangle.
+    pub fn is_panoramic(&self) -> bool {
+        matches!(self, Projection::Equirectangular | Projection::CubeFace(_) | Projection::Fisheye { .. })
+    }
+
+    /// Returns the ray direction through image coordinates (s, t) in [0, 1], with t growing
+    /// downward, or None where the projection covers no directions.
+    pub fn panoramic_direction(&self, s: f64, t: f64, aspect: f64, u: Vec3, v: Vec3, w: Vec3) -> Option<Vec3> {
+        match *self {
+            Projection::Equirectangular => {
+                let longitude = (s - 0.5) * 2.0 * PI;
+                let latitude = (0.5 - t) * PI;
+                let horizontal = latitude.cos();
+                Some(horizontal * longitude.sin() * u + latitude.sin() * v - horizontal * longitude.cos() * w)
+            }
+            Projection::CubeFace(face) => {
+                let (forward, right, up) = face.axes(u, v, w);
+                Some(forward + (2.0 * s - 1.0) * right + (1.0 - 2.0 * t) * up)
+            }
+            Projection::Fisheye { fov, mapping } => {
+                // Normalized coordinates with the unit circle inscribed in the shorter side.
+                let (x, y) = if aspect >= 1.0 {
+                    ((2.0 * s - 1.0) * aspect, 1.0 - 2.0 * t)
+                } else {
+                    (2.0 * s - 1.0, (1.0 - 2.0 * t) / aspect)
+                };
+                let r = (x * x + y * y).sqrt();
+                if r > 1.0 {
+                    return None;
+                }
+                let half_fov = 0.5 * DEG_TO_RAD * fov;
+                let theta = match mapping {
+                    FisheyeMapping::Equidistant => r * half_fov,
+                    FisheyeMapping::Equisolid => 2.0 * (r * (half_fov / 2.0).sin()).asin(),
+                };
+                let (sin_theta, cos_theta) = theta.sin_cos();
+                let (dx, dy) = if r > 0.0 { (x / r, y / r) } else { (0.0, 0.0) };
+                Some(sin_theta * (dx * u + dy * v) - cos_theta * w)
+            }
+            Projection::Perspective | Projection::Orthographic { .. } => None,
+        }
+    }
+}
+
+/// Horizontal direction to the right of the equirectangular column at s, i.e. the tangent of
+/// the interocular circle used for omni-directional stereo.
+pub fn equirectangular_tangent(s: f64, u: Vec3, w: Vec3) -> Vec3 {
+    let longitude = (s - 0.5) * 2.0 * PI;
+    longitude.cos() * u + longitude.sin() * w
+}
@@ -0,0 +1,61 @@
+use crate::utils::common::random;
+
+/// How much light the shutter lets through over its open interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShutterCurve {
+    /// Fully open for the whole interval.
+    Box,
+    /// Opens linearly to the middle of the interval and closes linearly after it.
+    Triangle,
+    /// Opens and closes linearly over the given fraction (at most 0.5) of the interval at
+    /// each end, fully open in between.
+    Trapezoid { ramp: f64 },
+}
+
+/// Camera shutter interval. Each camera ray gets a time drawn from the shutter curve,
+/// so objects moving within the interval are motion blurred.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shutter {
+    pub open: f64,
+    pub close: f64,
+    pub curve: ShutterCurve,
+}
+
+impl Default for Shutter {
+    /// An instantaneous shutter at time 0: no motion blur.
+    fn default() -> Self {
+        Shutter { open: 0.0, close: 0.0, curve: ShutterCurve::Box }
+    }
+}
+
+impl Shutter {
+    pub fn new(open: f64, close: f64) -> Self {
+        Shutter { open, close, curve: ShutterCurve::Box }
+    }
+
+    /// Draws a ray time with density proportional to the shutter curve.
+    pub fn sample(&self) -> f64 {
+        if self.close <= self.open {
+            return self.open;
+        }
+        let fraction = match self.curve {
+            ShutterCurve::Box => random(),
+            ShutterCurve::Triangle => 0.5 * (random() + random()),
+            ShutterCurve::Trapezoid { ramp } => {
+                // The plateau holds (1 - 2 ramp) of the area (1 - ramp); each ramp holds the
+                // rest in equal halves, sampled by inverting its linear density.
+                let ramp = ramp.clamp(0.0, 0.5);
+                let plateau = (1.0 - 2.0 * ramp) / (1.0 - ramp);
+                let u = random();
+                if u < plateau {
+                    ramp + (1.0 - 2.0 * ramp) * random()
+                } else if u < plateau + 0.5 * (1.0 - plateau) {
+                    ramp * random().sqrt()
+                } else {
+                    1.0 - ramp * random().sqrt()
+                }
+            }
+        };
+        self.open + fraction * (self.close - self.open)
+    }
+}
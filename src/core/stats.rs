@@ -0,0 +1,117 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Events counted while rendering. Each thread counts into its own cells, which are
+/// flushed into the render's totals once per tile, so hot loops never touch an atomic.
+#[derive(Clone, Copy)]
+pub enum Counter {
+    RaysCast,
+    BvhNodesVisited,
+    PrimitiveTests,
+}
+
+const COUNTERS: usize = 3;
+
+thread_local! {
+    static LOCAL: [Cell<u64>; COUNTERS] = const { [const { Cell::new(0) }; COUNTERS] };
+}
+
+#[inline]
+pub fn count(counter: Counter) {
+    LOCAL.with(|local| {
+        let cell = &local[counter as usize];
+        cell.set(cell.get() + 1);
+    });
+}
+
+/// Drops the calling thread's counts, such as those of hits tested outside a render.
+pub fn discard() {
+    LOCAL.with(|local| local.iter().for_each(|cell| cell.set(0)));
+}
+
+/// Counter totals of one render. Each render owns its own, so renders running side by side
+/// in one process keep separate reports.
+#[derive(Debug, Default)]
+pub struct CounterTotals {
+    totals: [AtomicU64; COUNTERS],
+}
+
+impl CounterTotals {
+    /// Moves the calling thread's counts into these totals.
+    pub fn flush(&self) {
+        LOCAL.with(|local| {
+            for (cell, total) in local.iter().zip(self.totals.iter()) {
+                total.fetch_add(cell.take(), Ordering::Relaxed);
+            }
+        });
+    }
+
+    fn get(&self, counter: Counter) -> u64 {
+        self.totals[counter as usize].load(Ordering::Relaxed)
+    }
+}
+
+/// Statistics of the last render, emittable as JSON for tracking performance across commits.
+#[derive(Clone, Debug, Default)]
+pub struct RenderStats {
+    pub image_width: u64,
+    pub image_height: u64,
+    pub samples: u64, // Camera samples traced over all pixels
+    pub passes: u64,
+    pub samples_per_pixel: u64, // Samples every pixel received by the last finished pass
+    pub mean_relative_error: f64,
+    pub cancelled: bool,
+    pub rays_cast: u64, // Camera rays plus every scattered ray
+    /// Rays cast toward lights to test visibility. Always 0: the path tracer finds lights only
+    /// by scattering into them and casts no shadow rays. Kept so reports keep a stable schema.
+    pub shadow_rays: u64,
+    pub bvh_nodes_visited: u64,
+    pub primitive_tests: u64,
+    pub scene_build_seconds: f64, // Set by the caller, which owns scene and BVH construction
+    pub render_seconds: f64,
+    pub output_seconds: f64,
+    pub acceleration_bytes: u64, // Memory held by BVH nodes
+}
+
+impl RenderStats {
+    /// Copies a render's counter totals into the stats.
+    pub fn collect_counters(&mut self, totals: &CounterTotals) {
+        self.rays_cast = totals.get(Counter::RaysCast);
+        self.bvh_nodes_visited = totals.get(Counter::BvhNodesVisited);
+        self.primitive_tests = totals.get(Counter::PrimitiveTests);
+    }
+
+    pub fn samples_per_ms(&self) -> f64 {
+        self.samples as f64 / (self.render_seconds * 1000.0).max(f64::EPSILON)
+    }
+
+    pub fn to_json(&self) -> String {
+        let fields = [
+            ("image_width", self.image_width.to_string()),
+            ("image_height", self.image_height.to_string()),
+            ("samples", self.samples.to_string()),
+            ("passes", self.passes.to_string()),
+            ("samples_per_pixel", self.samples_per_pixel.to_string()),
+            ("mean_relative_error", json_number(self.mean_relative_error)),
+            ("cancelled", self.cancelled.to_string()),
+            ("rays_cast", self.rays_cast.to_string()),
+            ("shadow_rays", self.shadow_rays.to_string()),
+            ("bvh_nodes_visited", self.bvh_nodes_visited.to_string()),
+            ("primitive_tests", self.primitive_tests.to_string()),
+            ("samples_per_ms", format!("{:.3}", self.samples_per_ms())),
+            ("scene_build_seconds", format!("{:.6}", self.scene_build_seconds)),
+            ("render_seconds", format!("{:.6}", self.render_seconds)),
+            ("output_seconds", format!("{:.6}", self.output_seconds)),
+            ("acceleration_bytes", self.acceleration_bytes.to_string()),
+        ];
+        let body: Vec<String> = fields.iter()
+            .map(|(name, value)| format!("  \"{}\": {}", name, value))
+            .collect();
+        format!("{{\n{}\n}}\n", body.join(",\n"))
+    }
+}
+
+/// JSON has no infinity, which is the error estimate of pixels with fewer than two samples.
+fn json_number(value: f64) -> String {
+    if value.is_finite() { format!("{:.6}", value) } else { "null".to_string() }
+}
@@ -0,0 +1,56 @@
+/// How the two eyes' view axes relate to the convergence (zero-parallax) distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Convergence {
+    /// Parallel view axes; objects at infinity have zero parallax.
+    Parallel,
+    /// Each eye is rotated to look at the convergence point. Simple, but introduces
+    /// vertical parallax toward the image corners.
+    ToeIn,
+    /// Parallel view axes with asymmetric frusta sharing the image rectangle at the
+    /// convergence distance. Usually the most comfortable to view.
+    OffAxis,
+}
+
+/// How the two eyes are packed into the output image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StereoLayout {
+    /// Left eye in the left half, right eye in the right half.
+    SideBySide,
+    /// Left eye in the top half, right eye in the bottom half.
+    TopBottom,
+}
+
+/// Stereoscopic rendering settings. Each eye is offset by half the interocular distance
+/// along the camera's `u` axis. With the equirectangular projection the result is an
+/// omni-directional stereo panorama: ray origins lie on the interocular circle, so every
+/// viewing direction gets correct parallax and the convergence mode does not apply.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stereo {
+    pub interocular_distance: f64, // In world units
+    pub convergence_distance: f64, // Distance from the eyes to the zero-parallax plane
+    pub convergence: Convergence,
+    pub layout: StereoLayout,
+}
+
+impl Stereo {
+    pub fn new(interocular_distance: f64, convergence_distance: f64, convergence: Convergence, layout: StereoLayout) -> Self {
+        Stereo { interocular_distance, convergence_distance, convergence, layout }
+    }
+
+    /// Size of the packed image for per-eye images of the given size.
+    pub fn frame_size(&self, eye_width: u64, eye_height: u64) -> (u64, u64) {
+        match self.layout {
+            StereoLayout::SideBySide => (2 * eye_width, eye_height),
+            StereoLayout::TopBottom => (eye_width, 2 * eye_height),
+        }
+    }
+
+    /// Maps a pixel of the packed image to (eye index, column, row) within that eye's image.
+    /// Eye 0 is the left eye.
+    pub fn eye_pixel(&self, i: u64, j: u64, eye_width: u64, eye_height: u64) -> (usize, u64, u64) {
+        match self.layout {
+            StereoLayout::SideBySide => ((i / eye_width) as usize, i % eye_width, j),
+            StereoLayout::TopBottom => ((j / eye_height) as usize, i, j % eye_height),
+        }
+    }
+}
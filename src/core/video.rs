@@ -0,0 +1,335 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::core::framebuffer::Framebuffer;
+
+/// Largest payload of a stored (uncompressed) deflate block.
+const MAX_STORED_BLOCK: usize = 65535;
+
+/// Position of the APNG acTL chunk: after the signature and the IHDR chunk.
+const ANIMATION_CONTROL_OFFSET: u64 = 8 + 25;
+
+/// Container formats that can be written without an external encoder.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VideoFormat {
+    Y4m, // Uncompressed YUV4MPEG2, 4:4:4 chroma, limited-range Rec. 601
+    Apng, // Animated PNG with uncompressed image data, playable in web browsers
+}
+
+impl VideoFormat {
+    /// Picks the format from a file extension (`.y4m`, `.png` or `.apng`).
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "y4m" => Some(VideoFormat::Y4m),
+            "png" | "apng" => Some(VideoFormat::Apng),
+            _ => None,
+        }
+    }
+}
+
+/// Appends successive framebuffers to a single video file.
+pub struct VideoWriter {
+    format: VideoFormat,
+    writer: BufWriter<File>,
+    width: u64,
+    height: u64,
+    fps: f64,
+    frames_written: u32,
+    sequence_number: u32, // APNG frame control and data chunks share one counter
+}
+
+impl VideoWriter {
+    /// Creates the file and writes the container header. APNG stores the frame count up
+    /// front; it is rewritten after every frame, so a sequence stopped early still gives a
+    /// playable file.
+    pub fn create(path: &str, width: u64, height: u64, fps: f64) -> io::Result<Self> {
+        let format = VideoFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown video format: {}", path))
+        })?;
+        let mut video = VideoWriter {
+            format,
+            writer: BufWriter::new(File::create(path)?),
+            width,
+            height,
+            fps,
+            frames_written: 0,
+            sequence_number: 0,
+        };
+        match format {
+            VideoFormat::Y4m => {
+                let (numerator, denominator) = frame_rate_ratio(fps);
+                writeln!(video.writer, "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C444", width, height, numerator, denominator)?;
+            }
+            VideoFormat::Apng => {
+                video.writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+                let mut header = Vec::with_capacity(13);
+                header.extend_from_slice(&(width as u32).to_be_bytes());
+                header.extend_from_slice(&(height as u32).to_be_bytes());
+                header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB, no interlacing
+                video.write_chunk(b"IHDR", &header)?;
+                video.write_animation_control()?;
+            }
+        }
+        Ok(video)
+    }
+
+    pub fn write_frame(&mut self, framebuffer: &Framebuffer) -> io::Result<()> {
+        if framebuffer.width != self.width || framebuffer.height != self.height {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Frame size differs from the video size"));
+        }
+        let pixels = framebuffer.to_bytes();
+        match self.format {
+            VideoFormat::Y4m => self.write_y4m_frame(&pixels)?,
+            VideoFormat::Apng => self.write_apng_frame(&pixels)?,
+        }
+        self.frames_written += 1;
+        if self.format == VideoFormat::Apng {
+            self.writer.seek(SeekFrom::Start(ANIMATION_CONTROL_OFFSET))?;
+            self.write_animation_control()?;
+            self.writer.seek(SeekFrom::End(0))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the trailer and flushes the file.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.format == VideoFormat::Apng {
+            self.write_chunk(b"IEND", &[])?;
+        }
+        self.writer.flush()
+    }
+
+    fn write_y4m_frame(&mut self, pixels: &[[u8; 3]]) -> io::Result<()> {
+        // Planar Y, Cb, Cr at full resolution.
+        let mut planes = vec![0u8; pixels.len() * 3];
+        let (luma, chroma) = planes.split_at_mut(pixels.len());
+        let (blue_difference, red_difference) = chroma.split_at_mut(pixels.len());
+        for (index, &[r, g, b]) in pixels.iter().enumerate() {
+            let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+            let y = 0.299 * r + 0.587 * g + 0.114 * b;
+            luma[index] = (16.0 + 219.0 * y).round() as u8;
+            blue_difference[index] = (128.0 + 224.0 * (b - y) / 1.772).round() as u8;
+            red_difference[index] = (128.0 + 224.0 * (r - y) / 1.402).round() as u8;
+        }
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&planes)
+    }
+
+    /// The acTL chunk with the count of frames written so far.
+    fn write_animation_control(&mut self) -> io::Result<()> {
+        let mut animation = Vec::with_capacity(8);
+        animation.extend_from_slice(&self.frames_written.to_be_bytes());
+        animation.extend_from_slice(&0u32.to_be_bytes()); // Loop forever
+        self.write_chunk(b"acTL", &animation)
+    }
+
+    fn write_apng_frame(&mut self, pixels: &[[u8; 3]]) -> io::Result<()> {
+        let (delay_numerator, delay_denominator) = frame_delay(self.fps);
+        let mut control = Vec::with_capacity(26);
+        control.extend_from_slice(&self.next_sequence_number().to_be_bytes());
+        control.extend_from_slice(&(self.width as u32).to_be_bytes());
+        control.extend_from_slice(&(self.height as u32).to_be_bytes());
+        control.extend_from_slice(&0u32.to_be_bytes()); // x offset
+        control.extend_from_slice(&0u32.to_be_bytes()); // y offset
+        control.extend_from_slice(&delay_numerator.to_be_bytes());
+        control.extend_from_slice(&delay_denominator.to_be_bytes());
+        control.extend_from_slice(&[0, 0]); // No disposal, replace the previous frame
+        self.write_chunk(b"fcTL", &control)?;
+
+        // Every scanline starts with filter type 0 (none).
+        let row_bytes = self.width as usize * 3;
+        let mut scanlines = Vec::with_capacity((row_bytes + 1) * self.height as usize);
+        for row in pixels.chunks(self.width as usize) {
+            scanlines.push(0);
+            scanlines.extend(row.iter().flatten());
+        }
+        let image_data = zlib_stored(&scanlines);
+
+        // The first frame doubles as the default image for viewers without APNG support.
+        if self.frames_written == 0 {
+            self.write_chunk(b"IDAT", &image_data)
+        } else {
+            let mut frame_data = Vec::with_capacity(image_data.len() + 4);
+            frame_data.extend_from_slice(&self.next_sequence_number().to_be_bytes());
+            frame_data.extend_from_slice(&image_data);
+            self.write_chunk(b"fdAT", &frame_data)
+        }
+    }
+
+    fn next_sequence_number(&mut self) -> u32 {
+        self.sequence_number += 1;
+        self.sequence_number - 1
+    }
+
+    fn write_chunk(&mut self, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+        self.writer.write_all(&(data.len() as u32).to_be_bytes())?;
+        self.writer.write_all(kind)?;
+        self.writer.write_all(data)?;
+        let crc = crc32(kind.iter().chain(data));
+        self.writer.write_all(&crc.to_be_bytes())
+    }
+}
+
+/// Frame rate as an integer ratio for the Y4M header: NTSC rates such as 29.97 become
+/// 30000/1001, other rates the smallest fraction within a millionth of them.
+fn frame_rate_ratio(fps: f64) -> (u64, u64) {
+    let ntsc = (fps * 1.001).round();
+    if fps.fract() != 0.0 && (ntsc * 1000.0 / 1001.0 - fps).abs() < 0.0005 {
+        return (ntsc as u64 * 1000, 1001);
+    }
+    let denominator = (1..=1000).find(|&denominator| {
+        let numerator = fps * denominator as f64;
+        (numerator - numerator.round()).abs() < 1e-6 * numerator.max(1.0)
+    });
+    match denominator {
+        Some(denominator) => ((fps * denominator as f64).round() as u64, denominator),
+        None => ((fps * 1000.0).round() as u64, 1000),
+    }
+}
+
+/// Frame duration in seconds as the 16-bit fraction stored by APNG.
+fn frame_delay(fps: f64) -> (u16, u16) {
+    if fps.fract() == 0.0 && fps <= u16::MAX as f64 {
+        (1, fps as u16)
+    } else {
+        ((1000.0 / fps).round().clamp(1.0, u16::MAX as f64) as u16, 1000)
+    }
+}
+
+/// Wraps data in a zlib stream of stored deflate blocks, trading file size for not needing
+/// a compressor.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let block_count = data.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    let mut stream = Vec::with_capacity(data.len() + block_count * 5 + 6);
+    stream.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let length = block.len() as u16;
+        stream.push(last as u8);
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // Sums stay below u32::MAX for chunks of this size before reducing.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+    (b << 16) | a
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::framebuffer::Tile;
+    use crate::math::color::Color;
+    use std::fs;
+    use std::io::Cursor;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("raytracer-{}-{}", std::process::id(), name)).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"123456789".iter()), 0xcbf4_3926);
+        assert_eq!(crc32(b"".iter()), 0);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(b""), 1);
+        // Long enough to need the periodic reduction.
+        let data = vec![0xff; 100_000];
+        let (a, b) = data.iter().fold((1u64, 0u64), |(a, b), &byte| ((a + byte as u64) % 65521, (b + a + byte as u64) % 65521));
+        assert_eq!(adler32(&data), ((b << 16) | a) as u32);
+    }
+
+    #[test]
+    fn frame_rates_become_exact_ratios() {
+        assert_eq!(frame_rate_ratio(24.0), (24, 1));
+        assert_eq!(frame_rate_ratio(12.5), (25, 2));
+        assert_eq!(frame_rate_ratio(29.97), (30000, 1001));
+        assert_eq!(frame_rate_ratio(23.976), (24000, 1001));
+        assert_eq!(frame_rate_ratio(59.94), (60000, 1001));
+        assert_eq!(frame_rate_ratio(1.0 / 3.0), (1, 3));
+        assert_eq!(frame_delay(25.0), (1, 25));
+        assert_eq!(frame_delay(12.5), (80, 1000));
+    }
+
+    #[test]
+    fn stored_zlib_stream_splits_large_data_into_blocks() {
+        assert_eq!(zlib_stored(&[]), [0x78, 0x01, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 1]);
+        let data = vec![7; MAX_STORED_BLOCK + 10];
+        let stream = zlib_stored(&data);
+        assert_eq!(stream.len(), 2 + 2 * 5 + data.len() + 4);
+        assert_eq!(stream[2], 0); // First block is not the last
+        assert_eq!(stream[2 + 5 + MAX_STORED_BLOCK], 1);
+    }
+
+    #[test]
+    fn apng_decodes_with_every_written_frame() {
+        let path = temp_path("video.png");
+        let mut framebuffer = Framebuffer::new(3, 2);
+        let mut video = VideoWriter::create(&path, 3, 2, 12.5).unwrap();
+        video.write_frame(&framebuffer).unwrap();
+        let tile = &Tile::grid(3, 2, 8)[0];
+        let mut pixels = framebuffer.tile_pixels(tile);
+        pixels[1].add(Color::new(1.0, 0.0, 0.0));
+        framebuffer.store_tile(tile, pixels);
+        video.write_frame(&framebuffer).unwrap();
+        video.finish().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let mut reader = png::Decoder::new(Cursor::new(bytes)).read_info().unwrap();
+        let animation = reader.info().animation_control.unwrap();
+        assert_eq!(animation.num_frames, 2);
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut buffer).unwrap();
+        assert!(buffer.iter().all(|&byte| byte == 0));
+        reader.next_frame(&mut buffer).unwrap();
+        assert_eq!(&buffer[..9], &[0, 0, 0, 255, 0, 0, 0, 0, 0]);
+        assert_eq!(reader.info().frame_control.unwrap().delay_num, 80);
+    }
+
+    #[test]
+    fn y4m_header_uses_the_exact_frame_rate() {
+        let path = temp_path("video.y4m");
+        let mut video = VideoWriter::create(&path, 2, 1, 29.97).unwrap();
+        video.write_frame(&Framebuffer::new(2, 1)).unwrap();
+        video.finish().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let header = "YUV4MPEG2 W2 H1 F30000:1001 Ip A1:1 C444\nFRAME\n";
+        assert!(bytes.starts_with(header.as_bytes()));
+        // Black is limited-range luma 16 with neutral chroma.
+        assert_eq!(&bytes[header.len()..], &[16, 16, 128, 128, 128, 128]);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::core::animation::CameraAnimation;
 use crate::core::camera::Camera;
 use crate::core::hittable::{Translate, RotateY};
 use crate::core::hittable_list::HittableList;
@@ -6,11 +7,13 @@ use crate::materials::lambertian::Lambertian;
 use crate::materials::diffuse_light::DiffuseLight;
 use crate::math::bvh_node::BvhNode;
 use crate::math::color::Color;
+use crate::math::keyframes::{Keyframe, Track};
 use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
 use crate::sdf::quad::{make_box, Quad};
-use std::fs::File;
-use std::io::BufWriter;
+use crate::utils::options::RenderOptions;
 use std::sync::Arc;
+use std::time::Instant;
 
 const OUTPUT_FILE: &str = "renders/cornell_box.ppm";
 const RED: Color = Color::new(0.65, 0.05, 0.05);
@@ -22,7 +25,8 @@ static white: Lambertian = Lambertian { albedo: WHITE };
 static green:Lambertian = Lambertian { albedo: GREEN };
 static light:DiffuseLight = DiffuseLight { emit: LIGHT };
 
-pub fn cornell_box() {
+pub fn cornell_box(options: &RenderOptions) {
+    let build_start = Instant::now();
     let mut camera = Camera::new();
     let mut world = HittableList::new();
 
@@ -73,13 +77,25 @@ pub fn cornell_box() {
     camera.look_at = Point3::new(278.0, 273.0, 0.0);
     camera.up = Vec3::new(0.0, 1.0, 0.0);
 
-    // File
-    let file = File::create(OUTPUT_FILE).unwrap();
-    let mut writer = BufWriter::new(file);
+    options.apply(&mut camera);
+
+    // Animation: a slow dolly toward the box while widening the view
+    let animation = CameraAnimation {
+        eye: Some(Track::bezier(vec![
+            Keyframe { time: 0.0, value: camera.eye },
+            Keyframe { time: 4.0, value: Point3::new(278.0, 273.0, -600.0) },
+        ])),
+        vfov: Some(Track::bezier(vec![
+            Keyframe { time: 0.0, value: camera.vfov },
+            Keyframe { time: 4.0, value: 50.0 },
+        ])),
+        ..CameraAnimation::default()
+    };
 
     let bvh_node = BvhNode::new_from_list(world.objects.clone());
     let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
 
     // Render
-    camera.render(&world, &mut writer).unwrap();
+    render_scene(&mut camera, &animation, &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
 }
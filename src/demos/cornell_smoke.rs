@@ -0,0 +1,73 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable::{Translate, RotateY};
+use crate::core::hittable_list::HittableList;
+use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::isotropic::Isotropic;
+use crate::materials::lambertian::Lambertian;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::quad::{make_box, Quad};
+use crate::utils::options::RenderOptions;
+use crate::volumes::constant_medium::ConstantMedium;
+use std::sync::Arc;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/cornell_smoke.ppm";
+static RED: Lambertian = Lambertian { albedo: Color::new(0.65, 0.05, 0.05) };
+static WHITE: Lambertian = Lambertian { albedo: Color::new(0.73, 0.73, 0.73) };
+static GREEN: Lambertian = Lambertian { albedo: Color::new(0.12, 0.45, 0.15) };
+static LIGHT: DiffuseLight = DiffuseLight { emit: Color::new(7.0, 7.0, 7.0) };
+static DARK_SMOKE: Isotropic = Isotropic { albedo: Color::new(0.0, 0.0, 0.0) };
+static LIGHT_SMOKE: Isotropic = Isotropic { albedo: Color::new(1.0, 1.0, 1.0) };
+
+/// The Cornell box with its two boxes replaced by dark and light smoke.
+pub fn cornell_smoke(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    // Light: larger and dimmer than in the plain box, to light the smoke evenly
+    world.add(Quad::new(Point3::new(113.0, 554.0, 127.0), Vec3::new(330.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 305.0), &LIGHT));
+
+    // Walls
+    world.add(Quad::new(Point3::new(555.0, 0.0, 0.0), Vec3::new(0.0, 555.0, 0.0), Vec3::new(0.0, 0.0, 555.0), &RED));
+    world.add(Quad::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 555.0, 0.0), Vec3::new(0.0, 0.0, 555.0), &GREEN));
+    world.add(Quad::new(Point3::new(0.0, 555.0, 0.0), Vec3::new(555.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 555.0), &WHITE));
+    world.add(Quad::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(555.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 555.0), &WHITE));
+    world.add(Quad::new(Point3::new(0.0, 0.0, 555.0), Vec3::new(555.0, 0.0, 0.0), Vec3::new(0.0, 555.0, 0.0), &WHITE));
+
+    // Smoke boxes
+    let box1 = make_box(Point3::new(0.0, 0.0, 0.0), Point3::new(165.0, 330.0, 165.0), &WHITE);
+    let box1 = RotateY::new(Arc::new(box1), 15.0);
+    let box1 = Translate::new(Arc::new(box1), Vec3::new(265.0, 0.0, 295.0));
+
+    let box2 = make_box(Point3::new(0.0, 0.0, 0.0), Point3::new(165.0, 165.0, 165.0), &WHITE);
+    let box2 = RotateY::new(Arc::new(box2), -18.0);
+    let box2 = Translate::new(Arc::new(box2), Vec3::new(130.0, 0.0, 65.0));
+
+    world.add(ConstantMedium::new(Arc::new(box1), 0.01, &DARK_SMOKE));
+    world.add(ConstantMedium::new(Arc::new(box2), 0.01, &LIGHT_SMOKE));
+
+    // Camera settings
+    camera.aspect_ratio = 1.0;
+    camera.image_width = 600;
+    camera.samples_per_pixel = 200;
+    camera.background = Color::new(0.0, 0.0, 0.0);
+
+    camera.vfov = 40.0;
+    camera.eye = Point3::new(278.0, 278.0, -800.0);
+    camera.look_at = Point3::new(278.0, 278.0, 0.0);
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
@@ -0,0 +1,100 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable::{Hittable, RotateY, Translate};
+use crate::core::hittable_list::HittableList;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metal::Metal;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::csg::Csg;
+use crate::sdf::quad::make_box;
+use crate::sdf::plane::Plane;
+use crate::sdf::sphere::Sphere;
+use crate::utils::options::RenderOptions;
+use std::sync::Arc;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/csg.ppm";
+static GROUND: Lambertian = Lambertian { albedo: Color::new(0.5, 0.5, 0.5) };
+static IVORY: Lambertian = Lambertian { albedo: Color::new(0.85, 0.82, 0.75) };
+static RED: Lambertian = Lambertian { albedo: Color::new(0.7, 0.15, 0.1) };
+static GOLD: Metal = Metal { albedo: Color::new(0.85, 0.65, 0.3), fuzz: 0.1 };
+static GLASS: Dielectric = Dielectric { ref_idx: 1.5 };
+
+/// Shapes built from spheres and boxes with boolean operations: a glass lens, a hollow shell
+/// cut open, a die with drilled pips and merged glass bubbles.
+pub fn csg(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    world.add(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &GROUND));
+
+    // Lens: where two large spheres overlap
+    let lens = Csg::intersection(
+        Arc::new(Sphere::new(Point3::new(0.0, 0.0, -1.6), 2.0, &GLASS)),
+        Arc::new(Sphere::new(Point3::new(0.0, 0.0, 1.6), 2.0, &GLASS)),
+    );
+    world.add(Translate::new(Arc::new(RotateY::new(Arc::new(lens), 60.0)), Vec3::new(-3.0, 1.25, 0.0)));
+
+    // Hollow shell with a box cut out of its front to show the inside
+    let shell = Csg::difference(
+        Arc::new(Sphere::new(Point3::default(), 1.0, &GOLD)),
+        Arc::new(Sphere::new(Point3::default(), 0.85, &GOLD)),
+    );
+    let cut = make_box(Point3::new(-0.6, -0.6, -1.5), Point3::new(1.5, 1.5, 0.0), &GOLD);
+    let open_shell = Csg::difference(Arc::new(shell), Arc::new(cut));
+    world.add(Translate::new(Arc::new(RotateY::new(Arc::new(open_shell), -30.0)), Vec3::new(-1.0, 1.0, 0.5)));
+
+    // Die: a box with rounded corners from a sphere, with pips drilled into three faces
+    let mut die: Arc<dyn Hittable + Send + Sync> = Arc::new(Csg::intersection(
+        Arc::new(make_box(Point3::new(-0.6, -0.6, -0.6), Point3::new(0.6, 0.6, 0.6), &IVORY)),
+        Arc::new(Sphere::new(Point3::default(), 0.82, &IVORY)),
+    ));
+    let pips = [
+        Point3::new(0.0, 0.6, 0.0),
+        Point3::new(-0.3, 0.3, -0.6),
+        Point3::new(0.3, -0.3, -0.6),
+        Point3::new(-0.6, 0.3, -0.3),
+        Point3::new(-0.6, 0.0, 0.0),
+        Point3::new(-0.6, -0.3, 0.3),
+    ];
+    for pip in pips {
+        die = Arc::new(Csg::difference(die, Arc::new(Sphere::new(pip, 0.12, &RED))));
+    }
+    world.add(Translate::new(Arc::new(RotateY::new(die, -35.0)), Vec3::new(1.0, 0.6, 0.0)));
+
+    // Bubbles: a union of overlapping glass spheres refracts as one solid, with no
+    // surfaces left inside
+    let bubbles = Csg::union(
+        Arc::new(Csg::union(
+            Arc::new(Sphere::new(Point3::new(0.0, 0.6, 0.0), 0.6, &GLASS)),
+            Arc::new(Sphere::new(Point3::new(0.45, 0.4, -0.2), 0.4, &GLASS)),
+        )),
+        Arc::new(Sphere::new(Point3::new(-0.2, 1.15, 0.1), 0.35, &GLASS)),
+    );
+    world.add(Translate::new(Arc::new(bubbles), Vec3::new(3.0, 0.0, 0.0)));
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 100;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+
+    camera.vfov = 30.0;
+    camera.eye = Point3::new(0.0, 3.0, -10.0);
+    camera.look_at = Point3::new(0.0, 0.7, 0.0);
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
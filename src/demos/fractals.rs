@@ -0,0 +1,66 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable::{RotateY, Translate};
+use crate::core::hittable_list::HittableList;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::orbit_trap::OrbitTrapGradient;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::distance::{Sdf, SphereTraced};
+use crate::sdf::fractals::{Mandelbox, Mandelbulb, MengerSponge, QuaternionJulia, SierpinskiTetrahedron};
+use crate::sdf::operators::Scale;
+use crate::sdf::shapes::PlaneSdf;
+use crate::utils::options::RenderOptions;
+use std::sync::Arc;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/fractals.ppm";
+static GROUND: Lambertian = Lambertian { albedo: Color::new(0.5, 0.5, 0.5) };
+static FIRE: OrbitTrapGradient = OrbitTrapGradient { near: Color::new(0.9, 0.8, 0.2), far: Color::new(0.6, 0.1, 0.05), range: 1.0 };
+static SEA: OrbitTrapGradient = OrbitTrapGradient { near: Color::new(0.9, 0.9, 0.9), far: Color::new(0.05, 0.3, 0.6), range: 1.2 };
+static MOSS: OrbitTrapGradient = OrbitTrapGradient { near: Color::new(0.1, 0.3, 0.1), far: Color::new(0.7, 0.8, 0.4), range: 1.5 };
+static DUSK: OrbitTrapGradient = OrbitTrapGradient { near: Color::new(0.8, 0.3, 0.6), far: Color::new(0.2, 0.1, 0.5), range: 1.5 };
+static ICE: OrbitTrapGradient = OrbitTrapGradient { near: Color::new(0.2, 0.6, 0.8), far: Color::new(0.9, 0.95, 1.0), range: 0.8 };
+
+/// The fractal distance estimators side by side, colored by their orbit traps.
+pub fn fractals(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    world.add(SphereTraced::new(PlaneSdf { normal: Vec3::new(0.0, 1.0, 0.0), offset: 0.0 }, &GROUND));
+
+    place(&mut world, SphereTraced::new(Mandelbulb { power: 8.0, iterations: 12 }, &FIRE), Vec3::new(0.0, 1.1, 2.5), 20.0);
+    let mandelbox = Scale { sdf: Mandelbox { scale: 2.0, iterations: 20 }, factor: 0.25 };
+    place(&mut world, SphereTraced::new(mandelbox, &SEA), Vec3::new(-3.5, 1.5, 4.5), 30.0);
+    place(&mut world, SphereTraced::new(MengerSponge { iterations: 5 }, &MOSS), Vec3::new(3.5, 1.0, 4.0), -30.0);
+    place(&mut world, SphereTraced::new(SierpinskiTetrahedron { iterations: 6 }, &DUSK), Vec3::new(-2.2, 1.0, -0.5), 15.0);
+    let julia = QuaternionJulia { c: [-0.2, 0.6, 0.2, 0.2], slice: 0.0, iterations: 12 };
+    place(&mut world, SphereTraced::new(julia, &ICE), Vec3::new(2.2, 1.0, -0.5), 0.0);
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 100;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+
+    camera.vfov = 40.0;
+    camera.eye = Point3::new(0.0, 4.0, -10.0);
+    camera.look_at = Point3::new(0.0, 1.0, 1.5);
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
+
+fn place<S: Sdf + 'static>(world: &mut HittableList, shape: SphereTraced<S>, position: Vec3, angle: f64) {
+    world.add(Translate::new(Arc::new(RotateY::new(Arc::new(shape), angle)), position));
+}
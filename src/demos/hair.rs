@@ -0,0 +1,87 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable_list::HittableList;
+use crate::materials::hair::Hair;
+use crate::materials::lambertian::Lambertian;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::curve::{Curve, CurveBasis, CurveMode, CurveSet};
+use crate::sdf::plane::Plane;
+use crate::sdf::sphere::Sphere;
+use crate::utils::common::{random, random_range};
+use crate::utils::options::RenderOptions;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/hair.ppm";
+static SOIL: Lambertian = Lambertian { albedo: Color::new(0.3, 0.22, 0.15) };
+static SKIN: Lambertian = Lambertian { albedo: Color::new(0.25, 0.18, 0.14) };
+static GRASS: Lambertian = Lambertian { albedo: Color::new(0.2, 0.45, 0.1) };
+static BLOND: Hair = Hair::from_melanin(0.3, 0.0, 0.3, 0.3);
+static AUBURN: Hair = Hair::from_melanin(0.6, 1.5, 0.25, 0.3);
+
+/// Two furry balls with hair in tubes on a lawn of ribbon grass blades.
+pub fn hair(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    world.add(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &SOIL));
+
+    // Fur balls: B-spline strands out of the upper part of each ball, drooping under gravity
+    for (center, radius, material) in [(Point3::new(-1.1, 0.7, 0.0), 0.6, &BLOND), (Point3::new(1.1, 0.7, 0.3), 0.6, &AUBURN)] {
+        world.add(Sphere::new(center, radius, &SKIN));
+        let mut strands = Vec::new();
+        for _ in 0..15000 {
+            let normal = Vec3::random_unit_vector();
+            if normal.y < -0.3 {
+                continue;
+            }
+            let length = random_range(0.4, 0.6);
+            let points: Vec<Point3> = (0..6).map(|i| {
+                let s = length * i as f64 / 5.0;
+                let droop = Vec3::new(0.0, -1.2 * s * s, 0.0);
+                center + normal * (radius - 0.02 + s) + droop
+            }).collect();
+            strands.extend(Curve::strand(CurveBasis::BSpline, &points, [0.01, 0.003], CurveMode::Tube, material));
+        }
+        world.add(CurveSet::new(strands, 2));
+    }
+
+    // Grass: tapering Bezier blades, bent over at random
+    let mut blades = Vec::new();
+    for _ in 0..20000 {
+        let root = Point3::new(random_range(-4.0, 4.0), 0.0, random_range(-2.0, 3.0));
+        let height = random_range(0.15, 0.35);
+        let lean = Vec3::new(random_range(-1.0, 1.0), 0.0, random_range(-1.0, 1.0)) * (height * 0.5 * random());
+        let control = [
+            root,
+            root + Vec3::new(0.0, height * 0.5, 0.0),
+            root + Vec3::new(0.0, height, 0.0) + lean * 0.5,
+            root + Vec3::new(0.0, height * 0.9, 0.0) + lean,
+        ];
+        blades.push(Curve::new(CurveBasis::Bezier, control, [0.02, 0.0], CurveMode::Ribbon, &GRASS));
+    }
+    world.add(CurveSet::new(blades, 1));
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 200;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+
+    camera.vfov = 30.0;
+    camera.eye = Point3::new(0.0, 1.6, -5.5);
+    camera.look_at = Point3::new(0.0, 0.6, 0.0);
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
@@ -0,0 +1,118 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable_list::HittableList;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metal::Metal;
+use crate::materials::vertex_color::VertexColor;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::mesh::TriangleMesh;
+use crate::sdf::plane::Plane;
+use crate::utils::common::PI;
+use crate::utils::options::RenderOptions;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/meshes.ppm";
+static GROUND: Lambertian = Lambertian { albedo: Color::new(0.5, 0.5, 0.5) };
+static CLAY: Lambertian = Lambertian { albedo: Color::new(0.75, 0.45, 0.3) };
+static STEEL: Metal = Metal { albedo: Color::new(0.8, 0.8, 0.85), fuzz: 0.1 };
+static PAINT: VertexColor = VertexColor { tint: Color::new(1.0, 1.0, 1.0) };
+
+/// Triangle meshes: a prism with generated normals, flat and with a crease angle that keeps
+/// its rims sharp, and a sphere with vertex colors. A file given with --mesh replaces them.
+pub fn meshes(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    world.add(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &GROUND));
+
+    match &options.mesh_path {
+        Some(path) => {
+            let mesh = match TriangleMesh::load(path, 30.0, &PAINT) {
+                Ok(mesh) => mesh.fit(Point3::new(0.0, 1.0, 0.0), 2.0),
+                Err(error) => {
+                    eprintln!("Failed to load {}: {}", path, error);
+                    return;
+                }
+            };
+            println!("Loaded {} triangles from {}", mesh.triangle_count(), path);
+            world.add(mesh);
+        }
+        None => {
+            let (positions, triangles) = prism(Point3::new(-2.2, 0.0, 0.0), 0.8, 1.4, 12);
+            world.add(TriangleMesh::new(positions, triangles, &CLAY));
+            let (positions, triangles) = prism(Point3::new(0.0, 0.0, 0.0), 0.8, 1.4, 12);
+            world.add(TriangleMesh::new(positions, triangles, &STEEL).with_crease_angle(45.0));
+
+            let (positions, triangles) = sphere(Point3::new(2.2, 0.9, 0.0), 0.9, 48, 24);
+            let colors = positions.iter().map(|p| {
+                let angle = (p.z).atan2(p.x - 2.2);
+                Color::new(0.5 + 0.5 * angle.cos(), 0.5 + 0.5 * (angle + 2.0 * PI / 3.0).cos(), 0.5 + 0.5 * (angle - 2.0 * PI / 3.0).cos())
+            }).collect();
+            world.add(TriangleMesh::new(positions, triangles, &PAINT).with_crease_angle(60.0).with_colors(colors));
+        }
+    }
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 100;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+
+    camera.vfov = 35.0;
+    camera.eye = Point3::new(0.0, 3.0, -7.5);
+    camera.look_at = Point3::new(0.0, 0.8, 0.0);
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
+
+/// Upright prism with `sides` around, capped at both ends, sharing the rim vertices between
+/// the sides and the caps.
+fn prism(base: Point3, radius: f64, height: f64, sides: u32) -> (Vec<Point3>, Vec<[u32; 3]>) {
+    let mut positions = vec![base, base + Vec3::new(0.0, height, 0.0)];
+    for i in 0..sides {
+        let angle = 2.0 * PI * i as f64 / sides as f64;
+        let rim = base + Vec3::new(radius * angle.cos(), 0.0, radius * angle.sin());
+        positions.extend([rim, rim + Vec3::new(0.0, height, 0.0)]);
+    }
+    let mut triangles = Vec::new();
+    for i in 0..sides {
+        let (bottom, top) = (2 + 2 * i, 3 + 2 * i);
+        let (next_bottom, next_top) = (2 + 2 * ((i + 1) % sides), 3 + 2 * ((i + 1) % sides));
+        triangles.extend([[bottom, top, next_top], [bottom, next_top, next_bottom], [0, bottom, next_bottom], [1, next_top, top]]);
+    }
+    (positions, triangles)
+}
+
+/// Latitude-longitude sphere.
+fn sphere(center: Point3, radius: f64, segments: u32, rings: u32) -> (Vec<Point3>, Vec<[u32; 3]>) {
+    let mut positions = Vec::new();
+    for ring in 0..=rings {
+        let theta = PI * ring as f64 / rings as f64;
+        for segment in 0..segments {
+            let phi = 2.0 * PI * segment as f64 / segments as f64;
+            positions.push(center + Vec3::new(theta.sin() * phi.cos(), -theta.cos(), theta.sin() * phi.sin()) * radius);
+        }
+    }
+    let mut triangles = Vec::new();
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let next = (segment + 1) % segments;
+            let (a, b) = (ring * segments + segment, ring * segments + next);
+            let (c, d) = (a + segments, b + segments);
+            triangles.extend([[a, c, d], [a, d, b]]);
+        }
+    }
+    (positions, triangles)
+}
@@ -1,2 +1,71 @@
 pub mod cornell_box;
-pub mod spheres;
\ No newline at end of file
+pub mod cornell_smoke;
+pub mod csg;
+pub mod fractals;
+pub mod hair;
+pub mod meshes;
+pub mod point_cloud;
+pub mod primitives;
+pub mod scene;
+pub mod sdf_operators;
+pub mod sdf_shapes;
+pub mod spheres;
+pub mod terrain;
+pub mod volumes;
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+
+use crate::core::animation::{numbered_path, render_sequence, CameraAnimation};
+use crate::core::camera::Camera;
+use crate::core::hittable_list::HittableList;
+use crate::core::progress::{CancellationToken, TerminalProgress};
+use crate::core::video::VideoWriter;
+use crate::utils::options::RenderOptions;
+
+/// Renders a demo scene to `output_file`, or to numbered files per frame when the options ask
+/// for a frame range (a single video file if one was given), and writes the requested statistics.
+pub fn render_scene(
+    camera: &mut Camera,
+    animation: &CameraAnimation,
+    world: &HittableList,
+    options: &RenderOptions,
+    output_file: &str,
+    scene_build_seconds: f64,
+) -> io::Result<()> {
+    let Some(frames) = options.frames else {
+        let file = File::create(output_file)?;
+        let mut writer = BufWriter::new(file);
+        camera.render(world, &mut writer)?;
+        camera.stats.scene_build_seconds = scene_build_seconds;
+        options.write_stats(&camera.stats, None);
+        return Ok(());
+    };
+
+    let progress = TerminalProgress::new();
+    let mut video: Option<VideoWriter> = None;
+    let rendered = render_sequence(camera, animation, world, &frames, &progress, &CancellationToken::new(), |frame, framebuffer, stats| {
+        if let Some(path) = &options.video_path {
+            let video = match &mut video {
+                Some(video) => video,
+                None => video.insert(VideoWriter::create(path, framebuffer.width, framebuffer.height, frames.fps)?),
+            };
+            video.write_frame(framebuffer)?;
+            println!("Frame {} added to {}", frame, path);
+        } else {
+            let path = numbered_path(output_file, frame);
+            let mut writer = BufWriter::new(File::create(&path)?);
+            framebuffer.write_ppm(&mut writer)?;
+            println!("Frame {} written to {}", frame, path);
+        }
+
+        let mut stats = stats.clone();
+        stats.scene_build_seconds = scene_build_seconds;
+        options.write_stats(&stats, Some(frame));
+        Ok(())
+    });
+
+    // Close the video even after a failed frame, so the frames before it stay playable.
+    let finished = video.map_or(Ok(()), VideoWriter::finish);
+    rendered.and(finished)
+}
@@ -0,0 +1,106 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable::Hittable;
+use crate::core::hittable_list::HittableList;
+use crate::materials::vertex_color::VertexColor;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::point_cloud::{ParticleShape, Particles, PointCloud};
+use crate::utils::common::{random, random_range, PI};
+use crate::utils::options::RenderOptions;
+use crate::volumes::field::value_noise;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/point_cloud.ppm";
+static PAINT: VertexColor = VertexColor { tint: Color::new(1.0, 1.0, 1.0) };
+
+/// A million-point scan of a hillside, drawn as oriented discs colored by height, with a
+/// fountain of spherical particles. A file given with --points replaces the scan.
+pub fn point_cloud(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    match &options.points_path {
+        Some(path) => {
+            let particles = match Particles::load(path) {
+                Ok(particles) => particles,
+                Err(error) => {
+                    eprintln!("Failed to load {}: {}", path, error);
+                    return;
+                }
+            };
+            let shape = if particles.normals.is_empty() { ParticleShape::Sphere } else { ParticleShape::Disc };
+            let cloud = PointCloud::new(particles, 0.01, shape, &PAINT);
+            // Frame the whole cloud, seen from above one side.
+            let bounds = cloud.bounding_box();
+            let size = bounds.x.size().max(bounds.y.size()).max(bounds.z.size());
+            camera.look_at = Point3::new((bounds.x.min + bounds.x.max) / 2.0, (bounds.y.min + bounds.y.max) / 2.0, (bounds.z.min + bounds.z.max) / 2.0);
+            camera.eye = camera.look_at + Vec3::new(0.3, 0.5, -1.0) * (1.6 * size);
+            world.add(cloud);
+        }
+        None => {
+            // Scan: jittered samples of a noise hillside with normals from its slope
+            let height = |x: f64, z: f64| 1.2 * value_noise(x * 0.4, 0.0, z * 0.4, 5) + 0.2 * value_noise(x * 2.0, 0.0, z * 2.0, 6);
+            let mut scan = Particles::default();
+            let spacing = 0.01;
+            for i in 0..1000 {
+                for j in 0..1000 {
+                    let x = -5.0 + (i as f64 + random()) * spacing;
+                    let z = -5.0 + (j as f64 + random()) * spacing;
+                    let y = height(x, z);
+                    let slope_x = (height(x + 0.005, z) - height(x - 0.005, z)) / 0.01;
+                    let slope_z = (height(x, z + 0.005) - height(x, z - 0.005)) / 0.01;
+                    let normal = Vec3::unit_vector(Vec3::new(-slope_x, 1.0, -slope_z));
+                    let grass = Color::new(0.15, 0.4, 0.1);
+                    let rock = Color::new(0.45, 0.35, 0.25);
+                    let blend = ((y - 0.4) / 0.8).clamp(0.0, 1.0);
+                    let color = grass * (1.0 - blend) + rock * blend;
+                    scan.positions.push([x as f32, y as f32, z as f32]);
+                    scan.normals.push([normal.x as f32, normal.y as f32, normal.z as f32]);
+                    scan.colors.push([color.x as f32, color.y as f32, color.z as f32]);
+                }
+            }
+            world.add(PointCloud::new(scan, 1.5 * spacing, ParticleShape::Disc, &PAINT));
+
+            // Fountain: particles thrown up and out at random times along their arcs
+            let source = Point3::new(0.0, height(0.0, 0.0), 0.0);
+            let mut drops = Particles::default();
+            for _ in 0..50000 {
+                let angle = 2.0 * PI * random();
+                let speed = random_range(0.8, 1.0);
+                let velocity = Vec3::new(1.3 * angle.cos(), 3.2, 1.3 * angle.sin()) * speed;
+                let time = random_range(0.0, 0.65);
+                let position = source + velocity * time + Vec3::new(0.0, -4.9 * time * time, 0.0);
+                let whiteness = time / 0.65;
+                drops.positions.push([position.x as f32, position.y as f32, position.z as f32]);
+                drops.radii.push(random_range(0.006, 0.012) as f32);
+                drops.colors.push([(0.2 + 0.8 * whiteness) as f32, (0.5 + 0.5 * whiteness) as f32, 1.0]);
+            }
+            world.add(PointCloud::new(drops, 0.01, ParticleShape::Sphere, &PAINT));
+
+            camera.eye = Point3::new(1.5, 2.2, -3.5);
+            camera.look_at = Point3::new(0.0, 1.0, 0.0);
+        }
+    }
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 100;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+
+    camera.vfov = 40.0;
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
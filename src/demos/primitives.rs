@@ -0,0 +1,89 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable::{RotateY, Translate};
+use crate::core::hittable_list::HittableList;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metal::Metal;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::cone::Cone;
+use crate::sdf::csg::Csg;
+use crate::sdf::cylinder::Cylinder;
+use crate::sdf::disk::{Disk, Ellipse};
+use crate::sdf::polygon::Polygon;
+use crate::sdf::quad::make_box;
+use crate::sdf::plane::Plane;
+use crate::sdf::torus::Torus;
+use crate::sdf::triangle::Triangle;
+use crate::utils::options::RenderOptions;
+use std::sync::Arc;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/primitives.ppm";
+static GROUND: Lambertian = Lambertian { albedo: Color::new(0.5, 0.5, 0.5) };
+static RED: Lambertian = Lambertian { albedo: Color::new(0.7, 0.15, 0.1) };
+static ORANGE: Lambertian = Lambertian { albedo: Color::new(0.8, 0.45, 0.1) };
+static GREEN: Lambertian = Lambertian { albedo: Color::new(0.2, 0.55, 0.2) };
+static BLUE: Lambertian = Lambertian { albedo: Color::new(0.15, 0.3, 0.7) };
+static PURPLE: Lambertian = Lambertian { albedo: Color::new(0.45, 0.2, 0.6) };
+static IVORY: Lambertian = Lambertian { albedo: Color::new(0.85, 0.82, 0.75) };
+static STEEL: Metal = Metal { albedo: Color::new(0.8, 0.8, 0.85), fuzz: 0.05 };
+static GOLD: Metal = Metal { albedo: Color::new(0.85, 0.65, 0.3), fuzz: 0.2 };
+static GLASS: Dielectric = Dielectric { ref_idx: 1.5 };
+
+/// The analytic primitives: disks, ellipses, cylinders, cones, a torus, a triangle and a
+/// polygon, plus a box drilled through with a cylinder.
+pub fn primitives(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+    let up = Vec3::new(0.0, 1.0, 0.0);
+
+    world.add(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &GROUND));
+
+    // Back row: flat shapes standing up
+    world.add(Disk::new(Point3::new(-3.0, 0.7, 1.5), Vec3::new(0.3, 0.2, -1.0), 0.6, &RED));
+    world.add(Ellipse::new(Point3::new(-1.5, 0.6, 1.5), Vec3::new(0.7, 0.0, 0.2), Vec3::new(0.0, 0.5, 0.0), &ORANGE));
+    world.add(Triangle::new(Point3::new(-0.5, 0.0, 1.5), Point3::new(0.5, 0.0, 1.7), Point3::new(0.0, 1.2, 1.6), &GREEN));
+    let hexagon = (0..6).map(|i| {
+        let angle = i as f64 * std::f64::consts::PI / 3.0;
+        Point3::new(1.5 - 0.6 * angle.cos(), 0.65 + 0.6 * angle.sin(), 1.5)
+    }).collect();
+    world.add(Polygon::new(hexagon, &BLUE));
+    // Drilled box: a cylinder cut through a box
+    let drilled = Csg::difference(
+        Arc::new(make_box(Point3::new(-0.5, 0.0, -0.5), Point3::new(0.5, 1.0, 0.5), &IVORY)),
+        Arc::new(Cylinder::new(Point3::new(0.0, 0.5, -1.0), Vec3::new(0.0, 0.0, 2.0), 0.3, true, &IVORY)),
+    );
+    world.add(Translate::new(Arc::new(RotateY::new(Arc::new(drilled), 25.0)), Vec3::new(3.0, 0.0, 1.5)));
+
+    // Front row: solids
+    world.add(Cylinder::new(Point3::new(-3.0, 0.0, -0.5), up * 1.0, 0.4, true, &PURPLE));
+    world.add(Cylinder::new(Point3::new(-1.5, 0.0, -0.5), up * 0.8, 0.45, false, &STEEL));
+    world.add(Cone::new(Point3::new(0.0, 0.0, -0.5), up * 1.1, 0.5, 0.0, true, &ORANGE));
+    world.add(Cone::new(Point3::new(1.5, 0.0, -0.5), up * 0.8, 0.5, 0.25, true, &GLASS));
+    world.add(Torus::new(Point3::new(3.0, 0.5, -0.5), Vec3::new(0.0, 0.3, -1.0), 0.4, 0.15, &GOLD));
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 100;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+
+    camera.vfov = 30.0;
+    camera.eye = Point3::new(0.0, 3.0, -10.0);
+    camera.look_at = Point3::new(0.0, 0.6, 0.5);
+    camera.up = up;
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
@@ -0,0 +1,98 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable_list::HittableList;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metallic_roughness::MetallicRoughness;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::scene::Scene;
+use crate::sdf::plane::Plane;
+use crate::sdf::sphere::Sphere;
+use crate::utils::common::DEG_TO_RAD;
+use crate::utils::options::RenderOptions;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/scene.ppm";
+static GROUND: Lambertian = Lambertian { albedo: Color::new(0.5, 0.5, 0.5) };
+
+/// Scenes from other applications, given with --scene. Without one, spheres of the glTF
+/// metallic-roughness material: plastic and gold from smooth to rough, then glass and a
+/// glowing sphere.
+pub fn scene(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 100;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+    camera.vfov = 35.0;
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    let world = match &options.scene_path {
+        Some(path) => {
+            let scene = match Scene::load(path) {
+                Ok(scene) => scene,
+                Err(error) => {
+                    eprintln!("Failed to load {}: {}", path, error);
+                    return;
+                }
+            };
+            for warning in &scene.warnings {
+                eprintln!("Warning: {}", warning);
+            }
+            if scene.world.objects.is_empty() {
+                eprintln!("Nothing to render in {}", path);
+                return;
+            }
+            match &scene.camera {
+                Some(scene_camera) => scene_camera.apply(&mut camera),
+                None => {
+                    // Look at the whole scene from the front and a little above.
+                    let bbox = &scene.world.bbox;
+                    let center = Point3::new(bbox.x.min + bbox.x.max, bbox.y.min + bbox.y.max, bbox.z.min + bbox.z.max) / 2.0;
+                    let radius = Vec3::new(bbox.x.size(), bbox.y.size(), bbox.z.size()).length() / 2.0;
+                    let distance = 1.1 * radius / (camera.vfov * DEG_TO_RAD / 2.0).sin();
+                    camera.look_at = center;
+                    camera.eye = center + Vec3::unit_vector(Vec3::new(0.0, 0.4, 1.0)) * distance;
+                }
+            }
+            if let Some(background) = scene.background {
+                camera.background = background;
+            }
+            scene.world
+        }
+        None => {
+            let mut world = HittableList::new();
+            world.add(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &GROUND));
+            for i in 0..5 {
+                let roughness = i as f64 / 4.0;
+                let x = (i as f64 - 2.0) * 1.1;
+                let plastic = MetallicRoughness { base_color: Color::new(0.8, 0.1, 0.1), metallic: 0.0, roughness, ..Default::default() };
+                world.add(Sphere::new(Point3::new(x, 0.5, 0.6), 0.5, Box::leak(Box::new(plastic))));
+                let gold = MetallicRoughness { base_color: Color::new(1.0, 0.77, 0.34), metallic: 1.0, roughness, ..Default::default() };
+                world.add(Sphere::new(Point3::new(x, 0.5, -0.6), 0.5, Box::leak(Box::new(gold))));
+            }
+            let glass = MetallicRoughness { metallic: 0.0, roughness: 0.0, transmission: 1.0, ..Default::default() };
+            world.add(Sphere::new(Point3::new(-1.1, 0.5, -1.9), 0.5, Box::leak(Box::new(glass))));
+            let glow = MetallicRoughness { metallic: 0.0, emissive: Color::new(4.0, 2.0, 0.8), ..Default::default() };
+            world.add(Sphere::new(Point3::new(1.1, 0.5, -1.9), 0.5, Box::leak(Box::new(glow))));
+
+            camera.eye = Point3::new(0.0, 3.5, 7.0);
+            camera.look_at = Point3::new(0.0, 0.3, -0.4);
+            world
+        }
+    };
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
@@ -0,0 +1,84 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable::Translate;
+use crate::core::hittable_list::HittableList;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metal::Metal;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::distance::{Sdf, SphereTraced};
+use crate::sdf::operators::{Bend, Displace, Intersection, Offset, Onion, RepeatLimited, Round, Subtraction, Twist, Union};
+use crate::sdf::shapes::{BoxSdf, CapsuleSdf, PlaneSdf, SphereSdf};
+use crate::utils::options::RenderOptions;
+use std::sync::Arc;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/sdf_operators.ppm";
+static GROUND: Lambertian = Lambertian { albedo: Color::new(0.5, 0.5, 0.5) };
+static RED: Lambertian = Lambertian { albedo: Color::new(0.7, 0.15, 0.1) };
+static ORANGE: Lambertian = Lambertian { albedo: Color::new(0.8, 0.45, 0.1) };
+static GREEN: Lambertian = Lambertian { albedo: Color::new(0.2, 0.55, 0.2) };
+static BLUE: Lambertian = Lambertian { albedo: Color::new(0.15, 0.3, 0.7) };
+static PURPLE: Lambertian = Lambertian { albedo: Color::new(0.45, 0.2, 0.6) };
+static STONE: Lambertian = Lambertian { albedo: Color::new(0.6, 0.55, 0.5) };
+static STEEL: Metal = Metal { albedo: Color::new(0.8, 0.8, 0.85), fuzz: 0.05 };
+static GOLD: Metal = Metal { albedo: Color::new(0.85, 0.65, 0.3), fuzz: 0.2 };
+
+/// One shape for each SDF operator: blends, cuts, shells, noise, twists, bends and repetition.
+pub fn sdf_operators(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    world.add(SphereTraced::new(PlaneSdf { normal: Vec3::new(0.0, 1.0, 0.0), offset: 0.0 }, &GROUND));
+
+    let cube = |half: f64| BoxSdf { half_extents: Vec3::new(half, half, half) };
+
+    // Back row: blends and cuts
+    let blob = Union { a: SphereSdf { radius: 0.45 }, b: Offset { sdf: cube(0.35), offset: Vec3::new(0.0, -0.25, 0.0) }, smoothness: 0.3 };
+    place(&mut world, SphereTraced::new(blob, &RED), Vec3::new(-3.0, 0.6, 1.5));
+    let carved = Subtraction { a: Round { sdf: cube(0.4), radius: 0.1 }, b: SphereSdf { radius: 0.6 }, smoothness: 0.05 };
+    place(&mut world, SphereTraced::new(carved, &STEEL), Vec3::new(-1.0, 0.5, 1.5));
+    let lens = Intersection { a: SphereSdf { radius: 0.6 }, b: cube(0.45), smoothness: 0.1 };
+    place(&mut world, SphereTraced::new(lens, &GOLD), Vec3::new(1.0, 0.45, 1.5));
+    // A shell cut open by a plane to show the inside
+    let cut = PlaneSdf { normal: Vec3::new(0.0, 1.0, 0.0), offset: 0.1 };
+    let shell = Intersection { a: Onion { sdf: SphereSdf { radius: 0.55 }, thickness: 0.08 }, b: cut, smoothness: 0.0 };
+    place(&mut world, SphereTraced::new(shell, &ORANGE), Vec3::new(3.0, 0.45, 1.5));
+
+    // Front row: noise and deformations
+    let rock = Displace { sdf: SphereSdf { radius: 0.5 }, amplitude: 0.12, frequency: 3.0, octaves: 4, seed: 3 };
+    place(&mut world, SphereTraced::new(rock, &STONE), Vec3::new(-3.0, 0.55, -0.5));
+    let twisted = Twist { sdf: BoxSdf { half_extents: Vec3::new(0.3, 0.6, 0.3) }, rate: 1.5 };
+    place(&mut world, SphereTraced::new(twisted, &PURPLE), Vec3::new(-1.0, 0.6, -0.5));
+    let capsule = CapsuleSdf { a: Point3::new(-0.6, 0.0, 0.0), b: Point3::new(0.6, 0.0, 0.0), radius: 0.15 };
+    place(&mut world, SphereTraced::new(Bend { sdf: capsule, rate: 1.2 }, &GREEN), Vec3::new(1.0, 0.3, -0.5));
+    let grid = RepeatLimited { sdf: SphereSdf { radius: 0.12 }, period: Vec3::new(0.3, 0.3, 0.3), count: [1, 2, 1] };
+    place(&mut world, SphereTraced::new(grid, &BLUE), Vec3::new(3.0, 0.75, -0.5));
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 100;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+
+    camera.vfov = 30.0;
+    camera.eye = Point3::new(0.0, 4.0, -10.0);
+    camera.look_at = Point3::new(0.0, 0.5, 0.5);
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
+
+fn place<S: Sdf + 'static>(world: &mut HittableList, shape: SphereTraced<S>, position: Vec3) {
+    world.add(Translate::new(Arc::new(shape), position));
+}
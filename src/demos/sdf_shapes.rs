@@ -0,0 +1,81 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable::{RotateY, Translate};
+use crate::core::hittable_list::HittableList;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metal::Metal;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::distance::SphereTraced;
+use crate::sdf::shapes::{BoxSdf, CapsuleSdf, ConeSdf, CylinderSdf, PlaneSdf, RoundBoxSdf, SphereSdf, TorusSdf};
+use crate::utils::options::RenderOptions;
+use std::sync::Arc;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/sdf_shapes.ppm";
+static GROUND: Lambertian = Lambertian { albedo: Color::new(0.5, 0.5, 0.5) };
+static RED: Lambertian = Lambertian { albedo: Color::new(0.7, 0.15, 0.1) };
+static ORANGE: Lambertian = Lambertian { albedo: Color::new(0.8, 0.45, 0.1) };
+static GREEN: Lambertian = Lambertian { albedo: Color::new(0.2, 0.55, 0.2) };
+static BLUE: Lambertian = Lambertian { albedo: Color::new(0.15, 0.3, 0.7) };
+static STEEL: Metal = Metal { albedo: Color::new(0.8, 0.8, 0.85), fuzz: 0.05 };
+static GOLD: Metal = Metal { albedo: Color::new(0.85, 0.65, 0.3), fuzz: 0.2 };
+static GLASS: Dielectric = Dielectric { ref_idx: 1.5 };
+
+/// The signed distance field world, sphere traced, standing on an SDF ground plane.
+pub fn sdf_shapes(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    world.add(SphereTraced::new(PlaneSdf { normal: Vec3::new(0.0, 1.0, 0.0), offset: 0.0 }, &GROUND));
+
+    // Back row
+    world.add(Translate::new(
+        Arc::new(SphereTraced::new(BoxSdf { half_extents: Vec3::new(0.6, 0.6, 0.6) }, &RED)),
+        Vec3::new(-3.0, 0.6, 1.5),
+    ));
+    let round_box = SphereTraced::new(RoundBoxSdf { half_extents: Vec3::new(0.7, 0.5, 0.5), radius: 0.2 }, &STEEL);
+    world.add(Translate::new(Arc::new(RotateY::new(Arc::new(round_box), 30.0)), Vec3::new(-1.0, 0.5, 1.5)));
+    world.add(Translate::new(
+        Arc::new(SphereTraced::new(TorusSdf { major_radius: 0.6, minor_radius: 0.25 }, &GOLD)),
+        Vec3::new(1.0, 0.25, 1.5),
+    ));
+    world.add(Translate::new(
+        Arc::new(SphereTraced::new(SphereSdf { radius: 0.6 }, &GLASS)),
+        Vec3::new(3.0, 0.6, 1.5),
+    ));
+
+    // Front row
+    let capsule = CapsuleSdf { a: Point3::new(-0.5, 0.3, 0.0), b: Point3::new(0.5, 0.9, 0.0), radius: 0.3 };
+    world.add(Translate::new(Arc::new(SphereTraced::new(capsule, &GREEN)), Vec3::new(-2.0, 0.0, -0.5)));
+    world.add(Translate::new(
+        Arc::new(SphereTraced::new(CylinderSdf { half_height: 0.6, radius: 0.45 }, &BLUE)),
+        Vec3::new(0.0, 0.6, -0.5),
+    ));
+    let cone = ConeSdf { half_height: 0.6, bottom_radius: 0.55, top_radius: 0.15 };
+    world.add(Translate::new(Arc::new(SphereTraced::new(cone, &ORANGE)), Vec3::new(2.0, 0.6, -0.5)));
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 100;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+
+    camera.vfov = 30.0;
+    camera.eye = Point3::new(0.0, 4.0, -10.0);
+    camera.look_at = Point3::new(0.0, 0.5, 0.5);
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
@@ -1,26 +1,34 @@
 use crate::materials::lambertian::Lambertian;
 use crate::math::bvh_node::BvhNode;
+use crate::math::keyframes::{Keyframe, Track};
 use crate::math::vec3::{Point3, Vec3};
+use crate::sdf::plane::Plane;
 use crate::sdf::sphere::Sphere;
 use crate::core::hittable_list::HittableList;
+use crate::core::animation::CameraAnimation;
 use crate::core::camera::Camera;
 use crate::math::color::Color;
 use crate::core::material::Material;
-use std::fs::File;
-use std::io::BufWriter;
+use crate::demos::render_scene;
+use crate::utils::common::PI;
+use crate::utils::options::RenderOptions;
+use std::time::Instant;
 
 const OUTPUT_FILE: &str = "renders/spheres.ppm";
+// Small spheres in front of the big ones that bounce twice a second, for motion blur
+const BOUNCING: [Point3; 3] = [Point3 { x: 7.0, y: 0.2, z: 1.0 }, Point3 { x: 6.0, y: 0.2, z: 2.2 }, Point3 { x: 8.0, y: 0.2, z: 2.6 }];
 
-pub fn spheres() {
+pub fn spheres(options: &RenderOptions) {
     use crate::materials::metal::Metal;
     use crate::materials::dielectric::Dielectric;
     use crate::utils::common::{random, random_range};
 
+    let build_start = Instant::now();
     let mut world = HittableList::new();
 
     // Ground
     let ground_material = Box::leak(Box::new(Lambertian { albedo: Color::new(0.5, 0.5, 0.5) }));
-    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, &*ground_material as &dyn Material));
+    world.add(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &*ground_material as &dyn Material));
 
     // Random small spheres
     for a in -11..11 {
@@ -32,7 +40,8 @@ pub fn spheres() {
                 b as f64 + 0.9 * random(),
             );
 
-            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+            let clear = BOUNCING.iter().all(|&bouncing| (center - bouncing).length() > 0.9);
+            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 && clear {
                 if choose_mat < 0.8 {
                     // diffuse
                     let albedo = Color::random() * Color::random();
@@ -53,6 +62,18 @@ pub fn spheres() {
         }
     }
 
+    // Bouncing spheres, up and down every half second over the 8 second orbit below
+    for ground in BOUNCING {
+        let albedo = Color::random() * Color::random();
+        let sphere_material = Box::leak(Box::new(Lambertian { albedo }));
+        let height = random_range(0.3, 0.6);
+        let bounce = (0..=32).map(|step| {
+            let lift = if step % 2 == 1 { height } else { 0.0 };
+            Keyframe { time: step as f64 / 4.0, value: ground + Vec3::new(0.0, lift, 0.0) }
+        }).collect();
+        world.add(Sphere::moving(Track::new(bounce), 0.2, &*sphere_material as &dyn Material));
+    }
+
     // Three big spheres
     let material1 = Box::leak(Box::new(Dielectric { ref_idx: 1.5 }));
     world.add(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, &*material1 as &dyn Material));
@@ -66,6 +87,7 @@ pub fn spheres() {
     // BVH
     let bvh_node = BvhNode::new_from_list(world.objects.clone());
     let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
 
     // Camera
     let mut camera = Camera::new();
@@ -81,7 +103,16 @@ pub fn spheres() {
     camera.focus_distance = 10.0;
     camera.background = Color::new(0.70, 0.80, 1.00);
 
-    let file = File::create(OUTPUT_FILE).unwrap();
-    let mut writer = BufWriter::new(file);
-    camera.render(&world, &mut writer).unwrap();
+    options.apply(&mut camera);
+
+    // Animation: a turntable orbit around the look-at point, one revolution every 8 seconds
+    let radius = (camera.eye.x * camera.eye.x + camera.eye.z * camera.eye.z).sqrt();
+    let start_angle = camera.eye.z.atan2(camera.eye.x);
+    let orbit = (0..=12).map(|step| {
+        let angle = start_angle + PI * step as f64 / 6.0;
+        Keyframe { time: 8.0 * step as f64 / 12.0, value: Point3::new(radius * angle.cos(), camera.eye.y, radius * angle.sin()) }
+    }).collect();
+    let animation = CameraAnimation { eye: Some(Track::bezier(orbit)), ..CameraAnimation::default() };
+
+    render_scene(&mut camera, &animation, &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
 }
@@ -0,0 +1,76 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable_list::HittableList;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metal::Metal;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::heightfield::Heightfield;
+use crate::sdf::plane::Plane;
+use crate::utils::options::RenderOptions;
+use crate::volumes::field::value_noise;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/terrain.ppm";
+static ROCK: Lambertian = Lambertian { albedo: Color::new(0.55, 0.5, 0.42) };
+static WATER: Metal = Metal { albedo: Color::new(0.3, 0.45, 0.55), fuzz: 0.05 };
+
+/// Ridged noise mountains above a lake, as a 512 x 512 heightfield. A heightmap given with
+/// --heightmap replaces them.
+pub fn terrain(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    let corner = Point3::new(-10.0, 0.0, -10.0);
+    let size = Vec3::new(20.0, 6.0, 20.0);
+    match &options.heightmap_path {
+        Some(path) => match Heightfield::load(path, corner, size, &ROCK) {
+            Ok(heightfield) => world.add(heightfield),
+            Err(error) => {
+                eprintln!("Failed to load {}: {}", path, error);
+                return;
+            }
+        },
+        None => {
+            let mountains = |x: f64, z: f64| {
+                let mut sum = 0.0;
+                let mut amplitude = 0.5;
+                let mut frequency = 3.0;
+                for octave in 0..5 {
+                    // Folding the noise around its midpoint gives sharp ridges.
+                    let noise = value_noise(x * frequency, 0.0, z * frequency, 3 + octave);
+                    sum += amplitude * (1.0 - (2.0 * noise - 1.0).abs()).powi(2);
+                    amplitude *= 0.5;
+                    frequency *= 2.0;
+                }
+                // Lower towards the front so the lake fills the foreground.
+                (sum * sum * (0.2 + 1.1 * z)).clamp(0.0, 1.0)
+            };
+            world.add(Heightfield::from_fn(corner, size, [512, 512], mountains, &ROCK));
+        }
+    }
+    world.add(Plane::new(Point3::new(0.0, 0.4, 0.0), Vec3::new(0.0, 1.0, 0.0), &WATER));
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 100;
+    camera.background = Color::new(0.7, 0.8, 1.0);
+
+    camera.vfov = 45.0;
+    camera.eye = Point3::new(0.0, 5.0, -14.0);
+    camera.look_at = Point3::new(0.0, 1.0, 3.0);
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
@@ -0,0 +1,103 @@
+use crate::core::animation::CameraAnimation;
+use crate::core::camera::Camera;
+use crate::core::hittable::Translate;
+use crate::core::hittable_list::HittableList;
+use crate::materials::henyey_greenstein::HenyeyGreenstein;
+use crate::materials::lambertian::Lambertian;
+use crate::math::aabb::Aabb;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+use crate::demos::render_scene;
+use crate::sdf::quad::make_box;
+use crate::sdf::plane::Plane;
+use crate::sdf::sphere::Sphere;
+use crate::utils::options::RenderOptions;
+use crate::volumes::field::{load_grid, DenseGrid, DensityField, NoiseField};
+use crate::volumes::heterogeneous_medium::{Emission, HeterogeneousMedium};
+use std::sync::Arc;
+use std::time::Instant;
+
+const OUTPUT_FILE: &str = "renders/volumes.ppm";
+static GROUND: Lambertian = Lambertian { albedo: Color::new(0.4, 0.4, 0.45) };
+
+/// A noise cloud lit by the sky next to a fire burning in a voxel grid.
+pub fn volumes(options: &RenderOptions) {
+    let build_start = Instant::now();
+    let mut camera = Camera::new();
+    let mut world = HittableList::new();
+
+    world.add(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &GROUND));
+
+    // Cloud: forward-scattering noise inside a sphere, tracked with delta tracking. A grid
+    // given with --grid replaces it, standing on the ground in its own units.
+    let cloud_center = Point3::new(-1.5, 2.2, 0.0);
+    let phase = HenyeyGreenstein { albedo: Color::new(0.95, 0.95, 0.95), g: 0.6 };
+    match &options.grid_path {
+        Some(path) => {
+            let (density, bounds) = match load_grid(path) {
+                Ok(grid) => grid,
+                Err(error) => {
+                    eprintln!("Failed to load {}: {}", path, error);
+                    return;
+                }
+            };
+            let boundary = make_box(
+                Point3::new(bounds.x.min, bounds.y.min, bounds.z.min),
+                Point3::new(bounds.x.max, bounds.y.max, bounds.z.max),
+                &GROUND,
+            );
+            let cloud = HeterogeneousMedium::scattering(Arc::new(boundary), density, 6.0, phase);
+            let base = Point3::new((bounds.x.min + bounds.x.max) / 2.0, bounds.y.min, (bounds.z.min + bounds.z.max) / 2.0);
+            world.add(Translate::new(Arc::new(cloud), Point3::new(cloud_center.x, 0.0, cloud_center.z) - base));
+        }
+        None => {
+            let cloud_density = NoiseField { center: cloud_center, radius: 2.0, frequency: 1.2, octaves: 4, seed: 7 };
+            let boundary = Sphere::new(cloud_center, 2.0, &GROUND);
+            world.add(HeterogeneousMedium::scattering(Arc::new(boundary), Arc::new(cloud_density), 6.0, phase));
+        }
+    }
+
+    // Fire: a flame-shaped grid of soot that glows hotter towards its core, tracked with
+    // ratio tracking
+    let fire_min = Point3::new(1.6, 0.0, -0.7);
+    let fire_max = Point3::new(3.0, 2.8, 0.7);
+    let fire_center = (fire_min + fire_max) * 0.5;
+    let flicker = NoiseField { center: fire_center, radius: 4.0, frequency: 3.0, octaves: 3, seed: 11 };
+    let flame = move |p: Point3| {
+        let height = (p.y - fire_min.y) / (fire_max.y - fire_min.y);
+        let width = 0.7 * (1.0 - height).max(0.0).powf(0.7);
+        let distance = ((p.x - fire_center.x).powi(2) + (p.z - fire_center.z).powi(2)).sqrt();
+        let profile = (1.0 - distance / width.max(1e-6)).max(0.0);
+        profile * (0.4 + flicker.density(p))
+    };
+    let bounds = Aabb::from_points(&fire_min, &fire_max);
+    let soot = DenseGrid::from_fn(bounds.clone(), [32, 64, 32], &flame);
+    let temperature = DenseGrid::from_fn(bounds, [32, 64, 32], |p| {
+        let height = (p.y - fire_min.y) / (fire_max.y - fire_min.y);
+        flame(p).powi(2) * (1.0 - height)
+    });
+    let fire = HeterogeneousMedium::absorbing(Arc::new(make_box(fire_min, fire_max, &GROUND)), Arc::new(soot), 4.0)
+        .with_emission(Emission { field: Arc::new(temperature), color: Color::new(12.0, 4.0, 0.8) });
+    world.add(fire);
+
+    // Camera settings
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 800;
+    camera.samples_per_pixel = 200;
+    camera.background = Color::new(0.35, 0.45, 0.65);
+
+    camera.vfov = 35.0;
+    camera.eye = Point3::new(0.5, 2.5, -10.0);
+    camera.look_at = Point3::new(0.5, 1.6, 0.0);
+    camera.up = Vec3::new(0.0, 1.0, 0.0);
+
+    options.apply(&mut camera);
+
+    let bvh_node = BvhNode::new_from_list(world.objects.clone());
+    let world = HittableList::from_bvh(bvh_node);
+    let scene_build_seconds = build_start.elapsed().as_secs_f64();
+
+    // Render
+    render_scene(&mut camera, &CameraAnimation::default(), &world, options, OUTPUT_FILE, scene_build_seconds).unwrap();
+}
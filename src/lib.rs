@@ -0,0 +1,8 @@
+pub mod utils;
+pub mod math;
+pub mod core;
+pub mod materials;
+pub mod sdf;
+pub mod volumes;
+pub mod scene;
+pub mod demos;
@@ -1,29 +1,61 @@
-
-mod utils;
-mod math;
-mod core;
-mod materials;
-mod sdf;
-mod demos;
-
-use demos::cornell_box::cornell_box;
-use demos::spheres::spheres;
+use raytracer::demos::cornell_box::cornell_box;
+use raytracer::demos::cornell_smoke::cornell_smoke;
+use raytracer::demos::csg::csg;
+use raytracer::demos::fractals::fractals;
+use raytracer::demos::hair::hair;
+use raytracer::demos::meshes::meshes;
+use raytracer::demos::point_cloud::point_cloud;
+use raytracer::demos::primitives::primitives;
+use raytracer::demos::scene::scene;
+use raytracer::demos::sdf_operators::sdf_operators;
+use raytracer::demos::sdf_shapes::sdf_shapes;
+use raytracer::demos::spheres::spheres;
+use raytracer::demos::terrain::terrain;
+use raytracer::demos::volumes::volumes;
+use raytracer::utils::options::RenderOptions;
 use std::env;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        eprintln!("Usage: cargo run -- <demo_name>");
-        eprintln!("Available demos: cornell_box, spheres");
+        eprintln!("Usage: cargo run -- <demo_name> [options]");
+        eprintln!("Options: --time <seconds>, --noise <relative error>, --max-samples <count>, --stats <file.json>,");
+        eprintln!("         --projection <name>, --stereo <side-by-side|top-bottom>, --interocular <distance>, --convergence <parallel|toe-in|off-axis>,");
+        eprintln!("         --aperture-blades <count>, --aperture-mask <file.pgm>, --shutter <open> <close>,");
+        eprintln!("         --frames <start> <end>, --fps <rate>, --video <file.y4m|file.png>,");
+        eprintln!("         --grid <file.vol|file.svol> (volumes demo), --heightmap <file.pgm> (terrain demo),");
+        eprintln!("         --points <file.ply|file.xyz> (point_cloud demo), --mesh <file.ply|file.stl> (meshes demo),");
+        eprintln!("         --scene <file.gltf|file.glb|file.pbrt> (scene demo)");
+        eprintln!("Available demos: cornell_box, cornell_smoke, csg, fractals, hair, meshes, point_cloud, primitives, scene, sdf_operators, sdf_shapes, spheres, terrain, volumes");
         return;
     }
 
+    let options = match RenderOptions::parse(&args[2..]) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            return;
+        }
+    };
+
     match args[1].as_str() {
-        "cornell_box" => cornell_box(),
-        "spheres" => spheres(),
+        "cornell_box" => cornell_box(&options),
+        "cornell_smoke" => cornell_smoke(&options),
+        "csg" => csg(&options),
+        "fractals" => fractals(&options),
+        "hair" => hair(&options),
+        "meshes" => meshes(&options),
+        "point_cloud" => point_cloud(&options),
+        "primitives" => primitives(&options),
+        "scene" => scene(&options),
+        "sdf_operators" => sdf_operators(&options),
+        "sdf_shapes" => sdf_shapes(&options),
+        "spheres" => spheres(&options),
+        "terrain" => terrain(&options),
+        "volumes" => volumes(&options),
         _ => {
             eprintln!("Unknown demo: {}", args[1]);
-            eprintln!("Available demos: cornell_box, spheres");
+            eprintln!("Available demos: cornell_box, cornell_smoke, csg, fractals, hair, meshes, point_cloud, primitives, scene, sdf_operators, sdf_shapes, spheres, terrain, volumes");
         }
     }
 }
@@ -38,7 +38,7 @@ impl Material for Dielectric {
             Vec3::refract(unit_direction, rec.normal, ri)
         };
 
-        *scattered = Ray { origin: rec.point, direction };
+        *scattered = Ray { origin: rec.point, direction, time: r_in.time };
         true
     }
 
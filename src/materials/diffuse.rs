@@ -11,7 +11,7 @@ pub struct Diffuse {
 impl Material for Diffuse {
     fn scatter(&self, r_in: Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
         let scatter_direction = rec.normal + Vec3::random_unit_vector();
-        *scattered = Ray { origin: rec.point, direction: scatter_direction };
+        *scattered = Ray { origin: rec.point, direction: scatter_direction, time: r_in.time };
         *attenuation = self.albedo.clone();
         true
     }
@@ -0,0 +1,144 @@
+use crate::core::hittable::HitRecord;
+use crate::core::material::Material;
+use crate::math::color::Color;
+use crate::math::onb::Onb;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vec3;
+use crate::utils::common::{random, DEG_TO_RAD, PI};
+
+/// Hair fiber scattering after Marschner et al. and d'Eon et al., sampled as in pbrt.
+///
+/// Light reflects off the fiber (R), passes through it (TT), or reflects inside it once (TRT)
+/// or more. Each lobe's color comes from Fresnel reflection and absorption inside the fiber;
+/// `beta_m` spreads the lobes along the fiber and `beta_n` around it, and the tilted cuticle
+/// scales shift them by `alpha`. A lobe is picked by its strength and its direction sampled
+/// exactly, so a path is weighted by the lobe's color over its probability.
+///
+/// Meant for `Curve`s, which provide the fiber direction and where across it the ray hit.
+#[derive(Debug, Clone, Copy)]
+pub struct Hair {
+    pub sigma_a: Color, // Absorption coefficient inside the fiber, per fiber diameter
+    pub beta_m: f64, // Longitudinal roughness, in (0, 1]
+    pub beta_n: f64, // Azimuthal roughness, in (0, 1]
+    pub alpha: f64, // Tilt of the cuticle scales, in degrees
+    pub eta: f64, // Index of refraction of the fiber
+}
+
+impl Hair {
+    /// Natural hair colored by its concentrations of the two melanin pigments: eumelanin
+    /// from about 0.3 for blond to 8 for black hair, and pheomelanin for red.
+    pub const fn from_melanin(eumelanin: f64, pheomelanin: f64, beta_m: f64, beta_n: f64) -> Self {
+        let sigma_a = Color::new(
+            eumelanin * 0.419 + pheomelanin * 0.187,
+            eumelanin * 0.697 + pheomelanin * 0.4,
+            eumelanin * 1.37 + pheomelanin * 1.05,
+        );
+        Hair { sigma_a, beta_m, beta_n, alpha: 2.0, eta: 1.55 }
+    }
+
+    /// Hair whose multiple scattering comes out roughly `color`, e.g. for dyed hair.
+    pub fn from_color(color: Color, beta_m: f64, beta_n: f64) -> Self {
+        let scale = 5.969 - 0.215 * beta_n + 2.532 * beta_n.powi(2) - 10.73 * beta_n.powi(3) + 5.574 * beta_n.powi(4)
+            + 0.245 * beta_n.powi(5);
+        let channel = |c: f64| (c.max(1e-4).ln() / scale).powi(2);
+        let sigma_a = Color::new(channel(color.x), channel(color.y), channel(color.z));
+        Hair { sigma_a, beta_m, beta_n, alpha: 2.0, eta: 1.55 }
+    }
+}
+
+impl Material for Hair {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        // Fiber frame: x along the fiber, z towards the viewer square to it, y = z cross x.
+        // Curves have v rising across the fiber against y, which gives the offset h.
+        let wo = -Vec3::unit_vector(r_in.direction);
+        let x = if rec.tangent.near_zero() { Onb::new(rec.normal).u } else { Vec3::unit_vector(rec.tangent) };
+        let across = wo - x * Vec3::dot_two(wo, x);
+        let z = if across.near_zero() { Onb::new(x).u } else { Vec3::unit_vector(across) };
+        let y = Vec3::cross_two(z, x);
+        let h = (1.0 - 2.0 * rec.v).clamp(-1.0, 1.0);
+
+        let sin_theta_o = Vec3::dot_two(wo, x).clamp(-1.0, 1.0);
+        let cos_theta_o = safe_sqrt(1.0 - sin_theta_o * sin_theta_o);
+        let phi_o = Vec3::dot_two(wo, z).atan2(Vec3::dot_two(wo, y));
+
+        // Refraction into the fiber, and the transmittance of one pass through it.
+        let sin_theta_t = sin_theta_o / self.eta;
+        let cos_theta_t = safe_sqrt(1.0 - sin_theta_t * sin_theta_t);
+        let eta_p = safe_sqrt(self.eta * self.eta - sin_theta_o * sin_theta_o) / cos_theta_o.max(1e-8);
+        let gamma_o = h.asin();
+        let gamma_t = (h / eta_p).clamp(-1.0, 1.0).asin();
+        let path = 2.0 * gamma_t.cos() / cos_theta_t;
+        let sigma = self.sigma_a;
+        let transmittance = Color::new((-sigma.x * path).exp(), (-sigma.y * path).exp(), (-sigma.z * path).exp());
+
+        // Lobe colors: R, TT, TRT, and the geometric series of all longer paths.
+        let f = fresnel(cos_theta_o * gamma_o.cos(), self.eta);
+        let r = Color::new(f, f, f);
+        let tt = transmittance * (1.0 - f) * (1.0 - f);
+        let trt = tt * transmittance * f;
+        let rest = trt * transmittance * f;
+        let rest = Color::new(rest.x / (1.0 - transmittance.x * f), rest.y / (1.0 - transmittance.y * f), rest.z / (1.0 - transmittance.z * f));
+        let lobes = [r, tt, trt, rest];
+        let total: f64 = lobes.iter().map(|lobe| lobe.luminance()).sum();
+        if total <= 0.0 {
+            return false;
+        }
+        let mut pick = random() * total;
+        let p = (0..3).find(|&p| {
+            pick -= lobes[p].luminance();
+            pick < 0.0
+        }).unwrap_or(3);
+
+        // Longitudinal direction, around the mirror of the tilted incoming angle.
+        let scale_tilt = [-2.0, 1.0, 4.0, 0.0][p] * self.alpha * DEG_TO_RAD;
+        let (sin_theta_op, cos_theta_op) = (sin_theta_o.asin() + scale_tilt).sin_cos();
+        let v0 = (0.726 * self.beta_m + 0.812 * self.beta_m.powi(2) + 3.7 * self.beta_m.powi(20)).powi(2);
+        let variance = [v0, 0.25 * v0, 4.0 * v0, 4.0 * v0][p];
+        let u = random().max(1e-5);
+        let cos_theta = 1.0 + variance * (u + (1.0 - u) * (-2.0 / variance).exp()).ln();
+        let sin_theta = safe_sqrt(1.0 - cos_theta * cos_theta);
+        let cos_phi = (2.0 * PI * random()).cos();
+        let sin_theta_i = (-cos_theta * sin_theta_op + sin_theta * cos_phi * cos_theta_op).clamp(-1.0, 1.0);
+        let cos_theta_i = safe_sqrt(1.0 - sin_theta_i * sin_theta_i);
+
+        // Azimuthal direction: the deflection of each path, blurred by the roughness.
+        let s = (PI / 8.0).sqrt() * (0.265 * self.beta_n + 1.194 * self.beta_n.powi(2) + 5.372 * self.beta_n.powi(22));
+        let delta_phi = if p < 3 {
+            let p = p as f64;
+            2.0 * p * gamma_t - 2.0 * gamma_o + p * PI + trimmed_logistic(random(), s)
+        } else {
+            2.0 * PI * random()
+        };
+        let phi_i = phi_o + delta_phi;
+
+        let direction = x * sin_theta_i + y * (cos_theta_i * phi_i.cos()) + z * (cos_theta_i * phi_i.sin());
+        *scattered = Ray { origin: rec.point, direction, time: r_in.time };
+        *attenuation = lobes[p] * (total / lobes[p].luminance());
+        true
+    }
+}
+
+fn safe_sqrt(x: f64) -> f64 {
+    x.max(0.0).sqrt()
+}
+
+/// Fresnel reflectance of a dielectric entered from air at an angle with cosine `cos_i`.
+fn fresnel(cos_i: f64, eta: f64) -> f64 {
+    let cos_i = cos_i.clamp(0.0, 1.0);
+    let sin_t = safe_sqrt(1.0 - cos_i * cos_i) / eta;
+    if sin_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = safe_sqrt(1.0 - sin_t * sin_t);
+    let parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    (parallel * parallel + perpendicular * perpendicular) / 2.0
+}
+
+/// Samples the logistic distribution with scale `s`, restricted to [-pi, pi].
+fn trimmed_logistic(u: f64, s: f64) -> f64 {
+    let cdf = |x: f64| 1.0 / (1.0 + (-x / s).exp());
+    let k = cdf(PI) - cdf(-PI);
+    let x = -s * (1.0 / (u * k + cdf(-PI)) - 1.0).ln();
+    x.clamp(-PI, PI)
+}
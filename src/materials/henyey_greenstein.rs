@@ -0,0 +1,46 @@
+use crate::core::hittable::HitRecord;
+use crate::core::material::Material;
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vec3;
+use crate::utils::common::{random, PI};
+
+/// Henyey-Greenstein phase function of a participating medium. Positive `g` scatters light
+/// mostly forward (like clouds), negative `g` mostly back, and 0 in all directions equally.
+#[derive(Debug, Clone, Copy)]
+pub struct HenyeyGreenstein {
+    pub albedo: Color,
+    pub g: f64, // Mean cosine of the scattering angle, in (-1, 1)
+}
+
+impl HenyeyGreenstein {
+    /// Samples a new direction for light travelling along `direction`, with probability
+    /// proportional to the phase function.
+    pub fn sample_direction(&self, direction: Vec3) -> Vec3 {
+        let g = self.g;
+        let xi = random();
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0 * xi
+        } else {
+            let term = (1.0 - g * g) / (1.0 - g + 2.0 * g * xi);
+            (1.0 + g * g - term * term) / (2.0 * g)
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random();
+
+        // Orthonormal basis around the travel direction.
+        let w = Vec3::unit_vector(direction);
+        let helper = if w.x.abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let u = Vec3::unit_vector(Vec3::cross_two(helper, w));
+        let v = Vec3::cross_two(w, u);
+        u * (sin_theta * phi.cos()) + v * (sin_theta * phi.sin()) + w * cos_theta
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        *scattered = Ray { origin: rec.point, direction: self.sample_direction(r_in.direction), time: r_in.time };
+        *attenuation = self.albedo;
+        true
+    }
+}
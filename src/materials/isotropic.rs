@@ -0,0 +1,19 @@
+use crate::core::hittable::HitRecord;
+use crate::core::material::Material;
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vec3;
+
+/// Phase function of a participating medium that scatters equally in all directions.
+#[derive(Debug, Clone, Copy)]
+pub struct Isotropic {
+    pub albedo: Color,
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        *scattered = Ray { origin: rec.point, direction: Vec3::random_unit_vector(), time: r_in.time };
+        *attenuation = self.albedo;
+        true
+    }
+}
@@ -29,7 +29,7 @@ impl Material for Lambertian {
         if scatter_direction.near_zero() {
             scatter_direction = rec.normal;
         }
-        *scattered = Ray { origin: rec.point, direction: scatter_direction };
+        *scattered = Ray { origin: rec.point, direction: scatter_direction, time: r_in.time };
         *attenuation = self.albedo.clone();
         true
     }
@@ -19,7 +19,7 @@ impl Default for Metal {
 impl Material for Metal {
     fn scatter(&self, r_in: Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
         let reflected = Vec3::reflect(r_in.direction, rec.normal);
-        *scattered = Ray { origin: rec.point, direction: reflected + self.fuzz * Vec3::random_unit_vector() };
+        *scattered = Ray { origin: rec.point, direction: reflected + self.fuzz * Vec3::random_unit_vector(), time: r_in.time };
         *attenuation = self.albedo.clone();
         true
     }
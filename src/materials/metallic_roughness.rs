@@ -0,0 +1,132 @@
+use crate::core::hittable::HitRecord;
+use crate::core::material::Material;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::texture::ImageTexture;
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+use crate::utils::common::random;
+
+/// How the alpha of the base color makes a surface transparent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaMode {
+    Opaque,
+    Mask { cutoff: f64 }, // Fully transparent below the cutoff, opaque above it
+    Blend,
+}
+
+/// The glTF metallic-roughness material. The base color is the diffuse color of dielectrics
+/// and the reflection color of metals, roughness blurs reflections, and transmission turns
+/// the surface into glass. Textures scale the factors where the surface has texture
+/// coordinates, and the base color is also scaled by vertex colors.
+///
+/// Every scatter picks one behavior at random by its weight: passing through where the
+/// surface is transparent, refraction for transmission, a tinted reflection for metals, and
+/// otherwise a clear reflection chosen by Fresnel reflectance over a diffuse bounce.
+#[derive(Clone, Debug)]
+pub struct MetallicRoughness {
+    pub base_color: Color, // Linear RGB
+    pub alpha: f64,
+    pub alpha_mode: AlphaMode,
+    pub metallic: f64,
+    pub roughness: f64,
+    pub emissive: Color, // Linear RGB, including any emissive strength
+    pub transmission: f64, // Fraction of light refracted through dielectrics
+    pub ior: f64,
+    pub base_color_texture: Option<ImageTexture>, // Linear RGB and alpha
+    pub metallic_roughness_texture: Option<ImageTexture>, // Roughness in green, metallic in blue
+    pub emissive_texture: Option<ImageTexture>, // Linear RGB
+}
+
+impl Default for MetallicRoughness {
+    /// The glTF defaults: a white, fully rough metal.
+    fn default() -> Self {
+        MetallicRoughness {
+            base_color: Color::white(),
+            alpha: 1.0,
+            alpha_mode: AlphaMode::Opaque,
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: Color::black(),
+            transmission: 0.0,
+            ior: 1.5,
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            emissive_texture: None,
+        }
+    }
+}
+
+impl MetallicRoughness {
+    /// Mirror direction blurred by the roughness. Directions blurred below the surface
+    /// bounce diffusely instead, so rough surfaces do not lose light.
+    fn glossy_direction(unit_direction: Vec3, normal: Vec3, roughness: f64) -> Vec3 {
+        let direction = Vec3::reflect(unit_direction, normal) + roughness * roughness * Vec3::random_unit_vector();
+        if Vec3::dot_two(direction, normal) > 0.0 {
+            return direction;
+        }
+        let diffuse = normal + Vec3::random_unit_vector();
+        if diffuse.near_zero() { normal } else { diffuse }
+    }
+}
+
+impl Material for MetallicRoughness {
+    fn emitted(&self, u: f64, v: f64, _p: Point3) -> Color {
+        match &self.emissive_texture {
+            Some(texture) => {
+                let [red, green, blue, _] = texture.sample(u, v);
+                self.emissive * Color::new(red, green, blue)
+            }
+            None => self.emissive,
+        }
+    }
+
+    fn scatter(&self, r_in: Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        let mut base_color = self.base_color * rec.color;
+        let mut alpha = self.alpha;
+        if let Some(texture) = &self.base_color_texture {
+            let [red, green, blue, texture_alpha] = texture.sample(rec.u, rec.v);
+            base_color = base_color * Color::new(red, green, blue);
+            alpha *= texture_alpha;
+        }
+        let coverage = match self.alpha_mode {
+            AlphaMode::Opaque => 1.0,
+            AlphaMode::Mask { cutoff } => if alpha >= cutoff { 1.0 } else { 0.0 },
+            AlphaMode::Blend => alpha,
+        };
+        if random() >= coverage {
+            *scattered = Ray { origin: rec.point, direction: r_in.direction, time: r_in.time };
+            *attenuation = Color::white();
+            return true;
+        }
+
+        let (mut metallic, mut roughness) = (self.metallic, self.roughness);
+        if let Some(texture) = &self.metallic_roughness_texture {
+            let [_, green, blue, _] = texture.sample(rec.u, rec.v);
+            roughness *= green;
+            metallic *= blue;
+        }
+        let unit_direction = Vec3::unit_vector(r_in.direction);
+        let grazing = (1.0 - Vec3::dot_two(-unit_direction, rec.normal).clamp(0.0, 1.0)).powi(5);
+
+        if random() < metallic {
+            // Schlick's approximation with the base color as reflectance at normal incidence.
+            *attenuation = base_color + (Color::white() - base_color) * grazing;
+            *scattered = Ray { origin: rec.point, direction: Self::glossy_direction(unit_direction, rec.normal, roughness), time: r_in.time };
+            return true;
+        }
+        if random() < self.transmission {
+            let refracted = Dielectric { ref_idx: self.ior }.scatter(r_in, rec, attenuation, scattered);
+            *attenuation = base_color;
+            return refracted;
+        }
+        let normal_reflectance = ((self.ior - 1.0) / (self.ior + 1.0)).powi(2);
+        if random() < normal_reflectance + (1.0 - normal_reflectance) * grazing {
+            *attenuation = Color::white();
+            *scattered = Ray { origin: rec.point, direction: Self::glossy_direction(unit_direction, rec.normal, roughness), time: r_in.time };
+            return true;
+        }
+        Lambertian { albedo: base_color }.scatter(r_in, rec, attenuation, scattered)
+    }
+}
@@ -3,4 +3,12 @@ pub mod lambertian;
 pub mod metal;
 pub mod dielectric;
 pub mod diffuse_light;
+pub mod isotropic;
+pub mod henyey_greenstein;
+pub mod orbit_trap;
+pub mod hair;
+pub mod vertex_color;
+pub mod texture;
+pub mod metallic_roughness;
+pub mod spot_light;
 
@@ -0,0 +1,22 @@
+use crate::core::hittable::HitRecord;
+use crate::core::material::Material;
+use crate::materials::lambertian::Lambertian;
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+
+/// Diffuse material colored by the orbit trap of a fractal hit: `near` where the orbit came
+/// close to the origin, blending to `far` at a trap of `range` and beyond.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitTrapGradient {
+    pub near: Color,
+    pub far: Color,
+    pub range: f64,
+}
+
+impl Material for OrbitTrapGradient {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        let blend = (rec.orbit_trap / self.range).clamp(0.0, 1.0);
+        let albedo = self.near * (1.0 - blend) + self.far * blend;
+        Lambertian { albedo }.scatter(r_in, rec, attenuation, scattered)
+    }
+}
@@ -0,0 +1,29 @@
+use crate::core::hittable::HitRecord;
+use crate::core::material::Material;
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+
+/// Emitter for a small sphere around `center` that shines into a cone along `direction`,
+/// standing in for a spot light. Emission fades out between the inner and outer cone
+/// angles, with the falloff glTF recommends.
+#[derive(Debug, Clone, Copy)]
+pub struct SpotLight {
+    pub emit: Color,
+    pub center: Point3,
+    pub direction: Vec3, // Unit length
+    pub cos_inner: f64,
+    pub cos_outer: f64,
+}
+
+impl Material for SpotLight {
+    fn emitted(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let cos_angle = Vec3::dot_two(Vec3::unit_vector(p - self.center), self.direction);
+        let falloff = ((cos_angle - self.cos_outer) / (self.cos_inner - self.cos_outer).max(1e-3)).clamp(0.0, 1.0);
+        self.emit * (falloff * falloff)
+    }
+
+    fn scatter(&self, _r_in: Ray, _rec: &HitRecord, _attenuation: &mut Color, _scattered: &mut Ray) -> bool {
+        false
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use crate::utils::image::RgbaImage;
+
+/// How texture coordinates outside [0, 1] map back onto the image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Wrap {
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
+impl Wrap {
+    /// Pixel index along an axis of `size` pixels for a possibly out of range index.
+    fn index(self, i: i64, size: usize) -> usize {
+        let size = size as i64;
+        let index = match self {
+            Wrap::Repeat => i.rem_euclid(size),
+            Wrap::Clamp => i.clamp(0, size - 1),
+            Wrap::Mirror => {
+                let period = i.rem_euclid(2 * size);
+                if period < size { period } else { 2 * size - 1 - period }
+            }
+        };
+        index as usize
+    }
+}
+
+/// Image looked up at texture coordinates with bilinear filtering. (0, 0) is the top left
+/// corner of the image and (1, 1) the bottom right, as in glTF.
+#[derive(Clone, Debug)]
+pub struct ImageTexture {
+    pub image: Arc<RgbaImage>,
+    pub wrap: [Wrap; 2], // Along u and v
+}
+
+impl ImageTexture {
+    pub fn new(image: Arc<RgbaImage>, wrap: [Wrap; 2]) -> Self {
+        ImageTexture { image, wrap }
+    }
+
+    /// RGBA value at (u, v).
+    pub fn sample(&self, u: f64, v: f64) -> [f64; 4] {
+        let (width, height) = (self.image.width, self.image.height);
+        // Pixel centers sit at half-integer coordinates.
+        let x = u * width as f64 - 0.5;
+        let y = v * height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let mut sum = [0.0; 4];
+        for (dy, wy) in [(0, 1.0 - fy), (1, fy)] {
+            for (dx, wx) in [(0, 1.0 - fx), (1, fx)] {
+                let column = self.wrap[0].index(x0 as i64 + dx, width);
+                let row = self.wrap[1].index(y0 as i64 + dy, height);
+                let pixel = self.image.pixel(column, row);
+                for channel in 0..4 {
+                    sum[channel] += wx * wy * pixel[channel] as f64;
+                }
+            }
+        }
+        sum
+    }
+}
@@ -0,0 +1,18 @@
+use crate::core::hittable::HitRecord;
+use crate::core::material::Material;
+use crate::materials::lambertian::Lambertian;
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+
+/// Diffuse material colored by the color the primitive stores at the hit, such as the
+/// colors of point cloud particles or of mesh vertices, scaled by `tint`.
+#[derive(Debug, Clone, Copy)]
+pub struct VertexColor {
+    pub tint: Color,
+}
+
+impl Material for VertexColor {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        Lambertian { albedo: rec.color * self.tint }.scatter(r_in, rec, attenuation, scattered)
+    }
+}
@@ -63,6 +63,11 @@ impl Aabb {
         Aabb { x, y, z }
     }
 
+    /// True if the box extends to infinity along some axis, as for infinite planes.
+    pub fn is_unbounded(&self) -> bool {
+        [&self.x, &self.y, &self.z].iter().any(|interval| interval.min.is_infinite() || interval.max.is_infinite())
+    }
+
     pub fn axis_interval(&self, n: usize) -> &Interval {
         match n {
             1 => &self.y,
@@ -72,7 +77,12 @@ impl Aabb {
     }
 
     /// Ray-AABB intersection test
-    pub fn hit(&self, r: Ray, mut ray_t: Interval) -> bool {
+    pub fn hit(&self, r: Ray, ray_t: Interval) -> bool {
+        self.clip(r, ray_t).is_some()
+    }
+
+    /// Returns the part of `ray_t` over which the ray is inside the box, if any.
+    pub fn clip(&self, r: Ray, mut ray_t: Interval) -> Option<Interval> {
         let ray_orig = r.origin;
         let ray_dir = r.direction;
 
@@ -91,10 +101,10 @@ impl Aabb {
                 ray_t.max = t_max;
             }
             if ray_t.max <= ray_t.min {
-                return false;
+                return None;
             }
         }
-        true
+        Some(ray_t)
     }
 
     /// Returns the index of the longest axis of the bounding box.
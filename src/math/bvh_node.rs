@@ -3,19 +3,39 @@ use crate::math::interval::Interval;
 use crate::math::ray::Ray;
 use std::cmp::Ordering;
 use std::sync::Arc;
-use crate::core::hittable::{Hittable, HitRecord};
+use crate::core::hittable::{Hittable, HitRecord, MediumSegment};
+use crate::core::hittable_list::HittableList;
+use crate::core::stats::{self, Counter};
 
 
 pub struct BvhNode {
     pub left: Arc<dyn Hittable>,
     pub right: Arc<dyn Hittable>,
     pub bbox: Aabb,
+    pub contains_media: bool, // Lets media queries skip subtrees of plain surfaces
+    pub unbounded: Vec<Arc<dyn Hittable + Send + Sync>>, // Objects with infinite bounds, tested outside the tree
 }
 
 impl BvhNode {
-    pub fn new_from_list(mut objects: Vec<Arc<dyn Hittable + Send + Sync>>) -> Self {
-        let len = objects.len();
-        Self::new(&mut objects, 0, len)
+    /// Builds a tree over the objects. Unbounded objects such as infinite planes would stretch
+    /// every box above them to infinity, so the root keeps them aside and tests them on their
+    /// own.
+    pub fn new_from_list(objects: Vec<Arc<dyn Hittable + Send + Sync>>) -> Self {
+        let (unbounded, mut bounded): (Vec<_>, Vec<_>) =
+            objects.into_iter().partition(|object| object.bounding_box().is_unbounded());
+        let len = bounded.len();
+        let mut root = if len == 0 {
+            let empty: Arc<dyn Hittable> = Arc::new(HittableList::new());
+            BvhNode { left: empty.clone(), right: empty, bbox: Aabb::empty(), contains_media: false, unbounded: Vec::new() }
+        } else {
+            Self::new(&mut bounded, 0, len)
+        };
+        if !unbounded.is_empty() {
+            root.bbox = Aabb::universe();
+            root.contains_media |= unbounded.iter().any(|object| object.contains_media());
+            root.unbounded = unbounded;
+        }
+        root
     }
 
     pub fn new(objects: &mut [Arc<dyn Hittable + Send + Sync>], start: usize, end: usize) -> Self {
@@ -55,28 +75,69 @@ impl BvhNode {
             Aabb::from_aabb(&left_box, &right_box)
         };
 
-        BvhNode { left, right, bbox }
+        let contains_media = left.contains_media() || right.contains_media();
+
+        BvhNode { left, right, bbox, contains_media, unbounded: Vec::new() }
     }
 }
 
 impl Hittable for BvhNode {
     fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::BvhNodesVisited);
         if !self.bbox.hit(r, interval.clone()) {
             return false;
         }
 
-        let hit_left = self.left.hit(r, interval.clone(), rec);
+        // Unbounded objects first: a close hit on a ground plane shortens the search below.
+        let mut hit_unbounded = false;
+        let mut t_max = interval.max;
+        for object in &self.unbounded {
+            if object.hit(r, Interval::new(interval.min, t_max), rec) {
+                hit_unbounded = true;
+                t_max = rec.t;
+            }
+        }
 
-        let t_max = if hit_left { rec.t } else { interval.max };
+        let hit_left = self.left.hit(r, Interval::new(interval.min, t_max), rec);
+
+        let t_max = if hit_left { rec.t } else { t_max };
         let new_interval = Interval { min: interval.min, max: t_max };
         let hit_right = self.right.hit(r, new_interval, rec);
 
-        hit_left || hit_right
+        hit_unbounded || hit_left || hit_right
     }
 
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn acceleration_bytes(&self) -> u64 {
+        let node = std::mem::size_of::<BvhNode>() as u64;
+        let unbounded: u64 = self.unbounded.iter().map(|object| object.acceleration_bytes()).sum();
+        // Single-object leaves store the same child on both sides.
+        if Arc::ptr_eq(&self.left, &self.right) {
+            node + unbounded + self.left.acceleration_bytes()
+        } else {
+            node + unbounded + self.left.acceleration_bytes() + self.right.acceleration_bytes()
+        }
+    }
+
+    fn contains_media(&self) -> bool {
+        self.contains_media
+    }
+
+    fn media_segments(&self, r: Ray, interval: Interval, segments: &mut Vec<MediumSegment>) {
+        if !self.contains_media || !self.bbox.hit(r, interval.clone()) {
+            return;
+        }
+        for object in &self.unbounded {
+            object.media_segments(r, interval.clone(), segments);
+        }
+        self.left.media_segments(r, interval.clone(), segments);
+        if !Arc::ptr_eq(&self.left, &self.right) {
+            self.right.media_segments(r, interval, segments);
+        }
+    }
 }
 
 // Box compare helpers
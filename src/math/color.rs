@@ -14,6 +14,27 @@ impl Color {
     pub fn black() -> Self {
         Color { x: 0.0, y: 0.0, z: 0.0 }
     }
+
+    /// Relative luminance of a linear RGB color (Rec. 709 weights).
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
+    }
+
+    /// Gamma-corrected 8-bit RGB values, as written to image files.
+    pub fn to_bytes(&self) -> [u8; 3] {
+        // Apply gamma correction
+        let r = linear_to_gamma(self.x);
+        let g = linear_to_gamma(self.y);
+        let b = linear_to_gamma(self.z);
+
+        // Translate [0,1] to [0,255]
+        let intensity = Interval::new(0.0, 0.999);
+        [
+            (256.0 * intensity.clamp(r)) as u8,
+            (256.0 * intensity.clamp(g)) as u8,
+            (256.0 * intensity.clamp(b)) as u8,
+        ]
+    }
 }
 
 pub trait WritableColor {
@@ -22,21 +43,7 @@ pub trait WritableColor {
 
 impl WritableColor for Color {
     fn write_color(&self, writer: &mut BufWriter<File>) -> Result<(), Box<dyn std::error::Error>> {
-        let r = self.x;
-        let g = self.y;
-        let b = self.z;
-
-        // Apply gamma correction
-        let r = linear_to_gamma(r);
-        let g = linear_to_gamma(g);
-        let b = linear_to_gamma(b);
-
-        // Translate [0,1] to [0,255]
-        let intensity = Interval::new(0.0, 0.999);
-        let rbyte = (256.0 * intensity.clamp(r)) as u8;
-        let gbyte = (256.0 * intensity.clamp(g)) as u8;
-        let bbyte = (256.0 * intensity.clamp(b)) as u8;
-
+        let [rbyte, gbyte, bbyte] = self.to_bytes();
         writeln!(writer, "{} {} {}", rbyte, gbyte, bbyte)?;
         Ok(())
     }
@@ -0,0 +1,113 @@
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::Point3;
+
+/// Items per leaf; leaves hold a run of item indices.
+const MAX_LEAF_ITEMS: usize = 4;
+
+/// Bounding volume hierarchy over items addressed by index, stored as one array of nodes.
+///
+/// Primitives made of many small pieces, such as curve segments, use this instead of
+/// `BvhNode`, which costs an `Arc` and a node allocation per object. The tree is built by
+/// median splits along the longest axis of the item centers.
+pub struct FlatBvh {
+    nodes: Vec<FlatNode>,
+    items: Vec<u32>, // Item indices in leaf order
+}
+
+struct FlatNode {
+    bbox: Aabb,
+    first: u32, // First item of a leaf, or the second child of an interior node
+    count: u32, // Items in a leaf; 0 for interior nodes, whose first child follows them
+    axis: u8, // Split axis of interior nodes, for visiting the nearer child first
+}
+
+impl FlatBvh {
+    pub fn new(boxes: &[Aabb]) -> Self {
+        let mut bvh = FlatBvh { nodes: Vec::with_capacity(2 * boxes.len().div_ceil(MAX_LEAF_ITEMS)), items: (0..boxes.len() as u32).collect() };
+        if !boxes.is_empty() {
+            bvh.build(boxes, 0, boxes.len());
+        }
+        bvh
+    }
+
+    fn build(&mut self, boxes: &[Aabb], start: usize, end: usize) -> usize {
+        let mut bbox = Aabb::empty();
+        for &item in &self.items[start..end] {
+            bbox = Aabb::from_aabb(&bbox, &boxes[item as usize]);
+        }
+        let node = self.nodes.len();
+        self.nodes.push(FlatNode { bbox, first: start as u32, count: (end - start) as u32, axis: 0 });
+        if end - start <= MAX_LEAF_ITEMS {
+            return node;
+        }
+
+        let mut centers = Aabb::empty();
+        for &item in &self.items[start..end] {
+            let b = &boxes[item as usize];
+            let center = Point3::new(b.x.min + b.x.max, b.y.min + b.y.max, b.z.min + b.z.max) / 2.0;
+            centers = Aabb::from_aabb(&centers, &Aabb::from_points(&center, &center));
+        }
+        let axis = centers.longest_axis();
+        let mid = start + (end - start) / 2;
+        let center = |item: &u32| {
+            let interval = boxes[*item as usize].axis_interval(axis);
+            interval.min + interval.max
+        };
+        self.items[start..end].select_nth_unstable_by(mid - start, |a, b| center(a).total_cmp(&center(b)));
+
+        self.build(boxes, start, mid);
+        let second = self.build(boxes, mid, end);
+        self.nodes[node].first = second as u32;
+        self.nodes[node].count = 0;
+        self.nodes[node].axis = axis as u8;
+        node
+    }
+
+    /// Calls `hit_item` with the index of every item whose box the ray reaches before the
+    /// closest hit so far, nearer subtrees first. `hit_item` returns the distance of a hit
+    /// within the interval it is given. Returns whether anything was hit.
+    pub fn hit(&self, r: Ray, interval: Interval, mut hit_item: impl FnMut(usize, Interval) -> Option<f64>) -> bool {
+        let mut closest = interval.max;
+        let mut hit = false;
+        let mut stack = Vec::with_capacity(32);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            stats::count(Counter::BvhNodesVisited);
+            let node = &self.nodes[index];
+            if !node.bbox.hit(r, Interval::new(interval.min, closest)) {
+                continue;
+            }
+            if node.count > 0 {
+                for &item in &self.items[node.first as usize..(node.first + node.count) as usize] {
+                    if let Some(t) = hit_item(item as usize, Interval::new(interval.min, closest)) {
+                        hit = true;
+                        closest = t;
+                    }
+                }
+            } else {
+                let (first, second) = (index + 1, node.first as usize);
+                let direction = [r.direction.x, r.direction.y, r.direction.z][node.axis as usize];
+                if direction < 0.0 {
+                    stack.extend([first, second]);
+                } else {
+                    stack.extend([second, first]);
+                }
+            }
+        }
+        hit
+    }
+
+    /// Box around all items.
+    pub fn bounds(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::empty(), |root| root.bbox.clone())
+    }
+
+    pub fn bytes(&self) -> u64 {
+        (self.nodes.len() * std::mem::size_of::<FlatNode>() + self.items.len() * std::mem::size_of::<u32>()) as u64
+    }
+}
@@ -0,0 +1,113 @@
+use std::ops::{Add, Mul, Sub};
+
+/// Values that can be blended between keyframes.
+pub trait Interpolate: Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f64, Output = Self> {}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T>> Interpolate for T {}
+
+fn lerp<T: Interpolate>(a: T, b: T, t: f64) -> T {
+    a + (b - a) * t
+}
+
+/// How a track blends between neighbouring keyframes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    /// Cubic Bezier segments with tangents from the neighbouring keyframes (Catmull-Rom
+    /// style), so the motion passes smoothly through every keyframe.
+    Bezier,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keyframe<T> {
+    pub time: f64,
+    pub value: T,
+}
+
+/// A value changing over time, given by keyframes sorted by time. Before the first and after
+/// the last keyframe the value holds still.
+#[derive(Clone, Debug)]
+pub struct Track<T> {
+    keys: Vec<Keyframe<T>>,
+    pub interpolation: Interpolation,
+}
+
+impl<T: Interpolate> Track<T> {
+    /// Builds a linearly interpolated track from keyframes in any order. Panics if there are none.
+    pub fn new(mut keys: Vec<Keyframe<T>>) -> Self {
+        assert!(!keys.is_empty(), "a track needs at least one keyframe");
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Track { keys, interpolation: Interpolation::Linear }
+    }
+
+    /// Builds a smoothly interpolated track from keyframes in any order.
+    pub fn bezier(keys: Vec<Keyframe<T>>) -> Self {
+        Track { interpolation: Interpolation::Bezier, ..Track::new(keys) }
+    }
+
+    pub fn constant(value: T) -> Self {
+        Track::new(vec![Keyframe { time: 0.0, value }])
+    }
+
+    /// Moves linearly from `from` at time 0 to `to` at time 1.
+    pub fn linear(from: T, to: T) -> Self {
+        Track::new(vec![Keyframe { time: 0.0, value: from }, Keyframe { time: 1.0, value: to }])
+    }
+
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.keys.len() == 1
+    }
+
+    pub fn sample(&self, time: f64) -> T {
+        let keys = &self.keys;
+        let last = keys.len() - 1;
+        if time <= keys[0].time {
+            return keys[0].value;
+        }
+        if time >= keys[last].time {
+            return keys[last].value;
+        }
+        // Index of the first keyframe after `time`; there is one before it too.
+        let next = keys.partition_point(|key| key.time <= time);
+        let t = (time - keys[next - 1].time) / (keys[next].time - keys[next - 1].time);
+        match self.interpolation {
+            Interpolation::Linear => lerp(keys[next - 1].value, keys[next].value, t),
+            Interpolation::Bezier => {
+                let [p0, c0, c1, p1] = self.segment_points(next - 1);
+                // De Casteljau evaluation of the cubic segment.
+                let (a, b, c) = (lerp(p0, c0, t), lerp(c0, c1, t), lerp(c1, p1, t));
+                let (d, e) = (lerp(a, b, t), lerp(b, c, t));
+                lerp(d, e, t)
+            }
+        }
+    }
+
+    /// Returns the points whose convex hull contains every value of the track, in order:
+    /// the keyframe values, plus the Bezier control points between them.
+    pub fn hull_points(&self) -> Vec<T> {
+        match self.interpolation {
+            Interpolation::Linear => self.keys.iter().map(|key| key.value).collect(),
+            Interpolation::Bezier => {
+                let mut points = vec![self.keys[0].value];
+                for segment in 0..self.keys.len() - 1 {
+                    points.extend_from_slice(&self.segment_points(segment)[1..]);
+                }
+                points
+            }
+        }
+    }
+
+    /// Start, control and end points of the Bezier segment after keyframe `index`. End
+    /// keyframes reuse themselves as the missing neighbour.
+    fn segment_points(&self, index: usize) -> [T; 4] {
+        let keys = &self.keys;
+        let previous = keys[index.saturating_sub(1)].value;
+        let (p0, p1) = (keys[index].value, keys[index + 1].value);
+        let next = keys[(index + 2).min(keys.len() - 1)].value;
+        [p0, p0 + (p1 - previous) * (1.0 / 6.0), p1 - (next - p0) * (1.0 / 6.0), p1]
+    }
+}
@@ -0,0 +1,164 @@
+use std::ops::Mul;
+
+use crate::math::vec3::{Point3, Vec3};
+
+/// 4x4 affine transformation matrix, applied to column vectors (`m[row][column]`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix4 {
+    pub m: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub const IDENTITY: Matrix4 = Matrix4 {
+        m: [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]],
+    };
+
+    /// Matrix from 16 values listed column by column, as file formats such as glTF and pbrt
+    /// store them.
+    pub fn from_columns(values: [f64; 16]) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (index, value) in values.into_iter().enumerate() {
+            m[index % 4][index / 4] = value;
+        }
+        Matrix4 { m }
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        let mut matrix = Matrix4::IDENTITY;
+        for row in 0..3 {
+            matrix.m[row][3] = offset[row];
+        }
+        matrix
+    }
+
+    pub fn scale(factors: Vec3) -> Self {
+        let mut matrix = Matrix4::IDENTITY;
+        for row in 0..3 {
+            matrix.m[row][row] = factors[row];
+        }
+        matrix
+    }
+
+    /// Rotation by `degrees` around `axis`, counterclockwise looking down the axis.
+    pub fn rotation(degrees: f64, axis: Vec3) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let a = Vec3::unit_vector(axis);
+        let mut matrix = Matrix4::IDENTITY;
+        for row in 0..3 {
+            for column in 0..3 {
+                let identity = if row == column { 1.0 } else { 0.0 };
+                // Rodrigues' formula: cos I + sin [a]x + (1 - cos) a a^T
+                let cross = match (row, column) {
+                    (0, 1) => -a.z,
+                    (0, 2) => a.y,
+                    (1, 0) => a.z,
+                    (1, 2) => -a.x,
+                    (2, 0) => -a.y,
+                    (2, 1) => a.x,
+                    _ => 0.0,
+                };
+                matrix.m[row][column] = cos * identity + sin * cross + (1.0 - cos) * a[row] * a[column];
+            }
+        }
+        matrix
+    }
+
+    /// Rotation given by a unit quaternion (x, y, z, w).
+    pub fn from_quaternion([x, y, z, w]: [f64; 4]) -> Self {
+        Matrix4 {
+            m: [
+                [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w), 2.0 * (x * z + y * w), 0.0],
+                [2.0 * (x * y + z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w), 0.0],
+                [2.0 * (x * z - y * w), 2.0 * (y * z + x * w), 1.0 - 2.0 * (x * x + y * y), 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (row, values) in m.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = self.m[column][row];
+            }
+        }
+        Matrix4 { m }
+    }
+
+    /// Inverse by Gauss-Jordan elimination with partial pivoting, or None if the matrix is
+    /// singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a = self.m;
+        let mut inverse = Matrix4::IDENTITY.m;
+        for column in 0..4 {
+            let pivot = (column..4).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+            if a[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(column, pivot);
+            inverse.swap(column, pivot);
+            let scale = 1.0 / a[column][column];
+            for k in 0..4 {
+                a[column][k] *= scale;
+                inverse[column][k] *= scale;
+            }
+            for row in (0..4).filter(|&row| row != column) {
+                let factor = a[row][column];
+                for k in 0..4 {
+                    a[row][k] -= factor * a[column][k];
+                    inverse[row][k] -= factor * inverse[column][k];
+                }
+            }
+        }
+        Some(Matrix4 { m: inverse })
+    }
+
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        let m = &self.m;
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        let q = self.transform_vector(p) + Vec3::new(m[0][3], m[1][3], m[2][3]);
+        if w == 1.0 || w == 0.0 { q } else { q / w }
+    }
+
+    /// Transforms a direction, ignoring the translation.
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+
+    /// Transforms a surface normal by the transpose of this matrix. Called on the inverse of
+    /// a transformation, it keeps normals perpendicular to transformed surfaces. The result
+    /// is not normalized.
+    pub fn transform_normal(&self, n: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * n.x + m[1][0] * n.y + m[2][0] * n.z,
+            m[0][1] * n.x + m[1][1] * n.y + m[2][1] * n.z,
+            m[0][2] * n.x + m[1][2] * n.y + m[2][2] * n.z,
+        )
+    }
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Matrix4::IDENTITY
+    }
+}
+
+impl Mul for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, other: Matrix4) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (row, values) in m.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[row][k] * other.m[k][column]).sum();
+            }
+        }
+        Matrix4 { m }
+    }
+}
@@ -3,4 +3,9 @@ pub mod ray;
 pub mod color;
 pub mod interval;
 pub mod aabb;
-pub mod bvh_node;
\ No newline at end of file
+pub mod bvh_node;
+pub mod flat_bvh;
+pub mod keyframes;
+pub mod matrix;
+pub mod onb;
+pub mod polynomial;
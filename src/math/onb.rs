@@ -0,0 +1,37 @@
+use crate::math::vec3::Vec3;
+use crate::utils::common::PI;
+
+/// Right-handed orthonormal basis (`u × v = w`) with `w` along a given direction, for working
+/// in the local frame of oriented shapes.
+#[derive(Clone, Copy, Debug)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    pub fn new(n: Vec3) -> Self {
+        let w = Vec3::unit_vector(n);
+        let a = if w.x.abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let v = Vec3::unit_vector(Vec3::cross_two(w, a));
+        let u = Vec3::cross_two(v, w);
+        Onb { u, v, w }
+    }
+
+    /// Converts local coordinates to a world vector.
+    pub fn transform(&self, local: Vec3) -> Vec3 {
+        self.u * local.x + self.v * local.y + self.w * local.z
+    }
+
+    /// Converts a world vector to local coordinates.
+    pub fn local(&self, world: Vec3) -> Vec3 {
+        Vec3::new(Vec3::dot_two(world, self.u), Vec3::dot_two(world, self.v), Vec3::dot_two(world, self.w))
+    }
+
+    /// Angle of a local vector around `w`, from `u` towards `v`, as a fraction of a turn in
+    /// [0, 1).
+    pub fn azimuth(local: Vec3) -> f64 {
+        (local.y.atan2(local.x) / (2.0 * PI)).rem_euclid(1.0)
+    }
+}
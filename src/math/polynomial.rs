@@ -0,0 +1,161 @@
+//! Real roots of low-degree polynomials, after Jochen Schwarze's "Cubic and Quartic Roots"
+//! in Graphics Gems. Coefficients are given from the constant term up.
+
+use crate::utils::common::PI;
+
+const NEAR_ZERO: f64 = 1e-9;
+
+/// Real roots of c[2] x² + c[1] x + c[0].
+pub fn solve_quadratic(c: [f64; 3]) -> Vec<f64> {
+    let p = c[1] / (2.0 * c[2]);
+    let q = c[0] / c[2];
+    let discriminant = p * p - q;
+    if discriminant.abs() < NEAR_ZERO {
+        vec![-p]
+    } else if discriminant < 0.0 {
+        Vec::new()
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![sqrt_d - p, -sqrt_d - p]
+    }
+}
+
+/// Real roots of c[3] x³ + c[2] x² + c[1] x + c[0].
+pub fn solve_cubic(c: [f64; 4]) -> Vec<f64> {
+    // Normal form x³ + Ax² + Bx + C, then substitute x = y - A/3 to remove the square term.
+    let (a, b, cc) = (c[2] / c[3], c[1] / c[3], c[0] / c[3]);
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + cc) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let mut roots = if discriminant.abs() < NEAR_ZERO {
+        if q.abs() < NEAR_ZERO {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![t * phi.cos(), -t * (phi + PI / 3.0).cos(), -t * (phi - PI / 3.0).cos()]
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![(sqrt_d - q).cbrt() - (sqrt_d + q).cbrt()]
+    };
+    for root in &mut roots {
+        *root -= a / 3.0;
+    }
+    roots
+}
+
+/// Real roots of c[4] x⁴ + c[3] x³ + c[2] x² + c[1] x + c[0], polished with Newton steps.
+pub fn solve_quartic(c: [f64; 5]) -> Vec<f64> {
+    // Normal form, then substitute x = y - A/4 to remove the cubic term.
+    let (a, b, cc, d) = (c[3] / c[4], c[2] / c[4], c[1] / c[4], c[0] / c[4]);
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + cc;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * cc / 4.0 + d;
+
+    let mut roots = if r.abs() < NEAR_ZERO {
+        // No constant term: y (y³ + py + q) = 0
+        let mut roots = solve_cubic([q, p, 0.0, 1.0]);
+        roots.push(0.0);
+        roots
+    } else {
+        // Split into two quadratics using one root of the resolvent cubic.
+        let z = solve_cubic([r * p / 2.0 - q * q / 8.0, -r, -p / 2.0, 1.0])[0];
+        let square_root = |x: f64| {
+            if x.abs() < NEAR_ZERO {
+                Some(0.0)
+            } else if x > 0.0 {
+                Some(x.sqrt())
+            } else {
+                None
+            }
+        };
+        let (Some(u), Some(v)) = (square_root(z * z - r), square_root(2.0 * z - p)) else {
+            return Vec::new();
+        };
+        let v = if q < 0.0 { -v } else { v };
+        let mut roots = solve_quadratic([z - u, v, 1.0]);
+        roots.extend(solve_quadratic([z + u, -v, 1.0]));
+        roots
+    };
+
+    let value = |x: f64| (((c[4] * x + c[3]) * x + c[2]) * x + c[1]) * x + c[0];
+    let slope = |x: f64| ((4.0 * c[4] * x + 3.0 * c[3]) * x + 2.0 * c[2]) * x + c[1];
+    for root in &mut roots {
+        *root -= a / 4.0;
+        for _ in 0..2 {
+            let derivative = slope(*root);
+            if derivative != 0.0 {
+                *root -= value(*root) / derivative;
+            }
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the roots against the expected ones in any order, allowing repeats of a root.
+    fn assert_roots(mut roots: Vec<f64>, expected: &[f64]) {
+        roots.sort_by(f64::total_cmp);
+        roots.dedup_by(|a, b| (*a - *b).abs() < 1e-6);
+        assert_eq!(roots.len(), expected.len(), "roots {:?}, expected {:?}", roots, expected);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() < 1e-6, "roots {:?}, expected {:?}", roots, expected);
+        }
+    }
+
+    #[test]
+    fn quadratic_roots() {
+        assert_roots(solve_quadratic([-6.0, 1.0, 1.0]), &[-3.0, 2.0]); // (x + 3)(x - 2)
+        assert_roots(solve_quadratic([1.0, -2.0, 1.0]), &[1.0]);
+        assert_roots(solve_quadratic([1.0, 0.0, 1.0]), &[]);
+    }
+
+    #[test]
+    fn cubic_roots() {
+        assert_roots(solve_cubic([-6.0, 11.0, -6.0, 1.0]), &[1.0, 2.0, 3.0]); // (x - 1)(x - 2)(x - 3)
+        assert_roots(solve_cubic([-2.0, 2.0, -1.0, 1.0]), &[1.0]); // (x - 1)(x² + 2)
+        assert_roots(solve_cubic([-1.0, 3.0, -3.0, 1.0]), &[1.0]); // (x - 1)³
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(solve_quartic([24.0, -50.0, 35.0, -10.0, 1.0]), &[1.0, 2.0, 3.0, 4.0]);
+        // 2 (x + 0.5)(x - 0.25)(x - 3)(x + 7), with a leading coefficient other than one
+        assert_roots(solve_quartic([5.25, -11.5, -40.25, 8.5, 2.0]), &[-7.0, -0.5, 0.25, 3.0]);
+        // (x - 1)(x + 2)(x² + 1)
+        assert_roots(solve_quartic([-2.0, 1.0, -1.0, 1.0, 1.0]), &[-2.0, 1.0]);
+        // (x² + 1)(x² + 4)
+        assert_roots(solve_quartic([4.0, 0.0, 5.0, 0.0, 1.0]), &[]);
+        // x (x - 1)(x + 1)(x - 2), without a constant term
+        assert_roots(solve_quartic([0.0, 2.0, -1.0, -2.0, 1.0]), &[-1.0, 0.0, 1.0, 2.0]);
+        // (x - 1)² (x + 1)², double roots
+        assert_roots(solve_quartic([1.0, 0.0, -2.0, 0.0, 1.0]), &[-1.0, 1.0]);
+    }
+
+    #[test]
+    fn quartic_roots_of_a_torus_intersection() {
+        // A ray along x from x = -5 through the center of a torus with radii 2 and 0.5 crosses
+        // the tube at t = 2.5, 3.5, 6.5 and 7.5; expand the product of (t - root).
+        let roots = [2.5, 3.5, 6.5, 7.5];
+        let mut c = [1.0, 0.0, 0.0, 0.0, 0.0];
+        for root in roots {
+            for k in (0..5).rev() {
+                c[k] = if k == 0 { 0.0 } else { c[k - 1] } - root * c[k];
+            }
+        }
+        assert_roots(solve_quartic(c), &roots);
+    }
+}
@@ -8,11 +8,16 @@ use crate::math::color::Color;
 pub struct Ray {
     pub origin: Point3,
     pub direction: Point3,
+    pub time: f64, // Moment within the camera shutter interval the ray samples
 }
 
 impl Ray {
     pub fn new(origin: Point3, direction: Point3) -> Self {
-        Self { origin, direction }
+        Self { origin, direction, time: 0.0 }
+    }
+
+    pub fn with_time(origin: Point3, direction: Point3, time: f64) -> Self {
+        Self { origin, direction, time }
     }
 
     pub fn at(&self, t: f64) -> Point3 {
@@ -47,13 +52,13 @@ impl Ray {
 
 impl Default for Ray {
     fn default() -> Self {
-        Ray { origin: Point3::default(), direction: Vec3::default() }
+        Ray { origin: Point3::default(), direction: Vec3::default(), time: 0.0 }
     }
 }
 
 impl Clone for Ray {
     fn clone(&self) -> Self {
-        Ray { origin: self.origin.clone(), direction: self.direction.clone() }
+        Ray { origin: self.origin, direction: self.direction, time: self.time }
     }
 }
 
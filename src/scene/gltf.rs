@@ -0,0 +1,826 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::core::hittable::{Hittable, Transform};
+use crate::core::hittable_list::HittableList;
+use crate::core::material::Material;
+use crate::core::projection::Projection;
+use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::metallic_roughness::{AlphaMode, MetallicRoughness};
+use crate::materials::spot_light::SpotLight;
+use crate::materials::texture::{ImageTexture, Wrap};
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::matrix::Matrix4;
+use crate::math::vec3::{Point3, Vec3};
+use crate::scene::{Scene, SceneCamera};
+use crate::sdf::disk::Disk;
+use crate::sdf::mesh::TriangleMesh;
+use crate::sdf::sphere::Sphere;
+use crate::utils::common::{PI, RAD_TO_DEG};
+use crate::utils::image::RgbaImage;
+
+/// Extensions that are imported, fully or in part.
+const SUPPORTED_EXTENSIONS: [&str; 5] = [
+    "KHR_lights_punctual",
+    "KHR_materials_emissive_strength",
+    "KHR_materials_ior",
+    "KHR_materials_transmission",
+    "KHR_mesh_quantization",
+];
+
+/// Radius of the spheres standing in for point and spot lights, relative to the size of
+/// the scene.
+const LIGHT_RADIUS: f64 = 0.02;
+
+/// Angular radius in radians of the discs standing in for directional lights. The sun is
+/// about 0.005, but larger discs are found far more often by paths bouncing around.
+const SUN_ANGLE: f64 = 0.05;
+
+/// Most values an accessor without a buffer view may have. Its zeros take no space in the
+/// file, so only this bounds the memory a small file can request.
+const MAX_UNBACKED_VALUES: usize = 1 << 26;
+
+/// Loads a glTF 2.0 scene: a `.gltf` file with its buffers and images in separate files or
+/// embedded as data URIs, or a binary `.glb` file.
+///
+/// The default scene's node hierarchy is flattened into `Transform` instances of the meshes,
+/// shared between the nodes using them. Materials become `MetallicRoughness` materials with
+/// their textures, and the first camera found becomes the scene camera. The renderer only
+/// knows light from glowing surfaces, so punctual lights become small glowing spheres (spot
+/// lights shining into their cone) and directional lights distant glowing discs, with
+/// intensities taken as radiometric.
+pub fn load(path: &str) -> io::Result<Scene> {
+    let bytes = fs::read(path)?;
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message));
+    let (json, binary_chunk) = if bytes.starts_with(b"glTF") {
+        parse_glb(&bytes).map_err(invalid)?
+    } else {
+        (serde_json::from_slice(&bytes).map_err(|error| invalid(error.to_string()))?, None)
+    };
+    let version = json["asset"]["version"].as_str().unwrap_or("");
+    if !version.starts_with("2.") {
+        return Err(invalid(format!("unsupported glTF version \"{}\"", version)));
+    }
+
+    let base = Path::new(path).parent().unwrap_or(Path::new("")).to_path_buf();
+    let mut binary_chunk = binary_chunk;
+    let mut buffers = Vec::new();
+    for (index, buffer) in array(&json, "buffers").iter().enumerate() {
+        let data = match buffer["uri"].as_str() {
+            Some(uri) => read_uri(&base, uri)?,
+            None if index == 0 => binary_chunk.take().ok_or_else(|| invalid("buffer 0 has no data".to_string()))?,
+            None => return Err(invalid(format!("buffer {} has no data", index))),
+        };
+        buffers.push(data);
+    }
+    let document = Document { path: path.to_string(), json, buffers, base };
+
+    let mut importer = Importer {
+        document: &document,
+        images: HashMap::new(),
+        materials: Vec::new(),
+        default_material: None,
+        meshes: Vec::new(),
+        lights: Vec::new(),
+        visited: HashSet::new(),
+        scene: Scene { world: HittableList::new(), camera: None, background: None, warnings: Vec::new() },
+    };
+    importer.import()?;
+    Ok(importer.scene)
+}
+
+/// JSON and binary chunks of a `.glb` file.
+fn parse_glb(bytes: &[u8]) -> Result<(Value, Option<Vec<u8>>), String> {
+    let word = |offset: usize| bytes.get(offset..offset + 4).map(|word| u32::from_le_bytes(word.try_into().unwrap()));
+    if word(4) != Some(2) {
+        return Err("unsupported GLB version".to_string());
+    }
+    let length = (word(8).unwrap_or(0) as usize).min(bytes.len());
+    let mut json = None;
+    let mut binary = None;
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let (chunk_length, chunk_type) = (word(offset).unwrap() as usize, word(offset + 4).unwrap());
+        let data = bytes.get(offset + 8..offset + 8 + chunk_length).ok_or("truncated GLB chunk")?;
+        match chunk_type {
+            0x4e4f_534a => json = Some(serde_json::from_slice(data).map_err(|error| error.to_string())?),
+            0x004e_4942 if binary.is_none() => binary = Some(data.to_vec()),
+            _ => {}
+        }
+        offset += 8 + chunk_length.div_ceil(4) * 4;
+    }
+    Ok((json.ok_or("GLB file without a JSON chunk")?, binary))
+}
+
+/// Contents of a buffer or image URI: embedded base64 data, or a file relative to the scene.
+fn read_uri(base: &Path, uri: &str) -> io::Result<Vec<u8>> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, encoded) = data.split_once(";base64,")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "data URI is not base64 encoded"))?;
+        return decode_base64(encoded).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid base64 data"));
+    }
+    fs::read(base.join(percent_decode(uri)))
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for c in text.bytes().filter(|&c| c != b'=' && !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        bits = bits << 6 | value as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Undoes the %XX escapes of relative URIs, such as %20 for spaces in file names.
+fn percent_decode(uri: &str) -> PathBuf {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+/// Elements of a JSON array property, or none if it is missing.
+fn array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value.get(key).and_then(Value::as_array).map_or(&[], Vec::as_slice)
+}
+
+fn number(value: &Value, key: &str, default: f64) -> f64 {
+    value.get(key).and_then(Value::as_f64).unwrap_or(default)
+}
+
+fn unsigned(value: &Value, key: &str) -> Option<usize> {
+    value.get(key).and_then(Value::as_u64).map(|index| index as usize)
+}
+
+/// A fixed size array of numbers, or `default` if it is missing or has the wrong length.
+fn numbers<const N: usize>(value: &Value, key: &str, default: [f64; N]) -> [f64; N] {
+    let values: Vec<f64> = array(value, key).iter().filter_map(Value::as_f64).collect();
+    values.try_into().unwrap_or(default)
+}
+
+/// Local transformation of a node, given as a matrix or as translation, rotation and scale.
+fn node_matrix(node: &Value) -> Matrix4 {
+    let values: Vec<f64> = array(node, "matrix").iter().filter_map(Value::as_f64).collect();
+    if let Ok(columns) = values.try_into() {
+        return Matrix4::from_columns(columns);
+    }
+    let [tx, ty, tz] = numbers(node, "translation", [0.0; 3]);
+    let [sx, sy, sz] = numbers(node, "scale", [1.0; 3]);
+    Matrix4::translation(Vec3::new(tx, ty, tz)) * Matrix4::from_quaternion(numbers(node, "rotation", [0.0, 0.0, 0.0, 1.0])) * Matrix4::scale(Vec3::new(sx, sy, sz))
+}
+
+/// The parsed file and its buffers.
+struct Document {
+    path: String,
+    json: Value,
+    buffers: Vec<Vec<u8>>,
+    base: PathBuf, // Directory that relative URIs start from
+}
+
+impl Document {
+    fn invalid(&self, message: String) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", self.path, message))
+    }
+
+    /// Bytes of a buffer view.
+    fn view(&self, index: usize) -> io::Result<&[u8]> {
+        let view = &self.json["bufferViews"][index];
+        let buffer = unsigned(view, "buffer").and_then(|buffer| self.buffers.get(buffer));
+        let offset = unsigned(view, "byteOffset").unwrap_or(0);
+        let length = unsigned(view, "byteLength").unwrap_or(0);
+        buffer.zip(offset.checked_add(length)).and_then(|(buffer, end)| buffer.get(offset..end))
+            .ok_or_else(|| self.invalid(format!("buffer view {} is out of bounds", index)))
+    }
+
+    /// Reads `count` elements of `components` values each from a buffer view, converting
+    /// normalized integers to [0, 1] (or [-1, 1] when signed).
+    fn read_elements(&self, view_index: usize, offset: usize, component_type: u64, components: usize, count: usize, normalized: bool) -> io::Result<Vec<f64>> {
+        let size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(self.invalid(format!("unknown component type {}", component_type))),
+        };
+        let view = self.view(view_index)?;
+        let element_size = components * size;
+        let stride = unsigned(&self.json["bufferViews"][view_index], "byteStride").unwrap_or(element_size);
+        if stride < element_size {
+            return Err(self.invalid(format!("buffer view {} has a stride smaller than its elements", view_index)));
+        }
+        let end = (count.max(1) - 1).checked_mul(stride).and_then(|bytes| bytes.checked_add(offset)?.checked_add(element_size));
+        if count > 0 && end.is_none_or(|end| end > view.len()) {
+            return Err(self.invalid(format!("data in buffer view {} is out of bounds", view_index)));
+        }
+        let mut values = Vec::with_capacity(count * components);
+        for element in 0..count {
+            for component in 0..components {
+                let at = offset + element * stride + component * size;
+                let bytes = &view[at..at + size];
+                let value = match component_type {
+                    5120 => bytes[0] as i8 as f64,
+                    5121 => bytes[0] as f64,
+                    5122 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    5123 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    5125 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+                    _ => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+                };
+                values.push(match (normalized, component_type) {
+                    (true, 5120) => (value / 127.0).max(-1.0),
+                    (true, 5121) => value / 255.0,
+                    (true, 5122) => (value / 32767.0).max(-1.0),
+                    (true, 5123) => value / 65535.0,
+                    _ => value,
+                });
+            }
+        }
+        Ok(values)
+    }
+
+    /// Values of an accessor, with the number of values per element. Sparse accessors are
+    /// applied over their base values.
+    fn accessor(&self, accessor_index: usize) -> io::Result<(Vec<f64>, usize)> {
+        let accessor = &self.json["accessors"][accessor_index];
+        let components = match accessor["type"].as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") | Some("MAT2") => 4,
+            Some("MAT3") => 9,
+            Some("MAT4") => 16,
+            _ => return Err(self.invalid(format!("accessor {} has no valid type", accessor_index))),
+        };
+        let count = unsigned(accessor, "count").unwrap_or(0);
+        let component_type = accessor["componentType"].as_u64().unwrap_or(0);
+        let normalized = accessor["normalized"].as_bool().unwrap_or(false);
+        let mut values = match unsigned(accessor, "bufferView") {
+            Some(view) => self.read_elements(view, unsigned(accessor, "byteOffset").unwrap_or(0), component_type, components, count, normalized)?,
+            None => {
+                let length = count.checked_mul(components).filter(|&length| length <= MAX_UNBACKED_VALUES)
+                    .ok_or_else(|| self.invalid(format!("accessor {} is too large", accessor_index)))?;
+                vec![0.0; length]
+            }
+        };
+
+        let sparse = &accessor["sparse"];
+        if let Some(sparse_count) = unsigned(sparse, "count") {
+            let (indices, sparse_values) = (&sparse["indices"], &sparse["values"]);
+            let view = |value: &Value| unsigned(value, "bufferView").ok_or_else(|| self.invalid(format!("sparse accessor {} without data", accessor_index)));
+            let targets = self.read_elements(view(indices)?, unsigned(indices, "byteOffset").unwrap_or(0), indices["componentType"].as_u64().unwrap_or(0), 1, sparse_count, false)?;
+            let replacements = self.read_elements(view(sparse_values)?, unsigned(sparse_values, "byteOffset").unwrap_or(0), component_type, components, sparse_count, normalized)?;
+            for (target, replacement) in targets.iter().zip(replacements.chunks_exact(components)) {
+                let start = *target as usize * components;
+                values.get_mut(start..start + components)
+                    .ok_or_else(|| self.invalid(format!("sparse accessor {} is out of bounds", accessor_index)))?
+                    .copy_from_slice(replacement);
+            }
+        }
+        Ok((values, components))
+    }
+}
+
+/// State built up while importing a document.
+struct Importer<'a> {
+    document: &'a Document,
+    images: HashMap<(usize, bool), Option<Arc<RgbaImage>>>, // By image and whether it was linearized
+    materials: Vec<&'static dyn Material>,
+    default_material: Option<&'static dyn Material>,
+    meshes: Vec<Option<Arc<dyn Hittable + Send + Sync>>>, // None for meshes without triangles
+    lights: Vec<(&'a Value, Matrix4)>, // Lights of the visited nodes, with their transformations
+    visited: HashSet<usize>, // Nodes already placed, as a node may have only one parent
+    scene: Scene,
+}
+
+impl<'a> Importer<'a> {
+    fn warn(&mut self, message: String) {
+        if !self.scene.warnings.contains(&message) {
+            self.scene.warnings.push(message);
+        }
+    }
+
+    fn import(&mut self) -> io::Result<()> {
+        let json = &self.document.json;
+        for extension in array(json, "extensionsRequired").iter().filter_map(Value::as_str) {
+            if !SUPPORTED_EXTENSIONS.contains(&extension) {
+                self.warn(format!("required extension {} is not supported", extension));
+            }
+        }
+        if !array(json, "animations").is_empty() {
+            self.warn("animations are ignored".to_string());
+        }
+
+        for material in array(json, "materials") {
+            let material = self.material(material)?;
+            self.materials.push(Box::leak(Box::new(material)));
+        }
+        for mesh in 0..array(json, "meshes").len() {
+            let mesh = self.mesh(mesh)?;
+            self.meshes.push(mesh);
+        }
+
+        // The default scene, else the first; files without scenes show their root nodes.
+        let scenes = array(json, "scenes");
+        let roots: Vec<usize> = match scenes.get(unsigned(json, "scene").unwrap_or(0)) {
+            Some(scene) => array(scene, "nodes").iter().filter_map(Value::as_u64).map(|node| node as usize).collect(),
+            None => {
+                let children: Vec<u64> = array(json, "nodes").iter().flat_map(|node| array(node, "children")).filter_map(Value::as_u64).collect();
+                (0..array(json, "nodes").len()).filter(|&node| !children.contains(&(node as u64))).collect()
+            }
+        };
+        for root in roots {
+            self.visit(root, Matrix4::IDENTITY, 0)?;
+        }
+
+        let bbox = self.scene.world.bbox.clone();
+        let extent = if bbox.x.size() > 0.0 {
+            Vec3::new(bbox.x.size(), bbox.y.size(), bbox.z.size()).length()
+        } else {
+            1.0
+        };
+        let center = if bbox.x.size() > 0.0 {
+            Point3::new(bbox.x.min + bbox.x.max, bbox.y.min + bbox.y.max, bbox.z.min + bbox.z.max) / 2.0
+        } else {
+            Point3::default()
+        };
+        for (light, matrix) in std::mem::take(&mut self.lights) {
+            self.light(light, matrix, center, extent);
+        }
+        Ok(())
+    }
+
+    /// Adds the node and its descendants to the scene, under the parent's transformation.
+    fn visit(&mut self, node_index: usize, parent: Matrix4, depth: usize) -> io::Result<()> {
+        let document = self.document;
+        let node = &document.json["nodes"][node_index];
+        if node.is_null() || depth > 1000 {
+            return Err(document.invalid(format!("node {} is missing or nested too deeply", node_index)));
+        }
+        // Shared children would be expanded once per path, exponentially many times.
+        if !self.visited.insert(node_index) {
+            return Err(document.invalid(format!("node {} has more than one parent or is part of a cycle", node_index)));
+        }
+        let matrix = parent * node_matrix(node);
+
+        if let Some(mesh) = unsigned(node, "mesh") {
+            if node.get("skin").is_some() {
+                self.warn("skins are ignored; skinned meshes are shown in their rest pose".to_string());
+            }
+            match (self.meshes.get(mesh).cloned().flatten(), matrix.inverse()) {
+                (Some(mesh), Some(_)) => self.scene.world.add(Transform::new(mesh, matrix)),
+                (Some(_), None) => self.warn(format!("node {} is scaled to nothing and skipped", node_index)),
+                (None, _) => {}
+            }
+        }
+        if let (Some(camera), None) = (unsigned(node, "camera"), &self.scene.camera) {
+            self.scene.camera = Some(scene_camera(&document.json["cameras"][camera], matrix));
+        }
+        if let Some(light) = unsigned(&node["extensions"]["KHR_lights_punctual"], "light") {
+            let light = &document.json["extensions"]["KHR_lights_punctual"]["lights"][light];
+            if !light.is_null() {
+                self.lights.push((light, matrix));
+            }
+        }
+        for child in array(node, "children").iter().filter_map(Value::as_u64) {
+            self.visit(child as usize, matrix, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    /// A mesh's triangle primitives as one object in the mesh's own space.
+    fn mesh(&mut self, mesh_index: usize) -> io::Result<Option<Arc<dyn Hittable + Send + Sync>>> {
+        let document = self.document;
+        let mut parts: Vec<Arc<dyn Hittable + Send + Sync>> = Vec::new();
+        for primitive in array(&document.json["meshes"][mesh_index], "primitives") {
+            let mode = unsigned(primitive, "mode").unwrap_or(4);
+            if mode < 4 {
+                self.warn("points and lines are skipped".to_string());
+                continue;
+            }
+            if !array(primitive, "targets").is_empty() {
+                self.warn("morph targets are ignored".to_string());
+            }
+            let attributes = &primitive["attributes"];
+            let Some(position_accessor) = unsigned(attributes, "POSITION") else {
+                self.warn(format!("a primitive of mesh {} has no positions and is skipped", mesh_index));
+                continue;
+            };
+            let (values, _) = document.accessor(position_accessor)?;
+            let positions: Vec<Point3> = values.chunks_exact(3).map(|p| Point3::new(p[0], p[1], p[2])).collect();
+            let indices: Vec<usize> = match unsigned(primitive, "indices") {
+                Some(accessor) => document.accessor(accessor)?.0.into_iter().map(|index| index as usize).collect(),
+                None => (0..positions.len()).collect(),
+            };
+            let triangles: Vec<[usize; 3]> = match mode {
+                4 => indices.chunks_exact(3).map(|corners| [corners[0], corners[1], corners[2]]).collect(),
+                // Strips alternate their winding so that all triangles face the same way.
+                5 => indices.windows(3).enumerate().map(|(i, w)| if i % 2 == 0 { [w[0], w[1], w[2]] } else { [w[0], w[2], w[1]] }).collect(),
+                _ => indices.windows(2).skip(1).map(|w| [w[0], w[1], indices[0]]).collect(),
+            };
+            if triangles.iter().flatten().any(|&vertex| vertex >= positions.len()) {
+                return Err(document.invalid(format!("a primitive of mesh {} refers to a missing vertex", mesh_index)));
+            }
+            if triangles.is_empty() {
+                continue;
+            }
+
+            let material = match unsigned(primitive, "material") {
+                Some(material) => *self.materials.get(material).ok_or_else(|| document.invalid(format!("material {} is missing", material)))?,
+                None => *self.default_material.get_or_insert_with(|| Box::leak(Box::new(MetallicRoughness::default()))),
+            };
+            let vertex_count = positions.len();
+            let triangles = triangles.into_iter().map(|corners| corners.map(|vertex| vertex as u32)).collect();
+            let mut mesh = TriangleMesh::new(positions, triangles, material);
+            let attribute = |name: &str, components: usize| -> io::Result<Option<Vec<f64>>> {
+                let Some(accessor) = unsigned(attributes, name) else { return Ok(None) };
+                let (values, stored) = document.accessor(accessor)?;
+                if values.len() != vertex_count * stored || stored < components {
+                    return Err(document.invalid(format!("{} of mesh {} does not match its positions", name, mesh_index)));
+                }
+                Ok(Some(values.chunks_exact(stored).flat_map(|value| value[..components].to_vec()).collect()))
+            };
+            if let Some(normals) = attribute("NORMAL", 3)? {
+                mesh = mesh.with_vertex_normals(normals.chunks_exact(3).map(|n| Vec3::new(n[0], n[1], n[2])).collect());
+            }
+            if let Some(uvs) = attribute("TEXCOORD_0", 2)? {
+                mesh = mesh.with_uvs(uvs.chunks_exact(2).map(|uv| [uv[0], uv[1]]).collect());
+            }
+            if let Some(colors) = attribute("COLOR_0", 3)? {
+                mesh = mesh.with_colors(colors.chunks_exact(3).map(|c| Color::new(c[0], c[1], c[2])).collect());
+            }
+            parts.push(Arc::new(mesh));
+        }
+        Ok(match parts.len() {
+            0 => None,
+            1 => parts.pop(),
+            _ => Some(Arc::new(BvhNode::new_from_list(parts))),
+        })
+    }
+
+    fn material(&mut self, material: &Value) -> io::Result<MetallicRoughness> {
+        let pbr = &material["pbrMetallicRoughness"];
+        let extensions = &material["extensions"];
+        let [red, green, blue, alpha] = numbers(pbr, "baseColorFactor", [1.0; 4]);
+        let [emissive_red, emissive_green, emissive_blue] = numbers(material, "emissiveFactor", [0.0; 3]);
+        let emissive_strength = number(&extensions["KHR_materials_emissive_strength"], "emissiveStrength", 1.0);
+        let alpha_mode = match material["alphaMode"].as_str() {
+            Some("MASK") => AlphaMode::Mask { cutoff: number(material, "alphaCutoff", 0.5) },
+            Some("BLEND") => AlphaMode::Blend,
+            _ => AlphaMode::Opaque,
+        };
+
+        if material.get("normalTexture").is_some() {
+            self.warn("normal textures are ignored".to_string());
+        }
+        if extensions["KHR_materials_transmission"].get("transmissionTexture").is_some() {
+            self.warn("transmission textures are ignored".to_string());
+        }
+        for name in extensions.as_object().into_iter().flat_map(|extensions| extensions.keys()) {
+            if !SUPPORTED_EXTENSIONS.contains(&name.as_str()) {
+                self.warn(format!("material extension {} is ignored", name));
+            }
+        }
+
+        Ok(MetallicRoughness {
+            base_color: Color::new(red, green, blue),
+            alpha,
+            alpha_mode,
+            metallic: number(pbr, "metallicFactor", 1.0),
+            roughness: number(pbr, "roughnessFactor", 1.0),
+            emissive: Color::new(emissive_red, emissive_green, emissive_blue) * emissive_strength,
+            transmission: number(&extensions["KHR_materials_transmission"], "transmissionFactor", 0.0),
+            ior: number(&extensions["KHR_materials_ior"], "ior", 1.5),
+            base_color_texture: self.texture(pbr.get("baseColorTexture"), true)?,
+            metallic_roughness_texture: self.texture(pbr.get("metallicRoughnessTexture"), false)?,
+            emissive_texture: self.texture(material.get("emissiveTexture"), true)?,
+        })
+    }
+
+    /// The texture a material refers to. Color textures are decoded to linear values.
+    /// Textures whose image cannot be read are left out with a warning.
+    fn texture(&mut self, info: Option<&Value>, color: bool) -> io::Result<Option<ImageTexture>> {
+        let Some(info) = info else { return Ok(None) };
+        if unsigned(info, "texCoord").unwrap_or(0) != 0 {
+            self.warn("only the first set of texture coordinates is supported".to_string());
+        }
+        if info["extensions"].get("KHR_texture_transform").is_some() {
+            self.warn("texture transforms are ignored".to_string());
+        }
+        let document = self.document;
+        let texture = unsigned(info, "index").map(|texture| &document.json["textures"][texture]).unwrap_or(&Value::Null);
+        let Some(source) = unsigned(texture, "source") else {
+            self.warn("textures without a PNG or JPEG image are skipped".to_string());
+            return Ok(None);
+        };
+
+        let image = match self.images.get(&(source, color)) {
+            Some(image) => image.clone(),
+            None => {
+                let image_json = &document.json["images"][source];
+                let bytes = match (image_json["uri"].as_str(), unsigned(image_json, "bufferView")) {
+                    (Some(uri), _) => read_uri(&document.base, uri),
+                    (None, Some(view)) => document.view(view).map(<[u8]>::to_vec),
+                    (None, None) => Err(io::Error::new(io::ErrorKind::InvalidData, "no image data")),
+                };
+                let image = match bytes.and_then(|bytes| RgbaImage::decode(&bytes)) {
+                    Ok(image) => Some(Arc::new(if color { image.linearized() } else { image })),
+                    Err(error) => {
+                        self.warn(format!("image {} could not be read and is skipped: {}", source, error));
+                        None
+                    }
+                };
+                self.images.insert((source, color), image.clone());
+                image
+            }
+        };
+
+        let sampler = unsigned(texture, "sampler").map(|sampler| &document.json["samplers"][sampler]).unwrap_or(&Value::Null);
+        let wrap = |key: &str| match unsigned(sampler, key) {
+            Some(33071) => Wrap::Clamp,
+            Some(33648) => Wrap::Mirror,
+            _ => Wrap::Repeat,
+        };
+        Ok(image.map(|image| ImageTexture::new(image, [wrap("wrapS"), wrap("wrapT")])))
+    }
+
+    /// Adds a glowing stand-in for a punctual light.
+    fn light(&mut self, light: &Value, matrix: Matrix4, scene_center: Point3, scene_extent: f64) {
+        let [red, green, blue] = numbers(light, "color", [1.0; 3]);
+        let intensity = Color::new(red, green, blue) * number(light, "intensity", 1.0);
+        let position = matrix.transform_point(Point3::default());
+        let direction = Vec3::unit_vector(matrix.transform_vector(Vec3::new(0.0, 0.0, -1.0)));
+        // A sphere of radiance L has an intensity of L * pi * r^2 in every direction.
+        let radius = LIGHT_RADIUS * scene_extent;
+        let emit = intensity / (PI * radius * radius);
+        match light["type"].as_str() {
+            Some("point") => {
+                self.scene.world.add(Sphere::new(position, radius, Box::leak(Box::new(DiffuseLight { emit }))));
+            }
+            Some("spot") => {
+                let spot = &light["spot"];
+                let material = SpotLight {
+                    emit,
+                    center: position,
+                    direction,
+                    cos_inner: number(spot, "innerConeAngle", 0.0).cos(),
+                    cos_outer: number(spot, "outerConeAngle", PI / 4.0).cos(),
+                };
+                self.scene.world.add(Sphere::new(position, radius, Box::leak(Box::new(material))));
+            }
+            Some("directional") => {
+                // Irradiance E from a disc of radiance L filling a solid angle w is L * w.
+                let distance = 10.0 * scene_extent;
+                let solid_angle = 2.0 * PI * (1.0 - SUN_ANGLE.cos());
+                let emit = intensity / solid_angle;
+                let center = scene_center - direction * distance;
+                self.scene.world.add(Disk::new(center, direction, distance * SUN_ANGLE.tan(), Box::leak(Box::new(DiffuseLight { emit }))));
+            }
+            _ => self.warn("lights of unknown type are skipped".to_string()),
+        }
+    }
+}
+
+/// Camera looking down the node's -z axis with +y up.
+fn scene_camera(camera: &Value, matrix: Matrix4) -> SceneCamera {
+    let eye = matrix.transform_point(Point3::default());
+    let forward = Vec3::unit_vector(matrix.transform_vector(Vec3::new(0.0, 0.0, -1.0)));
+    let up = Vec3::unit_vector(matrix.transform_vector(Vec3::new(0.0, 1.0, 0.0)));
+    let perspective = &camera["perspective"];
+    let orthographic = &camera["orthographic"];
+    let (projection, vfov, aspect_ratio) = match camera["type"].as_str() {
+        Some("orthographic") => {
+            let (x_magnification, y_magnification) = (number(orthographic, "xmag", 1.0), number(orthographic, "ymag", 1.0));
+            let projection = Projection::Orthographic { view_width: 2.0 * x_magnification, view_height: 2.0 * y_magnification };
+            (projection, 90.0, Some(x_magnification / y_magnification))
+        }
+        _ => (Projection::Perspective, number(perspective, "yfov", 0.8) * RAD_TO_DEG, perspective["aspectRatio"].as_f64()),
+    };
+    SceneCamera {
+        eye,
+        look_at: eye + forward,
+        up,
+        vfov,
+        projection,
+        aspect_ratio,
+        image_width: None,
+        samples_per_pixel: None,
+        depth_of_field: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A triangle's three float positions, then the bytes 0, 128, 255 and 2 (0, -128, -1 and
+    /// 2 as signed bytes).
+    fn buffer() -> Vec<u8> {
+        let mut buffer: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0].iter().flat_map(|value| value.to_le_bytes()).collect();
+        buffer.extend_from_slice(&[0, 128, 255, 2]);
+        buffer
+    }
+
+    fn document(accessors: Value) -> Document {
+        let json = json!({
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 4},
+                {"buffer": 0, "byteLength": 36, "byteStride": 12},
+                {"buffer": 0, "byteLength": 36, "byteStride": 2},
+                {"buffer": 0, "byteOffset": u64::MAX, "byteLength": 2},
+            ],
+            "accessors": accessors,
+        });
+        Document { path: "test.gltf".to_string(), json, buffers: vec![buffer()], base: PathBuf::new() }
+    }
+
+    fn chunk(kind: &[u8; 4], data: &[u8], padding: u8) -> Vec<u8> {
+        let mut chunk = (data.len().div_ceil(4) as u32 * 4).to_le_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(data);
+        chunk.resize(8 + data.len().div_ceil(4) * 4, padding);
+        chunk
+    }
+
+    fn glb(version: u32, chunks: &[Vec<u8>]) -> Vec<u8> {
+        let length = 12 + chunks.iter().map(Vec::len).sum::<usize>();
+        let mut bytes = b"glTF".to_vec();
+        bytes.extend_from_slice(&version.to_le_bytes());
+        bytes.extend_from_slice(&(length as u32).to_le_bytes());
+        chunks.iter().for_each(|chunk| bytes.extend_from_slice(chunk));
+        bytes
+    }
+
+    #[test]
+    fn decodes_base64() {
+        assert_eq!(decode_base64("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(decode_base64("aGVs\nbG8h").unwrap(), b"hello!");
+        assert_eq!(decode_base64("-_8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode_base64("+/8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode_base64("").unwrap(), b"");
+        assert!(decode_base64("aGVs*G8=").is_none());
+        assert_eq!(percent_decode("my%20mesh%2Ebin"), PathBuf::from("my mesh.bin"));
+        assert_eq!(percent_decode("100%"), PathBuf::from("100%"));
+    }
+
+    #[test]
+    fn parses_glb_chunks() {
+        let json = br#"{"asset":{"version":"2.0"}}"#;
+        let bytes = glb(2, &[chunk(b"JSON", json, b' '), chunk(b"BIN\0", &[1, 2, 3], 0)]);
+        let (parsed, binary) = parse_glb(&bytes).unwrap();
+        assert_eq!(parsed["asset"]["version"], "2.0");
+        assert_eq!(binary.unwrap(), [1, 2, 3, 0]);
+
+        let (_, binary) = parse_glb(&glb(2, &[chunk(b"JSON", json, b' ')])).unwrap();
+        assert!(binary.is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_glb() {
+        let json = chunk(b"JSON", br#"{"asset":{"version":"2.0"}}"#, b' ');
+        assert!(parse_glb(&glb(1, std::slice::from_ref(&json))).is_err());
+        assert!(parse_glb(&glb(2, &[chunk(b"BIN\0", &[1, 2, 3, 4], 0)])).is_err());
+        assert!(parse_glb(&glb(2, &[chunk(b"JSON", b"{", b' ')])).is_err());
+        // A chunk claiming more data than the file holds.
+        let mut truncated = glb(2, &[json]);
+        truncated[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_glb(&truncated).is_err());
+        assert!(parse_glb(b"glTF").is_err());
+    }
+
+    #[test]
+    fn reads_elements() {
+        let document = document(json!([]));
+        let positions = document.read_elements(0, 0, 5126, 3, 3, false).unwrap();
+        assert_eq!(positions, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(document.read_elements(0, 12, 5126, 3, 2, false).unwrap(), &positions[3..]);
+        assert_eq!(document.read_elements(1, 0, 5121, 1, 4, false).unwrap(), [0.0, 128.0, 255.0, 2.0]);
+        assert_eq!(document.read_elements(1, 0, 5121, 1, 3, true).unwrap(), [0.0, 128.0 / 255.0, 1.0]);
+        assert_eq!(document.read_elements(1, 0, 5120, 1, 3, true).unwrap(), [0.0, -1.0, -1.0 / 127.0]);
+        assert_eq!(document.read_elements(1, 0, 5123, 1, 2, false).unwrap(), [32768.0, 767.0]);
+        // Interleaved: the x and y of each 12-byte vertex.
+        assert_eq!(document.read_elements(2, 0, 5126, 2, 3, false).unwrap(), [0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+        assert!(document.read_elements(0, 0, 5126, 3, 0, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_elements_outside_their_buffer_view() {
+        let document = document(json!([]));
+        for (view, offset, count) in [(0, 0, 4), (0, 4, 3), (0, 0, usize::MAX), (0, usize::MAX, 1), (1, 0, usize::MAX / 2 + 1), (4, 0, 1), (5, 0, 1)] {
+            let error = document.read_elements(view, offset, 5126, 3, count, false).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+        // A stride shorter than the elements, and an unknown component type.
+        assert!(document.read_elements(3, 0, 5126, 3, 3, false).is_err());
+        assert!(document.read_elements(0, 0, 5130, 1, 1, false).is_err());
+    }
+
+    #[test]
+    fn reads_accessors() {
+        let document = document(json!([
+            {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"},
+            {"componentType": 5126, "count": 3, "type": "VEC3", "sparse": {
+                "count": 1,
+                "indices": {"bufferView": 1, "byteOffset": 3, "componentType": 5121},
+                "values": {"bufferView": 0, "byteOffset": 12, "componentType": 5126},
+            }},
+            {"componentType": 5126, "count": 2, "type": "VEC3", "sparse": {
+                "count": 1,
+                "indices": {"bufferView": 1, "byteOffset": 3, "componentType": 5121},
+                "values": {"bufferView": 0, "componentType": 5126},
+            }},
+            {"componentType": 5126, "count": usize::MAX / 2, "type": "MAT4"},
+            {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC5"},
+            {"componentType": 5126, "count": 1_000_000_000_000u64, "type": "VEC3"},
+        ]));
+        let (values, components) = document.accessor(0).unwrap();
+        assert_eq!((values.len(), components), (9, 3));
+        // Zeros with the third element replaced.
+        assert_eq!(document.accessor(1).unwrap().0, [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        for index in 2..6 {
+            assert_eq!(document.accessor(index).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn loads_a_triangle_with_an_external_buffer() {
+        let directory = std::env::temp_dir().join(format!("raytracer-gltf-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let json = json!({
+            "asset": {"version": "2.0"},
+            "buffers": [{"uri": "triangle%20data.bin", "byteLength": 40}],
+            "bufferViews": [{"buffer": 0, "byteLength": 36}],
+            "accessors": [{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}}]}],
+            "nodes": [{"mesh": 0, "translation": [0.0, 0.0, -2.0]}],
+            "scenes": [{"nodes": [0]}],
+        });
+        let path = directory.join("triangle.gltf");
+        fs::write(&path, json.to_string()).unwrap();
+        fs::write(directory.join("triangle data.bin"), buffer()).unwrap();
+        let scene = load(path.to_str().unwrap());
+        fs::remove_dir_all(&directory).unwrap();
+
+        let scene = scene.unwrap();
+        assert_eq!(scene.world.objects.len(), 1);
+        assert!(scene.camera.is_none());
+        assert!(scene.warnings.is_empty(), "{:?}", scene.warnings);
+        let bbox = &scene.world.bbox;
+        assert!(bbox.x.min <= 0.0 && bbox.x.max >= 1.0 && bbox.z.min <= -2.0 && bbox.z.max >= -2.0);
+    }
+
+    #[test]
+    fn rejects_shared_and_cyclic_nodes() {
+        let path = std::env::temp_dir().join(format!("raytracer-{}-nodes.gltf", std::process::id()));
+        let hierarchies = [
+            json!([{"children": [1, 2]}, {"children": [3]}, {}, {}]),
+            json!([{"children": [1, 2]}, {"children": [3]}, {"children": [3]}, {}]),
+            json!([{"children": [1]}, {"children": [0]}]),
+            json!([{"children": [0]}]),
+        ];
+        let mut results = Vec::new();
+        for nodes in hierarchies {
+            let json = json!({"asset": {"version": "2.0"}, "nodes": nodes, "scenes": [{"nodes": [0]}]});
+            fs::write(&path, json.to_string()).unwrap();
+            results.push(load(path.to_str().unwrap()));
+        }
+        fs::remove_file(&path).unwrap();
+
+        assert!(results.remove(0).is_ok());
+        for result in results {
+            assert_eq!(result.err().map(|error| error.kind()), Some(io::ErrorKind::InvalidData));
+        }
+    }
+}
@@ -0,0 +1,69 @@
+pub mod gltf;
+pub mod pbrt;
+
+use std::io;
+use std::path::Path;
+
+use crate::core::camera::Camera;
+use crate::core::hittable_list::HittableList;
+use crate::core::projection::Projection;
+use crate::math::color::Color;
+use crate::math::vec3::{Point3, Vec3};
+
+/// Viewpoint described by a scene file.
+#[derive(Clone, Copy, Debug)]
+pub struct SceneCamera {
+    pub eye: Point3,
+    pub look_at: Point3,
+    pub up: Vec3,
+    pub vfov: f64, // Vertical field of view in degrees
+    pub projection: Projection,
+    pub aspect_ratio: Option<f64>, // Width over height, if the file gives one
+    pub image_width: Option<u64>, // Width in pixels, if the file gives one
+    pub samples_per_pixel: Option<u64>,
+    pub depth_of_field: Option<(f64, f64)>, // Defocus angle in degrees and focus distance
+}
+
+impl SceneCamera {
+    /// Looks through this viewpoint with `camera`, keeping its other settings.
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.eye = self.eye;
+        camera.look_at = self.look_at;
+        camera.up = self.up;
+        camera.vfov = self.vfov;
+        camera.projection = self.projection;
+        if let Some(aspect_ratio) = self.aspect_ratio {
+            camera.aspect_ratio = aspect_ratio;
+        }
+        if let Some(image_width) = self.image_width {
+            camera.image_width = image_width;
+        }
+        if let Some(samples_per_pixel) = self.samples_per_pixel {
+            camera.samples_per_pixel = samples_per_pixel;
+        }
+        if let Some((defocus_angle, focus_distance)) = self.depth_of_field {
+            camera.defocus_angle = defocus_angle;
+            camera.focus_distance = focus_distance;
+        }
+    }
+}
+
+/// Objects and viewpoint read from a scene made by another application.
+pub struct Scene {
+    pub world: HittableList,
+    pub camera: Option<SceneCamera>,
+    pub background: Option<Color>, // Light from everywhere beyond the objects, if the file gives one
+    pub warnings: Vec<String>, // Parts of the file that were skipped or approximated
+}
+
+impl Scene {
+    /// Loads a scene, choosing the format by extension: `.gltf` or `.glb` for glTF 2.0, `.pbrt`
+    /// for pbrt-v3 or pbrt-v4.
+    pub fn load(path: &str) -> io::Result<Self> {
+        match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some("gltf" | "glb") => gltf::load(path),
+            Some("pbrt") => pbrt::load(path),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown scene format: {}", path))),
+        }
+    }
+}
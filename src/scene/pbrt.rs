@@ -0,0 +1,909 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::core::hittable::{Hittable, Transform};
+use crate::core::hittable_list::HittableList;
+use crate::core::material::Material;
+use crate::core::projection::Projection;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metal::Metal;
+use crate::math::bvh_node::BvhNode;
+use crate::math::color::Color;
+use crate::math::matrix::Matrix4;
+use crate::math::vec3::{Point3, Vec3};
+use crate::scene::{Scene, SceneCamera};
+use crate::sdf::mesh::TriangleMesh;
+use crate::sdf::sphere::Sphere;
+use crate::utils::common::{DEG_TO_RAD, RAD_TO_DEG};
+
+/// Nesting depth at which `Include` is taken to be recursive.
+const MAX_INCLUDE_DEPTH: usize = 32;
+
+/// Largest image side accepted from `Film`.
+const MAX_RESOLUTION: f64 = 65536.0;
+
+/// Loads a pbrt-v3 or pbrt-v4 scene, reading the subset of the format that maps onto this
+/// renderer:
+///
+/// - `LookAt`, `Translate`, `Scale`, `Rotate`, `Transform`, `ConcatTransform` and named
+///   coordinate systems, with `AttributeBegin`/`AttributeEnd` scoping;
+/// - `Camera "perspective"`, the `Film` resolution and the `Sampler` pixel samples;
+/// - `Shape "sphere"`, `"trianglemesh"`, `"bilinearmesh"` and `"plymesh"`, with object
+///   instancing;
+/// - `Material "diffuse"`, `"conductor"` and `"dielectric"` (and their pbrt-v3 names
+///   `matte`, `metal` and `glass`), also as named materials;
+/// - `AreaLightSource "diffuse"` and a constant `LightSource "infinite"` as the background.
+///
+/// Everything else is skipped or approximated, each with a warning on the scene. pbrt's
+/// world is left-handed, so it is mirrored to give the image pbrt would render.
+pub fn load(path: &str) -> io::Result<Scene> {
+    let text = fs::read_to_string(path)?;
+    let directory = Path::new(path).parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut importer = Importer::new(directory);
+    importer.run(path, &text, 0).map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))?;
+    Ok(importer.finish())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String), // Directive name
+    Str(String),
+    Num(f64),
+    Open,
+    Close,
+}
+
+/// A parameter such as `"float radius" [ 2 ]`.
+struct Param {
+    kind: String,
+    name: String,
+    values: Vec<Token>, // Numbers and strings
+}
+
+/// A directive with its positional arguments and parameter list.
+struct Directive {
+    name: String,
+    line: usize,
+    args: Vec<Token>,
+    params: Vec<Param>,
+}
+
+impl Directive {
+    fn param(&self, name: &str) -> Option<&Param> {
+        self.params.iter().find(|param| param.name == name)
+    }
+
+    fn floats(&self, name: &str) -> Option<Vec<f64>> {
+        let param = self.param(name)?;
+        Some(param.values.iter().filter_map(|value| if let Token::Num(n) = value { Some(*n) } else { None }).collect())
+    }
+
+    fn float(&self, name: &str) -> Option<f64> {
+        self.floats(name)?.first().copied()
+    }
+
+    fn string(&self, name: &str) -> Option<&str> {
+        match self.param(name)?.values.first()? {
+            Token::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn boolean(&self, name: &str, default: bool) -> bool {
+        self.string(name).map_or(default, |value| value == "true")
+    }
+
+    /// The first string argument, such as the type of a shape or material.
+    fn kind(&self) -> &str {
+        match self.args.first() {
+            Some(Token::Str(s)) => s,
+            _ => "",
+        }
+    }
+
+    fn numbers(&self) -> Vec<f64> {
+        self.args.iter().filter_map(|arg| if let Token::Num(n) = arg { Some(*n) } else { None }).collect()
+    }
+
+    /// Exactly `N` numeric arguments.
+    fn numbers_n<const N: usize>(&self) -> Result<[f64; N], String> {
+        self.numbers().try_into().map_err(|_| format!("{} expects {} numbers", self.name, N))
+    }
+}
+
+/// Splits a pbrt file into tokens, each with its line number. Bare `true` and `false` are
+/// read as strings, like their quoted forms.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, (usize, String)> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '[' => tokens.push((Token::Open, line)),
+            ']' => tokens.push((Token::Close, line)),
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        None | Some('\n') => return Err((line, "unterminated string".to_string())),
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(c) if c != '\n' => s.push(c),
+                            _ => return Err((line, "unterminated string".to_string())),
+                        },
+                        Some(c) => s.push(c),
+                    }
+                }
+                tokens.push((Token::Str(s), line));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && !"[]\"#".contains(c)) {
+                    word.push(c);
+                }
+                let token = if word == "true" || word == "false" {
+                    Token::Str(word)
+                } else if c.is_ascii_alphabetic() {
+                    Token::Word(word)
+                } else {
+                    Token::Num(word.parse().map_err(|_| (line, format!("unexpected \"{}\"", word)))?)
+                };
+                tokens.push((token, line));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Count of leading string arguments that may contain spaces, which would otherwise be
+/// taken for parameter declarations.
+fn string_arguments(directive: &str) -> usize {
+    match directive {
+        "Texture" => 3,
+        "Camera" | "Film" | "Sampler" | "Integrator" | "PixelFilter" | "Accelerator" | "ColorSpace" | "Shape" | "Material"
+        | "MakeNamedMaterial" | "NamedMaterial" | "LightSource" | "AreaLightSource" | "MakeNamedMedium" | "ObjectBegin"
+        | "ObjectInstance" | "CoordinateSystem" | "CoordSysTransform" | "Include" | "Import" => 1,
+        _ => 0,
+    }
+}
+
+fn parse(tokens: Vec<(Token, usize)>) -> Result<Vec<Directive>, (usize, String)> {
+    let mut tokens = tokens.into_iter().peekable();
+    let mut directives = Vec::new();
+    while let Some((token, line)) = tokens.next() {
+        let Token::Word(name) = token else {
+            return Err((line, "expected a directive".to_string()));
+        };
+        let mut directive = Directive { name, line, args: Vec::new(), params: Vec::new() };
+        for _ in 0..string_arguments(&directive.name) {
+            match tokens.next() {
+                Some((Token::Str(s), _)) => directive.args.push(Token::Str(s)),
+                _ => return Err((line, format!("{} expects a string", directive.name))),
+            }
+        }
+
+        // Further arguments: numbers, bracketed or not, and single-word strings.
+        let mut bracketed = false;
+        while let Some((token, _)) = tokens.next_if(|(token, _)| match token {
+            Token::Str(s) => bracketed || !s.contains(char::is_whitespace),
+            Token::Num(_) | Token::Close => true,
+            Token::Open => !bracketed,
+            Token::Word(_) => false,
+        }) {
+            match token {
+                Token::Open => bracketed = true,
+                Token::Close if !bracketed => return Err((line, "unmatched ]".to_string())),
+                Token::Close => bracketed = false,
+                token => directive.args.push(token),
+            }
+        }
+        if bracketed {
+            return Err((line, "unmatched [".to_string()));
+        }
+
+        while let Some((Token::Str(declaration), _)) = tokens.next_if(|(token, _)| matches!(token, Token::Str(_))) {
+            let parts: Vec<&str> = declaration.split_whitespace().collect();
+            let [kind, name] = parts[..] else {
+                return Err((line, format!("bad parameter declaration \"{}\"", declaration)));
+            };
+            let mut values = Vec::new();
+            match tokens.next() {
+                Some((Token::Open, _)) => loop {
+                    match tokens.next() {
+                        Some((Token::Close, _)) => break,
+                        Some((value @ (Token::Num(_) | Token::Str(_)), _)) => values.push(value),
+                        _ => return Err((line, format!("unterminated values of \"{}\"", name))),
+                    }
+                },
+                Some((value @ (Token::Num(_) | Token::Str(_)), _)) => values.push(value),
+                _ => return Err((line, format!("parameter \"{}\" has no value", name))),
+            }
+            directive.params.push(Param { kind: kind.to_string(), name: name.to_string(), values });
+        }
+        directives.push(directive);
+    }
+    Ok(directives)
+}
+
+/// Graphics state saved and restored by `AttributeBegin` and `AttributeEnd`.
+#[derive(Clone, Copy)]
+struct Attributes {
+    ctm: Matrix4, // Current transformation matrix, object space to pbrt's world space
+    material: Option<&'static dyn Material>, // None for interface surfaces, which only bound media
+    area_light: Option<&'static dyn Material>,
+}
+
+/// Camera as pbrt defines it, before mirroring into this renderer's world.
+#[derive(Clone, Copy)]
+struct CameraDefinition {
+    camera_from_world: Matrix4,
+    fov: f64, // Field of view of the shorter image axis, in degrees
+    lens_radius: f64,
+    focal_distance: f64,
+}
+
+struct Importer {
+    directory: PathBuf, // Relative paths are resolved from the main file's directory
+    file: String, // File being read, for warnings
+    attributes: Attributes,
+    stack: Vec<(Attributes, bool)>, // Saved attributes, and whether only the transformation is restored
+    named_materials: HashMap<String, Option<&'static dyn Material>>,
+    coordinate_systems: HashMap<String, Matrix4>,
+    camera: Option<CameraDefinition>,
+    mirror: Matrix4, // Maps pbrt's world into this renderer's
+    resolution: Option<[u64; 2]>,
+    samples_per_pixel: Option<u64>,
+    objects: HashMap<String, Arc<dyn Hittable + Send + Sync>>,
+    object: Option<(String, Vec<Arc<dyn Hittable + Send + Sync>>)>, // Instance being defined
+    warned: HashSet<String>,
+    scene: Scene,
+}
+
+impl Importer {
+    fn new(directory: PathBuf) -> Self {
+        Importer {
+            directory,
+            file: String::new(),
+            attributes: Attributes { ctm: Matrix4::IDENTITY, material: Some(&DEFAULT_MATERIAL), area_light: None },
+            stack: Vec::new(),
+            named_materials: HashMap::new(),
+            coordinate_systems: HashMap::new(),
+            camera: None,
+            mirror: Matrix4::scale(Vec3::new(-1.0, 1.0, 1.0)),
+            resolution: None,
+            samples_per_pixel: None,
+            objects: HashMap::new(),
+            object: None,
+            warned: HashSet::new(),
+            scene: Scene { world: HittableList::new(), camera: None, background: None, warnings: Vec::new() },
+        }
+    }
+
+    /// Warns once for each message, at its first occurrence.
+    fn warn(&mut self, line: usize, message: String) {
+        if self.warned.insert(message.clone()) {
+            self.scene.warnings.push(format!("{}:{}: {}", self.file, line, message));
+        }
+    }
+
+    fn run(&mut self, file: &str, text: &str, depth: usize) -> Result<(), String> {
+        let parent_file = std::mem::replace(&mut self.file, file.to_string());
+        let directives = tokenize(text).and_then(parse).map_err(|(line, message)| format!("{}:{}: {}", file, line, message))?;
+        for directive in &directives {
+            self.directive(directive, depth).map_err(|message| format!("{}:{}: {}", file, directive.line, message))?;
+        }
+        self.file = parent_file;
+        Ok(())
+    }
+
+    fn directive(&mut self, d: &Directive, depth: usize) -> Result<(), String> {
+        let line = d.line;
+        let ctm = self.attributes.ctm;
+        match d.name.as_str() {
+            "Identity" => self.attributes.ctm = Matrix4::IDENTITY,
+            "Translate" => {
+                let [x, y, z] = d.numbers_n()?;
+                self.attributes.ctm = ctm * Matrix4::translation(Vec3::new(x, y, z));
+            }
+            "Scale" => {
+                let [x, y, z] = d.numbers_n()?;
+                self.attributes.ctm = ctm * Matrix4::scale(Vec3::new(x, y, z));
+            }
+            "Rotate" => {
+                let [degrees, x, y, z] = d.numbers_n()?;
+                self.attributes.ctm = ctm * Matrix4::rotation(degrees, Vec3::new(x, y, z));
+            }
+            "LookAt" => {
+                let [ex, ey, ez, lx, ly, lz, ux, uy, uz] = d.numbers_n()?;
+                self.attributes.ctm = ctm * look_at(Point3::new(ex, ey, ez), Point3::new(lx, ly, lz), Vec3::new(ux, uy, uz))?;
+            }
+            "Transform" => self.attributes.ctm = Matrix4::from_columns(d.numbers_n()?),
+            "ConcatTransform" => self.attributes.ctm = ctm * Matrix4::from_columns(d.numbers_n()?),
+            "CoordinateSystem" => {
+                self.coordinate_systems.insert(d.kind().to_string(), ctm);
+            }
+            "CoordSysTransform" => match self.coordinate_systems.get(d.kind()) {
+                Some(&matrix) => self.attributes.ctm = matrix,
+                None => self.warn(line, format!("unknown coordinate system \"{}\"", d.kind())),
+            },
+            "AttributeBegin" | "TransformBegin" => self.stack.push((self.attributes, d.name == "TransformBegin")),
+            "AttributeEnd" | "TransformEnd" => match self.stack.pop() {
+                Some((attributes, true)) => self.attributes.ctm = attributes.ctm,
+                Some((attributes, false)) => self.attributes = attributes,
+                None => self.warn(line, format!("unmatched {}", d.name)),
+            },
+            "ReverseOrientation" => self.warn(line, "ReverseOrientation is ignored: surfaces are two-sided".to_string()),
+            "Camera" => self.camera(d)?,
+            "Film" => {
+                let width = d.float("xresolution").unwrap_or(1280.0);
+                let height = d.float("yresolution").unwrap_or(720.0);
+                let valid = |side: f64| (1.0..=MAX_RESOLUTION).contains(&side) && side.fract() == 0.0;
+                if !valid(width) || !valid(height) {
+                    return Err(format!("Film resolution must be a positive integer up to {}", MAX_RESOLUTION));
+                }
+                self.resolution = Some([width as u64, height as u64]);
+            }
+            "Sampler" => self.samples_per_pixel = Some(d.float("pixelsamples").unwrap_or(16.0).max(1.0) as u64),
+            "WorldBegin" => {
+                self.attributes.ctm = Matrix4::IDENTITY;
+                self.coordinate_systems.insert("world".to_string(), Matrix4::IDENTITY);
+            }
+            "WorldEnd" => {}
+            "Include" | "Import" => {
+                if depth >= MAX_INCLUDE_DEPTH {
+                    return Err("includes are nested too deeply".to_string());
+                }
+                let path = self.directory.join(d.kind());
+                let text = fs::read_to_string(&path).map_err(|error| format!("{}: {}", path.display(), error))?;
+                self.run(&path.to_string_lossy(), &text, depth + 1)?;
+            }
+            "Material" => self.attributes.material = self.material(d, d.kind()),
+            "MakeNamedMaterial" => {
+                let kind = d.string("type").unwrap_or_default();
+                let material = self.material(d, kind);
+                self.named_materials.insert(d.kind().to_string(), material);
+            }
+            "NamedMaterial" => match self.named_materials.get(d.kind()) {
+                Some(&material) => self.attributes.material = material,
+                None => self.warn(line, format!("unknown material \"{}\"", d.kind())),
+            },
+            "AreaLightSource" => {
+                if d.kind() != "diffuse" {
+                    self.warn(line, format!("area light \"{}\" is not supported", d.kind()));
+                    return Ok(());
+                }
+                let emit = self.color(d, &["L"], Color::white()) * self.scale(d);
+                self.attributes.area_light = Some(Box::leak(Box::new(DiffuseLight { emit })));
+            }
+            "LightSource" => {
+                if d.kind() != "infinite" {
+                    self.warn(line, format!("light \"{}\" is not supported; only area lights shine", d.kind()));
+                    return Ok(());
+                }
+                if d.param("filename").is_some() {
+                    self.warn(line, "environment maps are not supported; the background is constant".to_string());
+                }
+                self.scene.background = Some(self.color(d, &["L"], Color::white()) * self.scale(d));
+            }
+            "Shape" => self.shape(d)?,
+            "ObjectBegin" => {
+                if self.object.is_some() {
+                    return Err("ObjectBegin inside another object".to_string());
+                }
+                self.stack.push((self.attributes, false));
+                self.object = Some((d.kind().to_string(), Vec::new()));
+            }
+            "ObjectEnd" => {
+                let Some((name, mut parts)) = self.object.take() else {
+                    return Err("ObjectEnd without ObjectBegin".to_string());
+                };
+                if let Some((attributes, _)) = self.stack.pop() {
+                    self.attributes = attributes;
+                }
+                let object = match parts.len() {
+                    0 => return Ok(()),
+                    1 => parts.pop().unwrap(),
+                    _ => Arc::new(BvhNode::new_from_list(parts)),
+                };
+                self.objects.insert(name, object);
+            }
+            "ObjectInstance" => match self.objects.get(d.kind()) {
+                Some(object) => self.place(line, object.clone()),
+                None => self.warn(line, format!("unknown or empty object \"{}\"", d.kind())),
+            },
+            "Texture" => self.warn(line, "textures are not supported; materials use constant values".to_string()),
+            name => self.warn(line, format!("{} is not supported", name)),
+        }
+        Ok(())
+    }
+
+    fn camera(&mut self, d: &Directive) -> Result<(), String> {
+        if d.kind() != "perspective" {
+            self.warn(d.line, format!("camera \"{}\" is rendered as perspective", d.kind()));
+        }
+        let camera_from_world = self.attributes.ctm;
+        let world_from_camera = camera_from_world.inverse().ok_or("camera transformation is singular")?;
+        self.camera = Some(CameraDefinition {
+            camera_from_world,
+            fov: d.float("fov").unwrap_or(90.0),
+            lens_radius: d.float("lensradius").unwrap_or(0.0),
+            focal_distance: d.float("focaldistance").unwrap_or(1e6),
+        });
+        self.coordinate_systems.insert("camera".to_string(), world_from_camera);
+
+        // pbrt's camera space is left-handed, this renderer's right-handed: unless the camera
+        // transformation already mirrors, mirror the world so images are not flipped.
+        self.mirror = if determinant(&world_from_camera) > 0.0 { Matrix4::scale(Vec3::new(-1.0, 1.0, 1.0)) } else { Matrix4::IDENTITY };
+        Ok(())
+    }
+
+    fn material(&mut self, d: &Directive, kind: &str) -> Option<&'static dyn Material> {
+        let material: &'static dyn Material = match kind {
+            "" | "none" | "interface" => return None,
+            "diffuse" | "matte" => Box::leak(Box::new(Lambertian { albedo: self.color(d, &["reflectance", "Kd"], DEFAULT_MATERIAL.albedo) })),
+            "conductor" | "metal" => Box::leak(Box::new(self.conductor(d, kind))),
+            "dielectric" | "glass" | "thindielectric" => {
+                if kind == "thindielectric" {
+                    self.warn(d.line, "thin dielectrics are rendered as solid".to_string());
+                }
+                if d.float("roughness").or(d.float("uroughness")).is_some_and(|roughness| roughness > 0.0) {
+                    self.warn(d.line, "rough dielectrics are rendered smooth".to_string());
+                }
+                let ref_idx = match d.param("eta").or(d.param("index")) {
+                    Some(param) if param.kind == "spectrum" => {
+                        self.warn(d.line, "dielectric spectra are approximated by an index of 1.5".to_string());
+                        1.5
+                    }
+                    _ => d.float("eta").or(d.float("index")).unwrap_or(1.5),
+                };
+                Box::leak(Box::new(Dielectric { ref_idx }))
+            }
+            kind => {
+                self.warn(d.line, format!("material \"{}\" is rendered as diffuse", kind));
+                Box::leak(Box::new(Lambertian { albedo: self.color(d, &["reflectance", "Kd"], DEFAULT_MATERIAL.albedo) }))
+            }
+        };
+        Some(material)
+    }
+
+    /// Metal with the color conductors reflect head-on, and fuzz from their roughness.
+    fn conductor(&mut self, d: &Directive, kind: &str) -> Metal {
+        let roughness = d.float("roughness").or(d.float("uroughness")).unwrap_or(if kind == "metal" { 0.01 } else { 0.0 });
+        let fuzz = if d.boolean("remaproughness", true) { roughness.max(0.0).sqrt() } else { roughness }.clamp(0.0, 1.0);
+        if d.param("reflectance").is_some() {
+            return Metal { albedo: self.color(d, &["reflectance"], Color::white()), fuzz };
+        }
+
+        let rgb = |name: &str| d.param(name).filter(|param| param.kind == "rgb" || param.kind == "color").and_then(|_| d.floats(name));
+        let albedo = if let (Some(eta), Some(k)) = (rgb("eta"), rgb("k")) {
+            // Fresnel reflectance at normal incidence from the complex index of refraction
+            let reflectance = |i: usize| {
+                let (eta, k) = (eta.get(i).copied().unwrap_or(1.0), k.get(i).copied().unwrap_or(0.0));
+                ((eta - 1.0).powi(2) + k * k) / ((eta + 1.0).powi(2) + k * k)
+            };
+            Color::new(reflectance(0), reflectance(1), reflectance(2))
+        } else {
+            let name = d.string("eta").or(d.string("k")).unwrap_or("metal-Cu-eta");
+            let metal = name.trim_start_matches("metal-").trim_end_matches("-eta").trim_end_matches("-k");
+            named_metal(metal).unwrap_or_else(|| {
+                self.warn(d.line, format!("unknown conductor spectrum \"{}\" is rendered as silver", name));
+                named_metal("Ag").unwrap()
+            })
+        };
+        Metal { albedo, fuzz }
+    }
+
+    /// Color of the first parameter found among `names`.
+    fn color(&mut self, d: &Directive, names: &[&str], default: Color) -> Color {
+        let Some(param) = names.iter().find_map(|name| d.param(name)) else {
+            return default;
+        };
+        let values = d.floats(&param.name).unwrap_or_default();
+        match (param.kind.as_str(), &values[..]) {
+            ("rgb" | "color", &[r, g, b]) => Color::new(r, g, b),
+            ("float", &[value]) => Color::new(value, value, value),
+            ("blackbody", _) => {
+                self.warn(d.line, "blackbody spectra are approximated as white".to_string());
+                Color::white()
+            }
+            ("texture", _) => {
+                self.warn(d.line, format!("textures are not supported; \"{}\" uses its default", param.name));
+                default
+            }
+            (kind, _) => {
+                self.warn(d.line, format!("\"{} {}\" is not supported; it uses its default", kind, param.name));
+                default
+            }
+        }
+    }
+
+    /// The `scale` of lights, a number or (in pbrt-v3) a color.
+    fn scale(&mut self, d: &Directive) -> Color {
+        match d.floats("scale").as_deref() {
+            Some(&[scale]) => Color::new(scale, scale, scale),
+            Some(_) => self.color(d, &["scale"], Color::white()),
+            None => Color::white(),
+        }
+    }
+
+    fn shape(&mut self, d: &Directive) -> Result<(), String> {
+        let Some(material) = self.attributes.area_light.or(self.attributes.material) else {
+            return Ok(());
+        };
+        let shape: Arc<dyn Hittable + Send + Sync> = match d.kind() {
+            "sphere" => {
+                if ["zmin", "zmax", "phimax"].iter().any(|name| d.param(name).is_some()) {
+                    self.warn(d.line, "partial spheres are rendered whole".to_string());
+                }
+                Arc::new(Sphere::new(Point3::default(), d.float("radius").unwrap_or(1.0), material))
+            }
+            "trianglemesh" | "bilinearmesh" => Arc::new(mesh(d, material)?),
+            "plymesh" => {
+                let path = self.directory.join(d.string("filename").unwrap_or_default());
+                match TriangleMesh::load(&path.to_string_lossy(), 0.0, material) {
+                    Ok(mesh) => Arc::new(mesh),
+                    Err(error) => {
+                        self.warn(d.line, format!("skipped plymesh {}: {}", path.display(), error));
+                        return Ok(());
+                    }
+                }
+            }
+            kind => {
+                self.warn(d.line, format!("shape \"{}\" is not supported", kind));
+                return Ok(());
+            }
+        };
+        if d.param("alpha").is_some() {
+            self.warn(d.line, "alpha cutouts are ignored".to_string());
+        }
+        self.place(d.line, shape);
+        Ok(())
+    }
+
+    /// Adds an object under the current transformation, to the world or to the instance being
+    /// defined.
+    fn place(&mut self, line: usize, object: Arc<dyn Hittable + Send + Sync>) {
+        let matrix = match self.object {
+            Some(_) => self.attributes.ctm,
+            None => self.mirror * self.attributes.ctm,
+        };
+        if matrix.inverse().is_none() {
+            self.warn(line, "skipped an object with a singular transformation".to_string());
+            return;
+        }
+        match &mut self.object {
+            Some((_, parts)) => parts.push(Arc::new(Transform::new(object, matrix))),
+            None => self.scene.world.add(Transform::new(object, matrix)),
+        }
+    }
+
+    fn finish(mut self) -> Scene {
+        if let Some((name, _)) = &self.object {
+            let message = format!("object \"{}\" is never ended", name);
+            self.warn(0, message);
+        }
+        let camera = self.camera.unwrap_or(CameraDefinition { camera_from_world: Matrix4::IDENTITY, fov: 90.0, lens_radius: 0.0, focal_distance: 1e6 });
+        let world_from_camera = self.mirror * camera.camera_from_world.inverse().unwrap_or_default();
+        let eye = world_from_camera.transform_point(Point3::default());
+        let forward = world_from_camera.transform_vector(Vec3::new(0.0, 0.0, 1.0));
+
+        // pbrt's field of view spans the shorter image axis.
+        let aspect_ratio = self.resolution.map(|[width, height]| width as f64 / height as f64);
+        let vfov = match aspect_ratio {
+            Some(aspect_ratio) if aspect_ratio < 1.0 => 2.0 * ((camera.fov * DEG_TO_RAD / 2.0).tan() / aspect_ratio).atan() * RAD_TO_DEG,
+            _ => camera.fov,
+        };
+        let depth_of_field = (camera.lens_radius > 0.0).then(|| {
+            let focus_distance = camera.focal_distance * forward.length();
+            (2.0 * (camera.lens_radius / camera.focal_distance).atan() * RAD_TO_DEG, focus_distance)
+        });
+        self.scene.camera = Some(SceneCamera {
+            eye,
+            look_at: eye + forward,
+            up: Vec3::unit_vector(world_from_camera.transform_vector(Vec3::new(0.0, 1.0, 0.0))),
+            vfov,
+            projection: Projection::Perspective,
+            aspect_ratio,
+            image_width: self.resolution.map(|[width, _]| width),
+            samples_per_pixel: self.samples_per_pixel,
+            depth_of_field,
+        });
+        self.scene
+    }
+}
+
+/// pbrt's default material.
+static DEFAULT_MATERIAL: Lambertian = Lambertian { albedo: Color::new(0.5, 0.5, 0.5) };
+
+/// Triangles of a `trianglemesh`, or of a `bilinearmesh` with each patch split in two.
+fn mesh(d: &Directive, material: &'static dyn Material) -> Result<TriangleMesh, String> {
+    let points = d.floats("P").ok_or("mesh without positions \"P\"")?;
+    if points.is_empty() || !points.len().is_multiple_of(3) {
+        return Err("mesh positions \"P\" must be triples".to_string());
+    }
+    let positions: Vec<Point3> = points.chunks_exact(3).map(|p| Point3::new(p[0], p[1], p[2])).collect();
+    let corners = if d.kind() == "bilinearmesh" { 4 } else { 3 };
+    let indices: Vec<u32> = match d.floats("indices") {
+        Some(indices) => {
+            if indices.iter().any(|&index| index < 0.0 || index.fract() != 0.0 || index > u32::MAX as f64) {
+                return Err("mesh \"indices\" must be non-negative integers".to_string());
+            }
+            indices.into_iter().map(|index| index as u32).collect()
+        }
+        None if positions.len() == corners => (0..corners as u32).collect(),
+        None => return Err("mesh without \"indices\"".to_string()),
+    };
+    if !indices.len().is_multiple_of(corners) || indices.iter().any(|&index| index as usize >= positions.len()) {
+        return Err("mesh \"indices\" do not match its positions".to_string());
+    }
+    let triangles = if corners == 4 {
+        // Patch corners are ordered p00, p10, p01, p11.
+        indices.chunks_exact(4).flat_map(|q| [[q[0], q[1], q[3]], [q[0], q[3], q[2]]]).collect()
+    } else {
+        indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect()
+    };
+    let vertex_count = positions.len();
+    let mesh = TriangleMesh::new(positions, triangles, material);
+    match d.floats("N") {
+        Some(normals) if normals.len() == 3 * vertex_count => {
+            Ok(mesh.with_vertex_normals(normals.chunks_exact(3).map(|n| Vec3::new(n[0], n[1], n[2])).collect()))
+        }
+        Some(_) => Err("mesh normals \"N\" do not match its positions".to_string()),
+        None => Ok(mesh),
+    }
+}
+
+/// pbrt's `LookAt`: the camera space of a viewer at `eye`, with x to the right, y up and z
+/// towards `target`.
+fn look_at(eye: Point3, target: Point3, up: Vec3) -> Result<Matrix4, String> {
+    if (target - eye).near_zero() {
+        return Err("LookAt eye and target coincide".to_string());
+    }
+    let direction = Vec3::unit_vector(target - eye);
+    let right = Vec3::cross(Vec3::unit_vector(up), direction);
+    if right.near_zero() {
+        return Err("LookAt up vector is parallel to the viewing direction".to_string());
+    }
+    let right = Vec3::unit_vector(right);
+    let new_up = Vec3::cross(direction, right);
+    let world_from_camera = Matrix4::from_columns([
+        right.x, right.y, right.z, 0.0,
+        new_up.x, new_up.y, new_up.z, 0.0,
+        direction.x, direction.y, direction.z, 0.0,
+        eye.x, eye.y, eye.z, 1.0,
+    ]);
+    world_from_camera.inverse().ok_or_else(|| "LookAt transformation is singular".to_string())
+}
+
+/// Determinant of the linear part of an affine transformation; negative when it mirrors.
+fn determinant(matrix: &Matrix4) -> f64 {
+    let m = &matrix.m;
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1]) - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+/// Linear color of a metal seen head-on, for pbrt's named `metal-*-eta` spectra.
+fn named_metal(name: &str) -> Option<Color> {
+    Some(match name {
+        "Ag" => Color::new(0.97, 0.96, 0.91),
+        "Al" => Color::new(0.91, 0.92, 0.92),
+        "Au" => Color::new(1.0, 0.78, 0.34),
+        "Cu" => Color::new(0.96, 0.64, 0.54),
+        "CuZn" => Color::new(0.91, 0.78, 0.42),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directives(text: &str) -> Vec<Directive> {
+        parse(tokenize(text).unwrap()).unwrap()
+    }
+
+    fn import(text: &str) -> Result<Scene, String> {
+        let mut importer = Importer::new(PathBuf::new());
+        importer.run("test.pbrt", text, 0)?;
+        Ok(importer.finish())
+    }
+
+    #[test]
+    fn tokenizes() {
+        let text = "Shape \"sphere\" # a comment \"with quotes\"\n  \"float radius\" [ -1.5e1 .5 ] \"bool b\" true\n\"a \\\"b\\\"\\n\"";
+        let tokens = tokenize(text).unwrap();
+        assert_eq!(tokens, [
+            (Token::Word("Shape".to_string()), 1),
+            (Token::Str("sphere".to_string()), 1),
+            (Token::Str("float radius".to_string()), 2),
+            (Token::Open, 2),
+            (Token::Num(-15.0), 2),
+            (Token::Num(0.5), 2),
+            (Token::Close, 2),
+            (Token::Str("bool b".to_string()), 2),
+            (Token::Str("true".to_string()), 2),
+            (Token::Str("a \"b\"\n".to_string()), 3),
+        ]);
+        assert!(tokenize("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_tokens() {
+        assert_eq!(tokenize("Shape\n\"sphere").unwrap_err().0, 2);
+        assert_eq!(tokenize("Shape \"sphere\nTranslate").unwrap_err().0, 1);
+        assert_eq!(tokenize("Translate 1 2.0.0 3").unwrap_err(), (1, "unexpected \"2.0.0\"".to_string()));
+        assert!(tokenize("Shape \"sphere\\").is_err());
+    }
+
+    #[test]
+    fn parses_directives() {
+        let parsed = directives(concat!(
+            "LookAt 0 0 5  0 0 0  0 1 0\n",
+            "Translate [ 1 2 3 ]\n",
+            "MakeNamedMaterial \"red paint\" \"string type\" \"diffuse\" \"rgb reflectance\" [ 0.8 0.1 0.1 ]\n",
+            "Texture \"checks\" \"spectrum\" \"checkerboard\" \"float uscale\" 4\n",
+            "WorldBegin\n",
+            "Shape \"sphere\" \"float radius\" [ 2 ] \"bool flag\" false\n",
+        ));
+        let names: Vec<&str> = parsed.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["LookAt", "Translate", "MakeNamedMaterial", "Texture", "WorldBegin", "Shape"]);
+        assert_eq!(parsed[0].numbers_n::<9>().unwrap(), [0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(parsed[1].numbers(), [1.0, 2.0, 3.0]);
+        assert!(parsed[1].numbers_n::<4>().is_err());
+        assert_eq!(parsed[2].kind(), "red paint");
+        assert_eq!(parsed[2].string("type"), Some("diffuse"));
+        assert_eq!(parsed[2].param("reflectance").unwrap().kind, "rgb");
+        assert_eq!(parsed[2].floats("reflectance").unwrap(), [0.8, 0.1, 0.1]);
+        assert_eq!(parsed[3].args.len(), 3);
+        assert_eq!(parsed[3].float("uscale"), Some(4.0));
+        assert_eq!(parsed[5].line, 6);
+        assert_eq!(parsed[5].kind(), "sphere");
+        assert_eq!(parsed[5].float("radius"), Some(2.0));
+        assert!(!parsed[5].boolean("flag", true));
+        assert!(parsed[5].boolean("missing", true));
+    }
+
+    #[test]
+    fn rejects_malformed_directives() {
+        for (text, line) in [
+            ("1 2 3", 1),
+            ("WorldBegin\nShape 1", 2),
+            ("Translate [ 1 2 3", 1),
+            ("Translate 1 2 3 ]", 1),
+            ("Shape \"sphere\" \"float radius two\" 1", 1),
+            ("Shape \"sphere\"\n\"float radius\"", 1), // Errors point at the directive
+            ("Shape \"trianglemesh\" \"point3 P\" [ 0 0 0 WorldEnd", 1),
+        ] {
+            let error = tokenize(text).and_then(parse).err();
+            assert_eq!(error.map(|(line, _)| line), Some(line), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn builds_meshes() {
+        let parsed = directives(concat!(
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0  1 1 0 ] \"integer indices\" [ 0 1 2  2 1 3 ]\n",
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0 ] \"normal N\" [ 0 0 1  0 0 1  0 0 1 ]\n",
+            "Shape \"bilinearmesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0  1 1 0 ]\n",
+        ));
+        let counts: Vec<usize> = parsed.iter().map(|d| mesh(d, &DEFAULT_MATERIAL).unwrap().triangle_count()).collect();
+        assert_eq!(counts, [2, 1, 2]);
+    }
+
+    #[test]
+    fn rejects_malformed_meshes() {
+        let parsed = directives(concat!(
+            "Shape \"trianglemesh\" \"integer indices\" [ 0 1 2 ]\n",
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 ]\n",
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0  1 1 0 ]\n",
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0 ] \"integer indices\" [ 0 1 3 ]\n",
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0 ] \"integer indices\" [ 0 1 ]\n",
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0 ] \"integer indices\" [ 0 1 -1 ]\n",
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0 ] \"integer indices\" [ 0 1 1.5 ]\n",
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0 ] \"normal N\" [ 0 0 1 ]\n",
+        ));
+        for d in &parsed {
+            assert!(mesh(d, &DEFAULT_MATERIAL).is_err(), "line {}", d.line);
+        }
+    }
+
+    #[test]
+    fn imports_a_scene() {
+        let scene = import(concat!(
+            "LookAt 0 0 -5  0 0 0  0 1 0\n",
+            "Camera \"perspective\" \"float fov\" [ 40 ]\n",
+            "Film \"rgb\" \"integer xresolution\" [ 400 ] \"integer yresolution\" [ 200 ]\n",
+            "Sampler \"halton\" \"integer pixelsamples\" 64\n",
+            "WorldBegin\n",
+            "LightSource \"infinite\" \"rgb L\" [ 0.1 0.2 0.3 ]\n",
+            "AttributeBegin\n",
+            "  Material \"conductor\" \"spectrum eta\" \"metal-Au-eta\" \"spectrum k\" \"metal-Au-k\"\n",
+            "  Translate 1 0 0\n",
+            "  Shape \"sphere\" \"float radius\" 0.5\n",
+            "AttributeEnd\n",
+            "Shape \"trianglemesh\" \"point3 P\" [ 0 0 0  1 0 0  0 1 0 ]\n",
+            "Shape \"cylinder\"\n",
+            "Shape \"cylinder\"\n",
+        )).unwrap();
+
+        assert_eq!(scene.world.objects.len(), 2);
+        assert_eq!(scene.background, Some(Color::new(0.1, 0.2, 0.3)));
+        assert_eq!(scene.warnings, ["test.pbrt:13: shape \"cylinder\" is not supported"]);
+
+        // pbrt's world is mirrored along x, so the sphere at x = 1 lands at x = -1.
+        let sphere = scene.world.objects[0].bounding_box();
+        assert!((sphere.x.min + 1.5).abs() < 1e-3 && (sphere.x.max + 0.5).abs() < 1e-3);
+
+        let camera = scene.camera.unwrap();
+        assert!((camera.eye - Point3::new(0.0, 0.0, -5.0)).length() < 1e-9);
+        assert!((camera.look_at - Point3::new(0.0, 0.0, -4.0)).length() < 1e-9);
+        assert!((camera.up - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert_eq!(camera.vfov, 40.0);
+        assert_eq!(camera.aspect_ratio, Some(2.0));
+        assert_eq!((camera.image_width, camera.samples_per_pixel), (Some(400), Some(64)));
+        assert!(camera.depth_of_field.is_none());
+    }
+
+    #[test]
+    fn field_of_view_spans_the_shorter_axis() {
+        let scene = import("Camera \"perspective\" \"float fov\" 90\nFilm \"rgb\" \"integer xresolution\" 100 \"integer yresolution\" 200\n").unwrap();
+        let vfov = scene.camera.unwrap().vfov;
+        assert!((vfov - 2.0 * 2f64.atan() * RAD_TO_DEG).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reports_errors_with_their_location() {
+        assert_eq!(import("WorldBegin\nTranslate 1 2").err().unwrap(), "test.pbrt:2: Translate expects 3 numbers");
+        assert_eq!(import("LookAt 0 0 0  0 1 0  0 1 0").err().unwrap(), "test.pbrt:1: LookAt up vector is parallel to the viewing direction");
+        for resolution in ["0", "2.5", "1e30"] {
+            let error = import(&format!("Film \"rgb\" \"integer xresolution\" {}", resolution)).err().unwrap();
+            assert_eq!(error, "test.pbrt:1: Film resolution must be a positive integer up to 65536");
+        }
+        assert_eq!(import("ObjectEnd").err().unwrap(), "test.pbrt:1: ObjectEnd without ObjectBegin");
+        assert!(import("Shape \"trianglemesh\" \"point3 P\" [ 0 0 0 ]").is_err());
+        assert!(import("Include \"missing.pbrt\"").err().unwrap().starts_with("test.pbrt:1: missing.pbrt: "));
+    }
+
+    #[test]
+    fn instances_objects_and_named_materials() {
+        let scene = import(concat!(
+            "WorldBegin\n",
+            "MakeNamedMaterial \"glass\" \"string type\" \"dielectric\" \"float eta\" 1.33\n",
+            "ObjectBegin \"ball\"\n",
+            "  NamedMaterial \"glass\"\n",
+            "  Shape \"sphere\"\n",
+            "ObjectEnd\n",
+            "ObjectInstance \"ball\"\n",
+            "Translate 3 0 0\n",
+            "ObjectInstance \"ball\"\n",
+            "ObjectInstance \"box\"\n",
+            "NamedMaterial \"paint\"\n",
+            "AttributeEnd\n",
+        )).unwrap();
+        assert_eq!(scene.world.objects.len(), 2);
+        assert_eq!(scene.warnings, [
+            "test.pbrt:10: unknown or empty object \"box\"",
+            "test.pbrt:11: unknown material \"paint\"",
+            "test.pbrt:12: unmatched AttributeEnd",
+        ]);
+    }
+
+    #[test]
+    fn look_at_and_determinant() {
+        let camera_from_world = look_at(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)).unwrap();
+        assert_eq!(camera_from_world, Matrix4::IDENTITY);
+        assert!(look_at(Point3::new(1.0, 1.0, 1.0), Point3::new(1.0, 1.0, 1.0), Vec3::new(0.0, 1.0, 0.0)).is_err());
+        assert_eq!(determinant(&Matrix4::scale(Vec3::new(-1.0, 2.0, 3.0))), -6.0);
+        assert_eq!(determinant(&Matrix4::rotation(30.0, Vec3::new(1.0, 1.0, 0.0))).round(), 1.0);
+    }
+}
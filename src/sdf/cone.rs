@@ -0,0 +1,157 @@
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::onb::Onb;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+use crate::sdf::disk::disk_extent;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Part {
+    Side,
+    Bottom,
+    Top,
+}
+
+/// Cone along `axis` from `base`, narrowing (or widening) from `base_radius` to
+/// `top_radius` at `base + axis`. A top radius of 0 gives a pointed cone; equal radii give a
+/// cylinder. Capped cones are closed by disks at both ends, so they work with `Csg`.
+///
+/// On the side, u is the angle around the axis as a fraction of a turn and v the height as a
+/// fraction of the length; the caps have `Disk` UVs.
+pub struct Cone {
+    pub base: Point3,
+    pub height: f64,
+    pub base_radius: f64,
+    pub top_radius: f64,
+    pub capped: bool,
+    pub onb: Onb, // w points along the axis
+    pub bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+impl Cone {
+    pub fn new(
+        base: Point3,
+        axis: Vec3,
+        base_radius: f64,
+        top_radius: f64,
+        capped: bool,
+        material: &'static dyn Material,
+    ) -> Self {
+        let onb = Onb::new(axis);
+        let top = base + axis;
+        let base_extent = disk_extent(onb.w, base_radius);
+        let top_extent = disk_extent(onb.w, top_radius);
+        let bbox = Aabb::from_aabb(
+            &Aabb::from_points(&(base - base_extent), &(base + base_extent)),
+            &Aabb::from_points(&(top - top_extent), &(top + top_extent)),
+        );
+        Cone { base, height: axis.length(), base_radius, top_radius, capped, onb, bbox, material }
+    }
+
+    /// Change in radius per unit of height.
+    fn slope(&self) -> f64 {
+        (self.top_radius - self.base_radius) / self.height
+    }
+
+    /// The ray in the cone's local frame, with the base center at the origin.
+    fn local_ray(&self, r: Ray) -> (Vec3, Vec3) {
+        (self.onb.local(r.origin - self.base), self.onb.local(r.direction))
+    }
+
+    /// Every intersection with the infinite surfaces that lies on the cone, in no particular
+    /// order. Missing intersections are NaN.
+    fn candidates(&self, r: Ray) -> [(f64, Part); 4] {
+        let (o, d) = self.local_ray(r);
+        let k = self.slope();
+        let mut candidates = [(f64::NAN, Part::Side); 4];
+
+        // Side: x² + y² = (r0 + k z)², with half the usual b coefficient.
+        let radius_o = self.base_radius + k * o.z;
+        let a = d.x * d.x + d.y * d.y - k * k * d.z * d.z;
+        let half_b = o.x * d.x + o.y * d.y - k * d.z * radius_o;
+        let c = o.x * o.x + o.y * o.y - radius_o * radius_o;
+        let on_side = |t: f64| (0.0..=self.height).contains(&(o.z + t * d.z));
+        if a.abs() < 1e-12 {
+            // The ray runs parallel to the slope and crosses the surface once.
+            if half_b != 0.0 && on_side(-c / (2.0 * half_b)) {
+                candidates[0].0 = -c / (2.0 * half_b);
+            }
+        } else {
+            let discriminant = half_b * half_b - a * c;
+            if discriminant >= 0.0 {
+                let sqrtd = discriminant.sqrt();
+                for (candidate, t) in candidates.iter_mut().zip([(-half_b - sqrtd) / a, (-half_b + sqrtd) / a]) {
+                    if on_side(t) {
+                        candidate.0 = t;
+                    }
+                }
+            }
+        }
+
+        // Caps
+        if self.capped && d.z != 0.0 {
+            for (index, part, z, radius) in [(2, Part::Bottom, 0.0, self.base_radius), (3, Part::Top, self.height, self.top_radius)] {
+                let t = (z - o.z) / d.z;
+                let (x, y) = (o.x + t * d.x, o.y + t * d.y);
+                if x * x + y * y <= radius * radius {
+                    candidates[index] = (t, part);
+                }
+            }
+        }
+        candidates
+    }
+
+    fn fill_record(&self, r: Ray, t: f64, part: Part, rec: &mut HitRecord) {
+        let (o, d) = self.local_ray(r);
+        let p = o + t * d;
+        let (outward_normal, v) = match part {
+            Part::Side => {
+                let radius = self.base_radius + self.slope() * p.z;
+                let gradient = Vec3::new(p.x, p.y, -self.slope() * radius);
+                let normal = if gradient.near_zero() { Vec3::new(0.0, 0.0, 1.0) } else { Vec3::unit_vector(gradient) };
+                (normal, p.z / self.height)
+            }
+            Part::Bottom => (Vec3::new(0.0, 0.0, -1.0), p.x.hypot(p.y) / self.base_radius),
+            Part::Top => (Vec3::new(0.0, 0.0, 1.0), p.x.hypot(p.y) / self.top_radius),
+        };
+        rec.t = t;
+        rec.point = r.at(t);
+        rec.u = Onb::azimuth(p);
+        rec.v = v;
+        rec.material = Some(self.material);
+        rec.set_face_normal(r, self.onb.transform(outward_normal));
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let closest = self.candidates(r).into_iter()
+            .filter(|&(t, _)| interval.surrounds(t))
+            .min_by(|(a, _), (b, _)| a.total_cmp(b));
+        let Some((t, part)) = closest else {
+            return false;
+        };
+        self.fill_record(r, t, part, rec);
+        true
+    }
+
+    fn all_hits(&self, r: Ray, interval: Interval, hits: &mut Vec<HitRecord>) {
+        stats::count(Counter::PrimitiveTests);
+        let mut candidates: Vec<_> = self.candidates(r).into_iter().filter(|&(t, _)| interval.surrounds(t)).collect();
+        candidates.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        for (t, part) in candidates {
+            let mut rec = HitRecord::default();
+            self.fill_record(r, t, part, &mut rec);
+            hits.push(rec);
+        }
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference, // The first object with the second removed
+}
+
+impl CsgOperation {
+    fn inside(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_a || in_b,
+            CsgOperation::Intersection => in_a && in_b,
+            CsgOperation::Difference => in_a && !in_b,
+        }
+    }
+}
+
+/// Boolean combination of two closed objects, such as spheres, boxes or other `Csg` nodes.
+///
+/// Both objects report all their intersections along the ray. Walking through them in order
+/// tracks whether the ray is inside each object; the surfaces where the combined inside
+/// state changes are the surfaces of the result.
+pub struct Csg {
+    pub operation: CsgOperation,
+    pub a: Arc<dyn Hittable + Send + Sync>,
+    pub b: Arc<dyn Hittable + Send + Sync>,
+    pub bbox: Aabb,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, a: Arc<dyn Hittable + Send + Sync>, b: Arc<dyn Hittable + Send + Sync>) -> Self {
+        let (box_a, box_b) = (a.bounding_box(), b.bounding_box());
+        let bbox = match operation {
+            CsgOperation::Union => Aabb::from_aabb(box_a, box_b),
+            CsgOperation::Intersection => {
+                let overlap = |a: &Interval, b: &Interval| Interval::new(a.min.max(b.min), a.max.min(b.max));
+                Aabb::from_intervals(overlap(&box_a.x, &box_b.x), overlap(&box_a.y, &box_b.y), overlap(&box_a.z, &box_b.z))
+            }
+            CsgOperation::Difference => box_a.clone(),
+        };
+        Csg { operation, a, b, bbox }
+    }
+
+    pub fn union(a: Arc<dyn Hittable + Send + Sync>, b: Arc<dyn Hittable + Send + Sync>) -> Self {
+        Csg::new(CsgOperation::Union, a, b)
+    }
+
+    pub fn intersection(a: Arc<dyn Hittable + Send + Sync>, b: Arc<dyn Hittable + Send + Sync>) -> Self {
+        Csg::new(CsgOperation::Intersection, a, b)
+    }
+
+    pub fn difference(a: Arc<dyn Hittable + Send + Sync>, b: Arc<dyn Hittable + Send + Sync>) -> Self {
+        Csg::new(CsgOperation::Difference, a, b)
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        if !self.bbox.hit(r, interval.clone()) {
+            return false;
+        }
+        let mut hits = Vec::new();
+        self.all_hits(r, interval, &mut hits);
+        match hits.into_iter().next() {
+            Some(first) => {
+                *rec = first;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn all_hits(&self, r: Ray, interval: Interval, hits: &mut Vec<HitRecord>) {
+        if !self.bbox.hit(r, interval.clone()) {
+            return;
+        }
+        // The whole line is needed to know whether the ray starts inside each object.
+        let mut a_hits = Vec::new();
+        let mut b_hits = Vec::new();
+        self.a.all_hits(r, Interval::UNIVERSE, &mut a_hits);
+        self.b.all_hits(r, Interval::UNIVERSE, &mut b_hits);
+
+        // Before its first surface, the ray is inside an object if that surface is an exit.
+        let mut in_a = a_hits.first().is_some_and(|rec| !rec.front_face);
+        let mut in_b = b_hits.first().is_some_and(|rec| !rec.front_face);
+        let mut inside = self.operation.inside(in_a, in_b);
+
+        let mut events: Vec<(HitRecord, bool)> = a_hits.into_iter().map(|rec| (rec, true))
+            .chain(b_hits.into_iter().map(|rec| (rec, false)))
+            .collect();
+        events.sort_by(|(a, _), (b, _)| a.t.total_cmp(&b.t));
+
+        for (mut rec, from_a) in events {
+            if from_a {
+                in_a = !in_a;
+            } else {
+                in_b = !in_b;
+            }
+            let now_inside = self.operation.inside(in_a, in_b);
+            if now_inside == inside {
+                continue;
+            }
+            inside = now_inside;
+            if interval.surrounds(rec.t) {
+                // The normal already faces the ray; which side is outside depends on the
+                // operation (surfaces cut out by a difference face inwards).
+                rec.front_face = now_inside;
+                hits.push(rec);
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.a.acceleration_bytes() + self.b.acceleration_bytes()
+    }
+}
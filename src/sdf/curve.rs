@@ -0,0 +1,293 @@
+use std::f64::consts::SQRT_2;
+
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::flat_bvh::FlatBvh;
+use crate::math::interval::Interval;
+use crate::math::onb::Onb;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+
+/// How the control points of a curve are interpreted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurveBasis {
+    Bezier, // Starts at the first point and ends at the last
+    BSpline, // Uniform cubic B-spline: passes near the points rather than through them
+}
+
+/// Cross-section of a curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurveMode {
+    Ribbon, // Flat strip turned towards the ray, or oriented by normals from `with_normals`
+    Tube, // Round, with normals all around the curve
+}
+
+/// Cubic curve whose width changes linearly along it, for hair, fur and grass.
+///
+/// Rays are intersected in a frame where they run along the z axis, as in pbrt: the curve is
+/// halved until the pieces are nearly straight, and each piece is tested by the distance
+/// from the ray to its closest point. `u` runs along the curve and `v` across it, and
+/// `HitRecord::tangent` gives the direction of the curve for hair shading.
+pub struct Curve {
+    pub control: [Point3; 4], // Bezier control points
+    pub width: [f64; 2], // At the start and end
+    pub normals: Option<[Vec3; 2]>, // Ribbon orientation at the start and end
+    pub mode: CurveMode,
+    pub u_range: [f64; 2], // Part of a longer strand covered by this curve
+    pub bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+impl Curve {
+    pub fn new(basis: CurveBasis, control: [Point3; 4], width: [f64; 2], mode: CurveMode, material: &'static dyn Material) -> Self {
+        let control = match basis {
+            CurveBasis::Bezier => control,
+            CurveBasis::BSpline => bspline_to_bezier(control),
+        };
+        Curve::segment(control, width, [0.0, 1.0], mode, material)
+    }
+
+    /// Curves along a strand through many points, with the width tapering from `width[0]` at
+    /// the root to `width[1]` at the tip. Bezier strands take 3n + 1 points, each curve
+    /// sharing its last point with the next; B-spline strands start a curve at every point.
+    pub fn strand(basis: CurveBasis, points: &[Point3], width: [f64; 2], mode: CurveMode, material: &'static dyn Material) -> Vec<Curve> {
+        let controls: Vec<[Point3; 4]> = match basis {
+            CurveBasis::Bezier => points.windows(4).step_by(3).map(|p| [p[0], p[1], p[2], p[3]]).collect(),
+            CurveBasis::BSpline => points.windows(4).map(|p| bspline_to_bezier([p[0], p[1], p[2], p[3]])).collect(),
+        };
+        let count = controls.len() as f64;
+        let width_at = |u: f64| width[0] + (width[1] - width[0]) * u;
+        controls.into_iter().enumerate().map(|(i, control)| {
+            let u_range = [i as f64 / count, (i + 1) as f64 / count];
+            Curve::segment(control, u_range.map(width_at), u_range, mode, material)
+        }).collect()
+    }
+
+    fn segment(control: [Point3; 4], width: [f64; 2], u_range: [f64; 2], mode: CurveMode, material: &'static dyn Material) -> Self {
+        // The curve stays inside the convex hull of its control points.
+        let half_width = width[0].max(width[1]) / 2.0;
+        let padding = Vec3::new(half_width, half_width, half_width);
+        let min = control.iter().fold(control[0], |min, &p| min.min(p)) - padding;
+        let max = control.iter().fold(control[0], |max, &p| max.max(p)) + padding;
+        let bbox = Aabb::from_points(&min, &max);
+        Curve { control, width, normals: None, mode, u_range, bbox, material }
+    }
+
+    /// Orients a ribbon by normals at its start and end, instead of turning it towards every
+    /// ray. Seen edge-on, an oriented ribbon vanishes.
+    pub fn with_normals(mut self, start: Vec3, end: Vec3) -> Self {
+        self.normals = Some([Vec3::unit_vector(start), Vec3::unit_vector(end)]);
+        self
+    }
+
+    /// The part of the curve between parameters `u0` and `u1`, with its own tighter box.
+    pub fn piece(&self, u0: f64, u1: f64) -> Curve {
+        let (head, _) = split_bezier(self.control, u1);
+        let (_, control) = split_bezier(head, if u1 > 0.0 { u0 / u1 } else { 0.0 });
+        let u_range = [u0, u1].map(|u| self.u_range[0] + (self.u_range[1] - self.u_range[0]) * u);
+        let mut piece = Curve::segment(control, [self.width_at(u0), self.width_at(u1)], u_range, self.mode, self.material);
+        piece.normals = self.normals.map(|_| [self.normal_at(u0).unwrap(), self.normal_at(u1).unwrap()]);
+        piece
+    }
+
+    fn width_at(&self, u: f64) -> f64 {
+        self.width[0] + (self.width[1] - self.width[0]) * u
+    }
+
+    fn normal_at(&self, u: f64) -> Option<Vec3> {
+        self.normals.map(|[start, end]| Vec3::unit_vector(start * (1.0 - u) + end * u))
+    }
+
+    /// Intersects the part of the curve between parameters `u[0]` and `u[1]`, given by its
+    /// control points in ray space, where the ray runs along z in units of distance.
+    fn hit_piece(&self, r: Ray, control: [Vec3; 4], u: [f64; 2], depth: u32, z_range: Interval, rec: &mut HitRecord) -> bool {
+        if depth > 0 {
+            let (left, right) = split_bezier(control, 0.5);
+            let middle = (u[0] + u[1]) / 2.0;
+            let mut z_range = z_range;
+            let mut hit = false;
+            for (half, span) in [(left, [u[0], middle]), (right, [middle, u[1]])] {
+                let half_width = self.width_at(span[0]).max(self.width_at(span[1])) / 2.0;
+                if overlaps_ray(&half, half_width, &z_range)
+                    && self.hit_piece(r, half, span, depth - 1, z_range.clone(), rec)
+                {
+                    hit = true;
+                    z_range.max = rec.t * r.direction.length();
+                }
+            }
+            return hit;
+        }
+
+        // Rays passing beyond either end of the piece belong to its neighbors.
+        let start_edge = (control[1].y - control[0].y) * -control[0].y + control[0].x * (control[0].x - control[1].x);
+        let end_edge = (control[2].y - control[3].y) * -control[3].y + control[3].x * (control[3].x - control[2].x);
+        if start_edge < 0.0 || end_edge < 0.0 {
+            return false;
+        }
+
+        // Closest point to the ray, treating the nearly straight piece as a line.
+        let ray_length = r.direction.length();
+        let (dx, dy) = (control[3].x - control[0].x, control[3].y - control[0].y);
+        let denominator = dx * dx + dy * dy;
+        if denominator == 0.0 {
+            return false;
+        }
+        let w = ((-control[0].x * dx - control[0].y * dy) / denominator).clamp(0.0, 1.0);
+        let u_hit = u[0] + (u[1] - u[0]) * w;
+        let ribbon_normal = self.normal_at(u_hit);
+        let mut width = self.width_at(u_hit);
+        if let (CurveMode::Ribbon, Some(normal)) = (self.mode, ribbon_normal) {
+            width *= Vec3::dot_two(normal, r.direction).abs() / ray_length;
+        }
+        let (center, derivative) = evaluate_bezier(control, w);
+        let distance_squared = center.x * center.x + center.y * center.y;
+        let radius = width / 2.0;
+        if distance_squared > radius * radius {
+            return false;
+        }
+        let z = match self.mode {
+            CurveMode::Ribbon => center.z,
+            CurveMode::Tube => center.z - (radius * radius - distance_squared).sqrt(),
+        };
+        if !z_range.surrounds(z) {
+            return false;
+        }
+
+        // Which side of the curve the ray passes on, seen along the ray.
+        let distance = distance_squared.sqrt();
+        let side = derivative.x * -center.y + center.x * derivative.y;
+        let v = if side > 0.0 { 0.5 + distance / width } else { 0.5 - distance / width };
+
+        let (world_center, world_derivative) = evaluate_bezier(self.control, u_hit);
+        let tangent = Vec3::unit_vector(world_derivative);
+        rec.t = z / ray_length;
+        rec.point = r.at(rec.t);
+        rec.u = self.u_range[0] + (self.u_range[1] - self.u_range[0]) * u_hit;
+        rec.v = v;
+        rec.material = Some(self.material);
+        let facing = -r.direction - tangent * Vec3::dot_two(-r.direction, tangent);
+        let normal = match (self.mode, ribbon_normal) {
+            (CurveMode::Tube, _) if !(rec.point - world_center).near_zero() => rec.point - world_center,
+            (CurveMode::Ribbon, Some(normal)) => normal,
+            _ => facing,
+        };
+        rec.set_face_normal(r, Vec3::unit_vector(normal));
+        rec.tangent = tangent;
+        true
+    }
+}
+
+impl Hittable for Curve {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let half_width = self.width[0].max(self.width[1]) / 2.0;
+        if half_width <= 0.0 {
+            return false;
+        }
+        let ray_length = r.direction.length();
+        let frame = Onb::new(r.direction);
+        let control = self.control.map(|p| frame.local(p - r.origin));
+        let z_range = Interval::new(interval.min * ray_length, interval.max * ray_length);
+        if !overlaps_ray(&control, half_width, &z_range) {
+            return false;
+        }
+
+        // Enough halvings that the pieces stray from straight lines by a tenth of the width.
+        let bend = (0..2)
+            .map(|i| {
+                let d = control[i] - control[i + 1] * 2.0 + control[i + 2];
+                d.x.abs().max(d.y.abs()).max(d.z.abs())
+            })
+            .fold(0.0, f64::max);
+        let tolerance = half_width * 0.1;
+        let depth = ((SQRT_2 * 6.0 * bend / (8.0 * tolerance)).log2() / 2.0).ceil().clamp(0.0, 10.0) as u32;
+        self.hit_piece(r, control, [0.0, 1.0], depth, z_range, rec)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
+
+/// Many curves under a compact hierarchy of their own, such as the hairs on a head or the
+/// blades of a lawn. Curves are first cut into `pieces` parts each: a long bent strand has a
+/// loose box, and shorter pieces let the hierarchy skip most of it.
+pub struct CurveSet {
+    curves: Vec<Curve>,
+    bvh: FlatBvh,
+    bbox: Aabb,
+}
+
+impl CurveSet {
+    pub fn new(curves: Vec<Curve>, pieces: usize) -> Self {
+        let pieces = pieces.max(1);
+        let curves: Vec<Curve> = if pieces == 1 {
+            curves
+        } else {
+            curves.iter()
+                .flat_map(|curve| (0..pieces).map(move |i| curve.piece(i as f64 / pieces as f64, (i + 1) as f64 / pieces as f64)))
+                .collect()
+        };
+        let boxes: Vec<Aabb> = curves.iter().map(|curve| curve.bbox.clone()).collect();
+        let bvh = FlatBvh::new(&boxes);
+        let bbox = bvh.bounds();
+        CurveSet { curves, bvh, bbox }
+    }
+}
+
+impl Hittable for CurveSet {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        self.bvh.hit(r, interval, |index, interval| self.curves[index].hit(r, interval, rec).then_some(rec.t))
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.bvh.bytes()
+    }
+}
+
+/// Whether a piece of curve in ray space, widened by `half_width`, can reach the ray.
+fn overlaps_ray(control: &[Vec3; 4], half_width: f64, z_range: &Interval) -> bool {
+    let min = control.iter().fold(control[0], |min, &p| min.min(p));
+    let max = control.iter().fold(control[0], |max, &p| max.max(p));
+    min.x - half_width <= 0.0
+        && max.x + half_width >= 0.0
+        && min.y - half_width <= 0.0
+        && max.y + half_width >= 0.0
+        && max.z + half_width >= z_range.min
+        && min.z - half_width <= z_range.max
+}
+
+/// Point and derivative of a cubic Bezier curve, by de Casteljau's algorithm.
+fn evaluate_bezier(control: [Vec3; 4], u: f64) -> (Point3, Vec3) {
+    let lerp = |a: Vec3, b: Vec3| a * (1.0 - u) + b * u;
+    let first = [lerp(control[0], control[1]), lerp(control[1], control[2]), lerp(control[2], control[3])];
+    let second = [lerp(first[0], first[1]), lerp(first[1], first[2])];
+    // The derivative vanishes where control points coincide; the chord still gives a direction.
+    let derivative = if (second[1] - second[0]).near_zero() { control[3] - control[0] } else { (second[1] - second[0]) * 3.0 };
+    (lerp(second[0], second[1]), derivative)
+}
+
+/// Splits a cubic Bezier curve at `u` into the curves before and after it.
+fn split_bezier(control: [Vec3; 4], u: f64) -> ([Vec3; 4], [Vec3; 4]) {
+    let lerp = |a: Vec3, b: Vec3| a * (1.0 - u) + b * u;
+    let first = [lerp(control[0], control[1]), lerp(control[1], control[2]), lerp(control[2], control[3])];
+    let second = [lerp(first[0], first[1]), lerp(first[1], first[2])];
+    let point = lerp(second[0], second[1]);
+    ([control[0], first[0], second[0], point], [point, second[1], first[2], control[3]])
+}
+
+/// Bezier control points of one uniform cubic B-spline segment.
+fn bspline_to_bezier(p: [Point3; 4]) -> [Point3; 4] {
+    [
+        (p[0] + p[1] * 4.0 + p[2]) / 6.0,
+        (p[1] * 2.0 + p[2]) / 3.0,
+        (p[1] + p[2] * 2.0) / 3.0,
+        (p[1] + p[2] * 4.0 + p[3]) / 6.0,
+    ]
+}
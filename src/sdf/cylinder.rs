@@ -0,0 +1,33 @@
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+use crate::sdf::cone::Cone;
+
+/// Cylinder along `axis` from `base`, open or closed by disks at both ends. It is the cone
+/// with equal radii, and has the same UVs.
+pub struct Cylinder {
+    pub cone: Cone,
+}
+
+impl Cylinder {
+    pub fn new(base: Point3, axis: Vec3, radius: f64, capped: bool, material: &'static dyn Material) -> Self {
+        Cylinder { cone: Cone::new(base, axis, radius, radius, capped, material) }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        self.cone.hit(r, interval, rec)
+    }
+
+    fn all_hits(&self, r: Ray, interval: Interval, hits: &mut Vec<HitRecord>) {
+        self.cone.all_hits(r, interval, hits);
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        self.cone.bounding_box()
+    }
+}
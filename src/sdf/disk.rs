@@ -0,0 +1,130 @@
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::onb::Onb;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+
+/// Flat elliptical patch centered at `center`, with semi-axes `u` and `v`. u is the angle
+/// around the center as a fraction of a turn from `u`, and v the relative distance out
+/// towards the edge.
+pub struct Ellipse {
+    pub center: Point3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+    pub normal: Vec3,
+    pub d: f64,
+    pub bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+impl Ellipse {
+    pub fn new(center: Point3, u: Vec3, v: Vec3, material: &'static dyn Material) -> Self {
+        let n = Vec3::cross_two(u, v);
+        let normal = Vec3::unit_vector(n);
+        let d = Vec3::dot_two(normal, center);
+        let w = n / Vec3::dot_two(n, n);
+
+        // Extent along each axis of an ellipse spanned by u and v.
+        let extent = Vec3::new(u.x.hypot(v.x), u.y.hypot(v.y), u.z.hypot(v.z));
+        let bbox = Aabb::from_points(&(center - extent), &(center + extent));
+
+        Ellipse { center, u, v, w, normal, d, bbox, material }
+    }
+}
+
+impl Hittable for Ellipse {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let denom = Vec3::dot_two(self.normal, r.direction);
+        if denom.abs() < 1e-8 {
+            return false;
+        }
+        let t = (self.d - Vec3::dot_two(self.normal, r.origin)) / denom;
+        if !interval.contains(t) {
+            return false;
+        }
+
+        // Coordinates of the hit in units of the semi-axes.
+        let intersection = r.at(t);
+        let planar_hitpt_vector = intersection - self.center;
+        let alpha = Vec3::dot(self.w, Vec3::cross_two(planar_hitpt_vector, self.v));
+        let beta = Vec3::dot(self.w, Vec3::cross_two(self.u, planar_hitpt_vector));
+        let radius_squared = alpha * alpha + beta * beta;
+        if radius_squared > 1.0 {
+            return false;
+        }
+
+        rec.t = t;
+        rec.point = intersection;
+        rec.u = Onb::azimuth(Vec3::new(alpha, beta, 0.0));
+        rec.v = radius_squared.sqrt();
+        rec.material = Some(self.material);
+        rec.set_face_normal(r, self.normal);
+        true
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
+
+/// Flat circular disk facing along `normal`, with the same UVs as `Ellipse`.
+pub struct Disk {
+    pub center: Point3,
+    pub radius: f64,
+    pub onb: Onb, // w is the normal
+    pub bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: &'static dyn Material) -> Self {
+        let onb = Onb::new(normal);
+        let extent = disk_extent(onb.w, radius);
+        let bbox = Aabb::from_points(&(center - extent), &(center + extent));
+        Disk { center, radius, onb, bbox, material }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let denom = Vec3::dot_two(self.onb.w, r.direction);
+        if denom.abs() < 1e-8 {
+            return false;
+        }
+        let t = Vec3::dot_two(self.onb.w, self.center - r.origin) / denom;
+        if !interval.contains(t) {
+            return false;
+        }
+
+        let intersection = r.at(t);
+        let local = self.onb.local(intersection - self.center);
+        let distance = local.x.hypot(local.y);
+        if distance > self.radius {
+            return false;
+        }
+
+        rec.t = t;
+        rec.point = intersection;
+        rec.u = Onb::azimuth(local);
+        rec.v = distance / self.radius;
+        rec.material = Some(self.material);
+        rec.set_face_normal(r, self.onb.w);
+        true
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
+
+/// Half extents along each world axis of a circle with the given unit normal and radius.
+pub fn disk_extent(normal: Vec3, radius: f64) -> Vec3 {
+    let extent = |n: f64| radius * (1.0 - n * n).max(0.0).sqrt();
+    Vec3::new(extent(normal.x), extent(normal.y), extent(normal.z))
+}
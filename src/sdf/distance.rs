@@ -0,0 +1,119 @@
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+
+/// A signed distance field: negative inside the shape, positive outside, zero on the surface.
+pub trait Sdf: Send + Sync {
+    /// Signed distance from `p` to the surface. May underestimate, but by no more than the
+    /// factor given by `lipschitz`.
+    fn distance(&self, p: Point3) -> f64;
+
+    /// Box enclosing the surface. Unbounded shapes return `Aabb::universe()`.
+    fn bounds(&self) -> Aabb;
+
+    /// Bound on how fast the distance changes per unit of movement. Exact distance fields
+    /// have 1; deformations that stretch space have more, and sphere tracing divides its
+    /// steps by it to stay safe.
+    fn lipschitz(&self) -> f64 {
+        1.0
+    }
+
+    /// Value for coloring the surface at a point, stored in `HitRecord::orbit_trap`. Fractals
+    /// return their orbit trap; plain shapes have none.
+    fn orbit_trap(&self, _p: Point3) -> f64 {
+        0.0
+    }
+
+    /// Outward unit normal at a point on the surface, from the gradient of the distance.
+    fn normal(&self, p: Point3, epsilon: f64) -> Vec3 {
+        // Tetrahedral differences: four evaluations instead of six for central differences.
+        let k = [Vec3::new(1.0, -1.0, -1.0), Vec3::new(-1.0, -1.0, 1.0), Vec3::new(-1.0, 1.0, -1.0), Vec3::new(1.0, 1.0, 1.0)];
+        let gradient = k.iter().fold(Vec3::default(), |sum, &k| sum + k * self.distance(p + k * epsilon));
+        if gradient.near_zero() {
+            return Vec3::new(0.0, 1.0, 0.0);
+        }
+        Vec3::unit_vector(gradient)
+    }
+}
+
+/// Renders an `Sdf` by sphere tracing: marching along the ray in steps of the distance to
+/// the surface, which can never overshoot it.
+pub struct SphereTraced<S: Sdf> {
+    pub sdf: S,
+    pub material: &'static dyn Material,
+    pub epsilon: f64, // Distance to the surface that counts as a hit
+    pub max_steps: u32,
+    pub max_distance: f64, // Marching stops this far along the ray in unbounded shapes
+    pub bbox: Aabb,
+}
+
+impl<S: Sdf> SphereTraced<S> {
+    pub fn new(sdf: S, material: &'static dyn Material) -> Self {
+        let bounds = sdf.bounds();
+        // Relative to the size of bounded shapes, so scenes in any units get a sensible
+        // precision.
+        let size = bounds.x.size().max(bounds.y.size()).max(bounds.z.size());
+        let epsilon = if size.is_finite() { (size * 1e-5).max(1e-6) } else { 1e-4 };
+        let bbox = Aabb {
+            x: bounds.x.expand(2.0 * epsilon),
+            y: bounds.y.expand(2.0 * epsilon),
+            z: bounds.z.expand(2.0 * epsilon),
+        };
+        SphereTraced { sdf, material, epsilon, max_steps: 512, max_distance: 1e5, bbox }
+    }
+}
+
+impl<S: Sdf> Hittable for SphereTraced<S> {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let Some(clipped) = self.bbox.clip(r, interval.clone()) else {
+            return false;
+        };
+
+        // March in units of distance along a unit direction.
+        let ray_length = r.direction.length();
+        let direction = r.direction / ray_length;
+        let end = (clipped.max * ray_length).min(self.max_distance);
+        let step_scale = 1.0 / self.sdf.lipschitz();
+        let mut s = (clipped.min * ray_length).max(-self.max_distance);
+        let mut steps = 0;
+
+        // Rays scattered off the surface start on it; step off before looking for hits. Rays
+        // entering the box from outside may start right at the surface instead, a real hit.
+        let starts_inside = clipped.min == interval.min;
+        while starts_inside && self.sdf.distance(r.origin + direction * s).abs() < self.epsilon {
+            s += self.epsilon;
+            steps += 1;
+            if steps >= self.max_steps / 8 {
+                break;
+            }
+        }
+
+        while s <= end && steps < self.max_steps {
+            let p = r.origin + direction * s;
+            // Marching on the absolute distance also finds the surface from inside.
+            let distance = self.sdf.distance(p).abs();
+            if distance < self.epsilon {
+                rec.t = s / ray_length;
+                rec.point = p;
+                rec.u = 0.0;
+                rec.v = 0.0;
+                rec.orbit_trap = self.sdf.orbit_trap(p);
+                rec.set_face_normal(r, self.sdf.normal(p, self.epsilon));
+                rec.material = Some(self.material);
+                return true;
+            }
+            s += distance * step_scale;
+            steps += 1;
+        }
+        false
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
@@ -0,0 +1,265 @@
+//! Distance estimators for 3D fractals. Each also tracks an orbit trap: the closest the
+//! iterated point comes to the origin, which `SphereTraced` stores in the hit record so
+//! materials such as `OrbitTrapGradient` can color the fractal's structure.
+
+use crate::math::aabb::Aabb;
+use crate::math::vec3::{Point3, Vec3};
+use crate::sdf::distance::Sdf;
+
+/// The Mandelbulb: the 3D analogue of the Mandelbrot set, raising points to a power in
+/// spherical coordinates. The classic bulb has power 8.
+pub struct Mandelbulb {
+    pub power: f64,
+    pub iterations: u32,
+}
+
+impl Mandelbulb {
+    /// Distance estimate and orbit trap at `p`.
+    fn estimate(&self, p: Point3) -> (f64, f64) {
+        let mut z = p;
+        let mut dr = 1.0;
+        let mut r = z.length();
+        let mut trap = r;
+        for _ in 0..self.iterations {
+            if r > 2.0 || r == 0.0 {
+                break;
+            }
+            // The y axis is the pole, so the bulb stands upright.
+            let theta = (z.y / r).acos() * self.power;
+            let phi = z.z.atan2(z.x) * self.power;
+            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+            let zr = r.powf(self.power);
+            z = Vec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()) * zr + p;
+            r = z.length();
+            trap = trap.min(r);
+        }
+        if r == 0.0 {
+            return (0.0, trap);
+        }
+        (0.5 * r.ln() * r / dr, trap)
+    }
+}
+
+impl Sdf for Mandelbulb {
+    fn distance(&self, p: Point3) -> f64 {
+        self.estimate(p).0
+    }
+
+    fn bounds(&self) -> Aabb {
+        // Points further out than this grow on every iteration, so they escape.
+        let radius = 2.0f64.powf(1.0 / (self.power - 1.0));
+        Aabb::from_points(&Point3::new(-radius, -radius, -radius), &Point3::new(radius, radius, radius))
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.estimate(p).1
+    }
+}
+
+/// The Mandelbox: points are repeatedly folded into a box and a sphere, scaled and offset.
+/// Scales between 1 and 3 in magnitude give the classic shapes; 2 and -1.5 are popular.
+pub struct Mandelbox {
+    pub scale: f64,
+    pub iterations: u32,
+}
+
+impl Mandelbox {
+    const MIN_RADIUS_SQUARED: f64 = 0.25;
+    const FIXED_RADIUS_SQUARED: f64 = 1.0;
+
+    fn estimate(&self, p: Point3) -> (f64, f64) {
+        let mut z = p;
+        let mut dr = 1.0;
+        let mut trap = z.length();
+        for _ in 0..self.iterations {
+            // Box fold: reflect the parts outside [-1, 1] back in.
+            z = Vec3::new(box_fold(z.x), box_fold(z.y), box_fold(z.z));
+            // Sphere fold: invert points inside the fixed sphere, scaling up tiny ones linearly.
+            let r2 = z.length_squared();
+            let factor = if r2 < Self::MIN_RADIUS_SQUARED {
+                Self::FIXED_RADIUS_SQUARED / Self::MIN_RADIUS_SQUARED
+            } else if r2 < Self::FIXED_RADIUS_SQUARED {
+                Self::FIXED_RADIUS_SQUARED / r2
+            } else {
+                1.0
+            };
+            z = z * (factor * self.scale) + p;
+            dr = dr * factor * self.scale.abs() + 1.0;
+            trap = trap.min(z.length());
+            if z.length_squared() > 1e6 {
+                break;
+            }
+        }
+        (z.length() / dr.abs(), trap)
+    }
+}
+
+impl Sdf for Mandelbox {
+    fn distance(&self, p: Point3) -> f64 {
+        self.estimate(p).0
+    }
+
+    fn bounds(&self) -> Aabb {
+        let scale = self.scale.abs();
+        let extent = 2.0 * (scale + 1.0) / (scale - 1.0);
+        Aabb::from_points(&Point3::new(-extent, -extent, -extent), &Point3::new(extent, extent, extent))
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.estimate(p).1
+    }
+}
+
+/// The Menger sponge: a cube with a cross-shaped tunnel cut through every sub-cube, spanning
+/// -1 to 1. The distance is exact.
+pub struct MengerSponge {
+    pub iterations: u32,
+}
+
+impl MengerSponge {
+    fn estimate(&self, p: Point3) -> (f64, f64) {
+        // Start from the cube, then carve the crosses of each level.
+        let q = Vec3::new(p.x.abs(), p.y.abs(), p.z.abs()) - Vec3::new(1.0, 1.0, 1.0);
+        let mut d = q.max(Vec3::default()).length() + q.x.max(q.y).max(q.z).min(0.0);
+        let mut scale = 1.0;
+        let mut trap = f64::INFINITY;
+        for _ in 0..self.iterations {
+            // Position within the current sub-cube, in [-1, 1].
+            let a = Vec3::new(
+                (p.x * scale).rem_euclid(2.0) - 1.0,
+                (p.y * scale).rem_euclid(2.0) - 1.0,
+                (p.z * scale).rem_euclid(2.0) - 1.0,
+            );
+            scale *= 3.0;
+            let r = Vec3::new((1.0 - 3.0 * a.x.abs()).abs(), (1.0 - 3.0 * a.y.abs()).abs(), (1.0 - 3.0 * a.z.abs()).abs());
+            let cross = (r.x.max(r.y).min(r.y.max(r.z)).min(r.z.max(r.x)) - 1.0) / scale;
+            d = d.max(cross);
+            trap = trap.min(a.length());
+        }
+        (d, trap)
+    }
+}
+
+impl Sdf for MengerSponge {
+    fn distance(&self, p: Point3) -> f64 {
+        self.estimate(p).0
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::from_points(&Point3::new(-1.0, -1.0, -1.0), &Point3::new(1.0, 1.0, 1.0))
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.estimate(p).1
+    }
+}
+
+/// The Sierpinski tetrahedron, with corners at (1, 1, 1), (-1, -1, 1), (1, -1, -1) and
+/// (-1, 1, -1): folds towards the nearest corner, then scales up by 2 around it.
+pub struct SierpinskiTetrahedron {
+    pub iterations: u32,
+}
+
+impl SierpinskiTetrahedron {
+    fn estimate(&self, p: Point3) -> (f64, f64) {
+        let mut z = p;
+        let mut trap = z.length();
+        for _ in 0..self.iterations {
+            if z.x + z.y < 0.0 {
+                (z.x, z.y) = (-z.y, -z.x);
+            }
+            if z.x + z.z < 0.0 {
+                (z.x, z.z) = (-z.z, -z.x);
+            }
+            if z.y + z.z < 0.0 {
+                (z.y, z.z) = (-z.z, -z.y);
+            }
+            z = z * 2.0 - Vec3::new(1.0, 1.0, 1.0);
+            trap = trap.min(z.length());
+        }
+        // Distance to the tetrahedron at the deepest level, scaled back down.
+        let tetrahedron = ((z.x + z.y).abs() - z.z).max((z.x - z.y).abs() + z.z) - 1.0;
+        (tetrahedron / 3.0f64.sqrt() * 0.5f64.powi(self.iterations as i32), trap)
+    }
+}
+
+impl Sdf for SierpinskiTetrahedron {
+    fn distance(&self, p: Point3) -> f64 {
+        self.estimate(p).0
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::from_points(&Point3::new(-1.0, -1.0, -1.0), &Point3::new(1.0, 1.0, 1.0))
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.estimate(p).1
+    }
+}
+
+/// A 3D slice of a quaternion Julia set, iterating z² + c. The fourth component of the
+/// starting quaternion is `slice`.
+pub struct QuaternionJulia {
+    pub c: [f64; 4],
+    pub slice: f64,
+    pub iterations: u32,
+}
+
+impl QuaternionJulia {
+    fn estimate(&self, p: Point3) -> (f64, f64) {
+        let mut z = [p.x, p.y, p.z, self.slice];
+        let mut dz = [1.0, 0.0, 0.0, 0.0];
+        let mut trap = quaternion_length(z);
+        for _ in 0..self.iterations {
+            // z' = 2 z z', then z = z² + c
+            dz = quaternion_multiply(z, dz).map(|component| 2.0 * component);
+            z = quaternion_multiply(z, z);
+            for (component, c) in z.iter_mut().zip(self.c) {
+                *component += c;
+            }
+            trap = trap.min(quaternion_length(z));
+            if quaternion_length(z) > 4.0 {
+                break;
+            }
+        }
+        let r = quaternion_length(z);
+        let dr = quaternion_length(dz);
+        if r == 0.0 || dr == 0.0 {
+            return (0.0, trap);
+        }
+        (0.5 * r * r.ln() / dr, trap)
+    }
+}
+
+impl Sdf for QuaternionJulia {
+    fn distance(&self, p: Point3) -> f64 {
+        self.estimate(p).0
+    }
+
+    fn bounds(&self) -> Aabb {
+        // Beyond the radius where |z|² > |z| + |c|, every iteration moves further out.
+        let radius = (1.0 + (1.0 + 4.0 * quaternion_length(self.c)).sqrt()) / 2.0;
+        Aabb::from_points(&Point3::new(-radius, -radius, -radius), &Point3::new(radius, radius, radius))
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.estimate(p).1
+    }
+}
+
+fn box_fold(x: f64) -> f64 {
+    x.clamp(-1.0, 1.0) * 2.0 - x
+}
+
+fn quaternion_multiply(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    [
+        a[0] * b[0] - a[1] * b[1] - a[2] * b[2] - a[3] * b[3],
+        a[0] * b[1] + a[1] * b[0] + a[2] * b[3] - a[3] * b[2],
+        a[0] * b[2] - a[1] * b[3] + a[2] * b[0] + a[3] * b[1],
+        a[0] * b[3] + a[1] * b[2] - a[2] * b[1] + a[3] * b[0],
+    ]
+}
+
+fn quaternion_length(q: [f64; 4]) -> f64 {
+    q.iter().map(|component| component * component).sum::<f64>().sqrt()
+}
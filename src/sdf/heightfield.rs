@@ -0,0 +1,221 @@
+use std::io;
+use std::path::Path;
+
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+use crate::sdf::triangle::intersect_triangle;
+use crate::utils::pgm::GrayImage;
+
+/// Terrain from a regular grid of height samples, without turning it into triangles up front.
+///
+/// Each grid cell is split into two triangles along its diagonal. A min/max mip hierarchy
+/// (every level halving the cells along each axis, down to a single node) bounds the heights
+/// in blocks of cells, so rays skip whole blocks they pass over or under and only test the
+/// triangles of the few cells they might hit. Normals are interpolated from per-sample
+/// gradients for smooth shading, and UVs run from 0 to 1 across the grid.
+pub struct Heightfield {
+    pub corner: Point3, // Position of the first sample at height 0
+    pub size: Vec3, // Extent of the grid along x and z, and the height of a sample of 1
+    pub resolution: [usize; 2], // Samples along x and z
+    heights: Vec<f32>, // Row by row along x, in [0, 1]
+    levels: Vec<MipLevel>,
+    pub bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+/// Height range of blocks of cells at one level of the hierarchy.
+struct MipLevel {
+    width: usize,
+    depth: usize,
+    ranges: Vec<(f32, f32)>,
+}
+
+impl Heightfield {
+    pub fn new(corner: Point3, size: Vec3, resolution: [usize; 2], heights: Vec<f32>, material: &'static dyn Material) -> Self {
+        let [nx, nz] = resolution;
+        assert!(nx >= 2 && nz >= 2, "a heightfield needs at least 2 x 2 samples");
+        assert_eq!(heights.len(), nx * nz, "heights do not match the resolution");
+
+        // Level 0 holds one range per cell; each further level merges 2 x 2 blocks.
+        let mut level = MipLevel { width: nx - 1, depth: nz - 1, ranges: Vec::with_capacity((nx - 1) * (nz - 1)) };
+        for j in 0..nz - 1 {
+            for i in 0..nx - 1 {
+                let corners = [heights[j * nx + i], heights[j * nx + i + 1], heights[(j + 1) * nx + i], heights[(j + 1) * nx + i + 1]];
+                let min = corners.iter().copied().fold(f32::INFINITY, f32::min);
+                let max = corners.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                level.ranges.push((min, max));
+            }
+        }
+        let mut levels = vec![level];
+        while let Some(below) = levels.last().filter(|level| level.width > 1 || level.depth > 1) {
+            let (width, depth) = (below.width.div_ceil(2), below.depth.div_ceil(2));
+            let mut ranges = Vec::with_capacity(width * depth);
+            for j in 0..depth {
+                for i in 0..width {
+                    let mut range = (f32::INFINITY, f32::NEG_INFINITY);
+                    for (ci, cj) in [(2 * i, 2 * j), (2 * i + 1, 2 * j), (2 * i, 2 * j + 1), (2 * i + 1, 2 * j + 1)] {
+                        if ci < below.width && cj < below.depth {
+                            let (min, max) = below.ranges[cj * below.width + ci];
+                            range = (range.0.min(min), range.1.max(max));
+                        }
+                    }
+                    ranges.push(range);
+                }
+            }
+            levels.push(MipLevel { width, depth, ranges });
+        }
+
+        let (min, max) = levels.last().unwrap().ranges[0];
+        let bbox = Aabb::from_points(
+            &Point3::new(corner.x, corner.y + min as f64 * size.y, corner.z),
+            &Point3::new(corner.x + size.x, corner.y + max as f64 * size.y, corner.z + size.z),
+        );
+        Heightfield { corner, size, resolution, heights, levels, bbox, material }
+    }
+
+    /// Fills the grid by evaluating `f` at every sample, with x and z in [0, 1] across the
+    /// grid. Heights should be in [0, 1].
+    pub fn from_fn(
+        corner: Point3,
+        size: Vec3,
+        resolution: [usize; 2],
+        f: impl Fn(f64, f64) -> f64,
+        material: &'static dyn Material,
+    ) -> Self {
+        let [nx, nz] = resolution;
+        let heights = (0..nz)
+            .flat_map(|j| (0..nx).map(move |i| (i, j)))
+            .map(|(i, j)| f(i as f64 / (nx - 1) as f64, j as f64 / (nz - 1) as f64) as f32)
+            .collect();
+        Heightfield::new(corner, size, resolution, heights, material)
+    }
+
+    /// Loads a grayscale PGM heightmap, 8 or 16 bits per sample. The top row of the image
+    /// lies at the far end of the z range, as on a map viewed from above.
+    pub fn load<P: AsRef<Path>>(path: P, corner: Point3, size: Vec3, material: &'static dyn Material) -> io::Result<Self> {
+        let image = GrayImage::load(path)?;
+        if image.width < 2 || image.height < 2 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "heightmap is smaller than 2 x 2"));
+        }
+        let heights = (0..image.height).rev()
+            .flat_map(|row| image.values[row * image.width..(row + 1) * image.width].iter().map(|&v| v as f32))
+            .collect();
+        Ok(Heightfield::new(corner, size, [image.width, image.height], heights, material))
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        self.heights[j * self.resolution[0] + i] as f64
+    }
+
+    fn cell_size(&self) -> (f64, f64) {
+        (self.size.x / (self.resolution[0] - 1) as f64, self.size.z / (self.resolution[1] - 1) as f64)
+    }
+
+    fn sample_point(&self, i: usize, j: usize) -> Point3 {
+        let (dx, dz) = self.cell_size();
+        self.corner + Vec3::new(i as f64 * dx, self.height(i, j) * self.size.y, j as f64 * dz)
+    }
+
+    /// Surface normal at a sample from central differences of its neighbors.
+    fn sample_normal(&self, i: usize, j: usize) -> Vec3 {
+        let [nx, nz] = self.resolution;
+        let (dx, dz) = self.cell_size();
+        let (left, right) = (i.saturating_sub(1), (i + 1).min(nx - 1));
+        let (near, far) = (j.saturating_sub(1), (j + 1).min(nz - 1));
+        let slope_x = (self.height(right, j) - self.height(left, j)) * self.size.y / ((right - left) as f64 * dx);
+        let slope_z = (self.height(i, far) - self.height(i, near)) * self.size.y / ((far - near) as f64 * dz);
+        Vec3::unit_vector(Vec3::new(-slope_x, 1.0, -slope_z))
+    }
+
+    /// World box around a block of cells at some level of the hierarchy.
+    fn block_box(&self, level: usize, i: usize, j: usize) -> Aabb {
+        let (dx, dz) = self.cell_size();
+        let span = 1usize << level;
+        let (min, max) = self.levels[level].ranges[j * self.levels[level].width + i];
+        let x1 = ((i + 1) * span).min(self.resolution[0] - 1);
+        let z1 = ((j + 1) * span).min(self.resolution[1] - 1);
+        Aabb::from_points(
+            &(self.corner + Vec3::new((i * span) as f64 * dx, min as f64 * self.size.y, (j * span) as f64 * dz)),
+            &(self.corner + Vec3::new(x1 as f64 * dx, max as f64 * self.size.y, z1 as f64 * dz)),
+        )
+    }
+
+    /// Intersects the two triangles of cell (i, j), updating the record if one is hit closer
+    /// than `t_max`.
+    fn hit_cell(&self, r: Ray, i: usize, j: usize, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let mut hit = false;
+        let mut closest = t_max;
+        for triangle in [[0, 2, 1], [0, 3, 2]] {
+            let [a, b, c] = triangle.map(|corner| corners[corner]);
+            let (pa, pb, pc) = (self.sample_point(a.0, a.1), self.sample_point(b.0, b.1), self.sample_point(c.0, c.1));
+            let Some((t, u, v)) = intersect_triangle(r, pa, pb, pc) else {
+                continue;
+            };
+            if t <= t_min || t >= closest {
+                continue;
+            }
+            hit = true;
+            closest = t;
+            rec.t = t;
+            rec.point = r.at(t);
+            let normal = self.sample_normal(a.0, a.1) * (1.0 - u - v) + self.sample_normal(b.0, b.1) * u + self.sample_normal(c.0, c.1) * v;
+            rec.u = (rec.point.x - self.corner.x) / self.size.x;
+            rec.v = (rec.point.z - self.corner.z) / self.size.z;
+            rec.material = Some(self.material);
+            rec.set_face_normal(r, Vec3::unit_vector(normal));
+        }
+        hit
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        // Depth-first through the hierarchy, nearest blocks first, skipping any block that
+        // starts beyond the closest hit so far.
+        let top = self.levels.len() - 1;
+        let mut closest = interval.max;
+        let mut hit = false;
+        let mut stack = vec![(top, 0, 0, interval.min)];
+        while let Some((level, i, j, t_enter)) = stack.pop() {
+            if t_enter >= closest {
+                continue;
+            }
+            if level == 0 {
+                if self.hit_cell(r, i, j, interval.min, closest, rec) {
+                    hit = true;
+                    closest = rec.t;
+                }
+                continue;
+            }
+            let below = &self.levels[level - 1];
+            let mut children: Vec<(usize, usize, usize, f64)> = Vec::with_capacity(4);
+            for (ci, cj) in [(2 * i, 2 * j), (2 * i + 1, 2 * j), (2 * i, 2 * j + 1), (2 * i + 1, 2 * j + 1)] {
+                if ci >= below.width || cj >= below.depth {
+                    continue;
+                }
+                if let Some(clipped) = self.block_box(level - 1, ci, cj).clip(r, Interval::new(interval.min, closest)) {
+                    children.push((level - 1, ci, cj, clipped.min));
+                }
+            }
+            // Farthest first onto the stack, so the nearest is popped next.
+            children.sort_by(|a, b| b.3.total_cmp(&a.3));
+            stack.extend(children);
+        }
+        hit
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.levels.iter().map(|level| (level.ranges.len() * std::mem::size_of::<(f32, f32)>()) as u64).sum()
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::color::Color;
+use crate::math::flat_bvh::FlatBvh;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+use crate::sdf::triangle::intersect_triangle;
+use crate::utils::common::{gamma_to_linear, DEG_TO_RAD};
+use crate::utils::ply::PlyFile;
+use crate::utils::stl::StlFile;
+
+/// Triangles sharing a list of vertices, such as scanned or CAD models, under a compact
+/// hierarchy of their own.
+///
+/// Vertices may carry normals for smooth shading, colors (shown by the `VertexColor`
+/// material) and texture coordinates, which are interpolated across the triangles. Meshes
+/// without normals are flat shaded, or smoothed by `with_crease_angle`.
+pub struct TriangleMesh {
+    positions: Vec<Point3>,
+    triangles: Vec<[u32; 3]>, // Corners counterclockwise seen from the front
+    normals: Vec<[Vec3; 3]>, // Shading normals at the corners of each triangle; empty when flat
+    colors: Vec<Color>, // Per vertex, linear RGB
+    uvs: Vec<[f64; 2]>, // Per vertex
+    bvh: FlatBvh,
+    bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+impl TriangleMesh {
+    pub fn new(positions: Vec<Point3>, triangles: Vec<[u32; 3]>, material: &'static dyn Material) -> Self {
+        assert!(triangles.iter().flatten().all(|&vertex| (vertex as usize) < positions.len()), "triangle refers to a missing vertex");
+        let mut mesh = TriangleMesh {
+            positions,
+            triangles,
+            normals: Vec::new(),
+            colors: Vec::new(),
+            uvs: Vec::new(),
+            bvh: FlatBvh::new(&[]),
+            bbox: Aabb::empty(),
+            material,
+        };
+        mesh.build();
+        mesh
+    }
+
+    /// Loads a `.ply` or `.stl` file. Meshes without normals in the file get them generated,
+    /// smoothing across edges where the faces meet at less than `crease_angle` degrees.
+    pub fn load(path: &str, crease_angle: f64, material: &'static dyn Material) -> io::Result<Self> {
+        match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some("ply") => Self::load_ply(path, crease_angle, material),
+            Some("stl") => Self::load_stl(path, crease_angle, material),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown mesh format: {}", path))),
+        }
+    }
+
+    /// PLY meshes: a `vertex` element with `x`, `y`, `z` and optional `nx`/`ny`/`nz`,
+    /// `red`/`green`/`blue` and `u`/`v` (or `s`/`t`) properties, and a `face` element with
+    /// `vertex_indices` lists. Polygons are split into triangle fans.
+    fn load_ply(path: &str, crease_angle: f64, material: &'static dyn Material) -> io::Result<Self> {
+        let ply = PlyFile::load(path)?;
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message));
+        let vertices = ply.element("vertex").ok_or_else(|| invalid("no vertex element"))?;
+        let faces = ply.element("face").ok_or_else(|| invalid("no face element"))?;
+        let positions: Vec<Point3> = vertices.triples(["x", "y", "z"]).ok_or_else(|| invalid("vertices without positions"))?
+            .into_iter().map(|[x, y, z]| Point3::new(x, y, z)).collect();
+        let indices = faces.list("vertex_indices").or_else(|| faces.list("vertex_index")).ok_or_else(|| invalid("faces without vertex indices"))?;
+        let mut triangles = Vec::with_capacity(indices.len());
+        for face in indices {
+            if face.iter().any(|&vertex| vertex < 0.0 || vertex as usize >= positions.len()) {
+                return Err(invalid("face refers to a missing vertex"));
+            }
+            for i in 1..face.len().saturating_sub(1) {
+                triangles.push([face[0] as u32, face[i] as u32, face[i + 1] as u32]);
+            }
+        }
+
+        let mut mesh = TriangleMesh::new(positions, triangles, material);
+        mesh = match vertices.triples(["nx", "ny", "nz"]) {
+            Some(normals) => mesh.with_vertex_normals(normals.into_iter().map(|[x, y, z]| Vec3::new(x, y, z)).collect()),
+            None => mesh.with_crease_angle(crease_angle),
+        };
+        if let Some(colors) = vertices.colors() {
+            mesh = mesh.with_colors(colors.into_iter().map(|color| {
+                let [red, green, blue] = color.map(gamma_to_linear);
+                Color::new(red, green, blue)
+            }).collect());
+        }
+        let uv_names = [["u", "v"], ["s", "t"], ["texture_u", "texture_v"], ["texture_s", "texture_t"]];
+        if let Some((u, v)) = uv_names.iter().find_map(|[u, v]| Some((vertices.scalar(u)?, vertices.scalar(v)?))) {
+            mesh = mesh.with_uvs(u.iter().zip(v).map(|(&u, &v)| [u, v]).collect());
+        }
+        Ok(mesh)
+    }
+
+    /// STL meshes store every triangle on its own; corners at the same position are merged
+    /// into shared vertices so normals can be smoothed across edges.
+    fn load_stl(path: &str, crease_angle: f64, material: &'static dyn Material) -> io::Result<Self> {
+        let stl = StlFile::load(path)?;
+        let mut positions = Vec::new();
+        let mut index: HashMap<[u64; 3], u32> = HashMap::new();
+        let triangles = stl.triangles.iter().map(|corners| {
+            corners.map(|p| {
+                *index.entry([p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]).or_insert_with(|| {
+                    positions.push(p);
+                    (positions.len() - 1) as u32
+                })
+            })
+        }).collect();
+        Ok(TriangleMesh::new(positions, triangles, material).with_crease_angle(crease_angle))
+    }
+
+    /// Smooth shading from normals given per vertex.
+    pub fn with_vertex_normals(mut self, normals: Vec<Vec3>) -> Self {
+        assert_eq!(normals.len(), self.positions.len(), "normals do not match the vertices");
+        self.normals = self.triangles.iter().map(|triangle| triangle.map(|vertex| Vec3::unit_vector(normals[vertex as usize]))).collect();
+        self
+    }
+
+    /// Generates shading normals: each corner averages the normals of the triangles around
+    /// its vertex (weighted by area) that face within `degrees` of its own triangle, so
+    /// edges sharper than the crease angle stay sharp. 0 gives flat shading.
+    pub fn with_crease_angle(mut self, degrees: f64) -> Self {
+        if degrees <= 0.0 {
+            self.normals = Vec::new();
+            return self;
+        }
+        let area_normals: Vec<Vec3> = self.triangles.iter().map(|&[a, b, c]| {
+            let (a, b, c) = (self.positions[a as usize], self.positions[b as usize], self.positions[c as usize]);
+            Vec3::cross_two(b - a, c - a)
+        }).collect();
+        let unit_normals: Vec<Vec3> = area_normals.iter()
+            .map(|&normal| if normal.near_zero() { normal } else { Vec3::unit_vector(normal) })
+            .collect();
+        let mut around: Vec<Vec<u32>> = vec![Vec::new(); self.positions.len()];
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            for &vertex in triangle {
+                around[vertex as usize].push(index as u32);
+            }
+        }
+
+        let min_cos = (degrees * DEG_TO_RAD).cos() - 1e-9;
+        self.normals = self.triangles.iter().enumerate().map(|(index, triangle)| {
+            let own = unit_normals[index];
+            triangle.map(|vertex| {
+                let sum = around[vertex as usize].iter()
+                    .filter(|&&other| Vec3::dot_two(unit_normals[other as usize], own) >= min_cos)
+                    .fold(Vec3::default(), |sum, &other| sum + area_normals[other as usize]);
+                if sum.near_zero() { own } else { Vec3::unit_vector(sum) }
+            })
+        }).collect();
+        self
+    }
+
+    pub fn with_colors(mut self, colors: Vec<Color>) -> Self {
+        assert_eq!(colors.len(), self.positions.len(), "colors do not match the vertices");
+        self.colors = colors;
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: Vec<[f64; 2]>) -> Self {
+        assert_eq!(uvs.len(), self.positions.len(), "texture coordinates do not match the vertices");
+        self.uvs = uvs;
+        self
+    }
+
+    /// Scales and moves the mesh so that it fits a cube of side `size` centered at `center`,
+    /// for models in whatever units they were made in.
+    pub fn fit(mut self, center: Point3, size: f64) -> Self {
+        let bbox = self.bbox.clone();
+        let extent = bbox.x.size().max(bbox.y.size()).max(bbox.z.size());
+        let middle = Point3::new(bbox.x.min + bbox.x.max, bbox.y.min + bbox.y.max, bbox.z.min + bbox.z.max) / 2.0;
+        let scale = if extent > 0.0 { size / extent } else { 1.0 };
+        for position in &mut self.positions {
+            *position = center + (*position - middle) * scale;
+        }
+        self.build();
+        self
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    fn build(&mut self) {
+        let boxes: Vec<Aabb> = self.triangles.iter().map(|triangle| {
+            let [a, b, c] = triangle.map(|vertex| self.positions[vertex as usize]);
+            Aabb::from_points(&a.min(b).min(c), &a.max(b).max(c))
+        }).collect();
+        self.bvh = FlatBvh::new(&boxes);
+        self.bbox = self.bvh.bounds();
+    }
+
+    fn hit_triangle(&self, index: usize, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let [a, b, c] = self.triangles[index].map(|vertex| vertex as usize);
+        let (pa, pb, pc) = (self.positions[a], self.positions[b], self.positions[c]);
+        let Some((t, u, v)) = intersect_triangle(r, pa, pb, pc) else {
+            return false;
+        };
+        if !interval.contains(t) {
+            return false;
+        }
+
+        let w = 1.0 - u - v;
+        rec.t = t;
+        rec.point = r.at(t);
+        (rec.u, rec.v) = match self.uvs.as_slice() {
+            [] => (u, v),
+            uvs => (uvs[a][0] * w + uvs[b][0] * u + uvs[c][0] * v, uvs[a][1] * w + uvs[b][1] * u + uvs[c][1] * v),
+        };
+        rec.material = Some(self.material);
+        // The geometric normal decides which side was hit; the shading normal is turned to
+        // the same side.
+        rec.set_face_normal(r, Vec3::unit_vector(Vec3::cross_two(pb - pa, pc - pa)));
+        if let Some(corners) = self.normals.get(index) {
+            let shading = Vec3::unit_vector(corners[0] * w + corners[1] * u + corners[2] * v);
+            rec.normal = if Vec3::dot_two(shading, rec.normal) < 0.0 { -shading } else { shading };
+        }
+        if !self.colors.is_empty() {
+            rec.color = self.colors[a] * w + self.colors[b] * u + self.colors[c] * v;
+        }
+        true
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        self.bvh.hit(r, interval, |index, interval| self.hit_triangle(index, r, interval, rec).then_some(rec.t))
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.bvh.bytes()
+    }
+}
@@ -1,2 +1,18 @@
+pub mod csg;
+pub mod distance;
+pub mod fractals;
+pub mod operators;
+pub mod shapes;
 pub mod sphere;
 pub mod quad;
+pub mod disk;
+pub mod cone;
+pub mod cylinder;
+pub mod torus;
+pub mod triangle;
+pub mod polygon;
+pub mod plane;
+pub mod heightfield;
+pub mod curve;
+pub mod point_cloud;
+pub mod mesh;
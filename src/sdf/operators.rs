@@ -0,0 +1,414 @@
+//! Combinators that build new distance fields out of others: boolean operations, rounding,
+//! shelling, noise displacement and deformations of space. Each adjusts the bounds and the
+//! Lipschitz bound of its inputs so `SphereTraced` stays correct.
+
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::vec3::{Point3, Vec3};
+use crate::sdf::distance::Sdf;
+use crate::volumes::field::value_noise;
+
+/// Steepest slope of `value_noise` along any direction: the smoothstep fade has slope 1.5 per
+/// axis.
+const VALUE_NOISE_LIPSCHITZ: f64 = 1.5 * 1.732_050_807_568_877_2;
+
+/// Both shapes. With a `smoothness` above 0 they blend into each other within that distance.
+pub struct Union<A: Sdf, B: Sdf> {
+    pub a: A,
+    pub b: B,
+    pub smoothness: f64,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Union<A, B> {
+    fn distance(&self, p: Point3) -> f64 {
+        smooth_min(self.a.distance(p), self.b.distance(p), self.smoothness)
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        if self.a.distance(p) <= self.b.distance(p) { self.a.orbit_trap(p) } else { self.b.orbit_trap(p) }
+    }
+
+    fn bounds(&self) -> Aabb {
+        // Blending bulges out by up to a quarter of the smoothness.
+        expand(&Aabb::from_aabb(&self.a.bounds(), &self.b.bounds()), self.smoothness / 4.0)
+    }
+
+    fn lipschitz(&self) -> f64 {
+        self.a.lipschitz().max(self.b.lipschitz())
+    }
+}
+
+/// Where both shapes overlap, optionally blended.
+pub struct Intersection<A: Sdf, B: Sdf> {
+    pub a: A,
+    pub b: B,
+    pub smoothness: f64,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Intersection<A, B> {
+    fn distance(&self, p: Point3) -> f64 {
+        -smooth_min(-self.a.distance(p), -self.b.distance(p), self.smoothness)
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        if self.a.distance(p) >= self.b.distance(p) { self.a.orbit_trap(p) } else { self.b.orbit_trap(p) }
+    }
+
+    fn bounds(&self) -> Aabb {
+        let (a, b) = (self.a.bounds(), self.b.bounds());
+        let overlap = |a: &Interval, b: &Interval| Interval::new(a.min.max(b.min), a.max.min(b.max));
+        Aabb::from_intervals(overlap(&a.x, &b.x), overlap(&a.y, &b.y), overlap(&a.z, &b.z))
+    }
+
+    fn lipschitz(&self) -> f64 {
+        self.a.lipschitz().max(self.b.lipschitz())
+    }
+}
+
+/// Shape `a` with shape `b` carved out of it, optionally blended.
+pub struct Subtraction<A: Sdf, B: Sdf> {
+    pub a: A,
+    pub b: B,
+    pub smoothness: f64,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Subtraction<A, B> {
+    fn distance(&self, p: Point3) -> f64 {
+        -smooth_min(-self.a.distance(p), self.b.distance(p), self.smoothness)
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        if self.a.distance(p) >= -self.b.distance(p) { self.a.orbit_trap(p) } else { self.b.orbit_trap(p) }
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.a.bounds()
+    }
+
+    fn lipschitz(&self) -> f64 {
+        self.a.lipschitz().max(self.b.lipschitz())
+    }
+}
+
+/// Moves the shape by `offset`, for arranging shapes relative to each other inside a
+/// combinator.
+pub struct Offset<S: Sdf> {
+    pub sdf: S,
+    pub offset: Vec3,
+}
+
+impl<S: Sdf> Sdf for Offset<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(p - self.offset)
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.sdf.orbit_trap(p - self.offset)
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.sdf.bounds() + self.offset
+    }
+
+    fn lipschitz(&self) -> f64 {
+        self.sdf.lipschitz()
+    }
+}
+
+/// Scales the shape uniformly by `factor` around the origin. A negative factor also mirrors
+/// the shape through the origin; the factor must not be zero.
+pub struct Scale<S: Sdf> {
+    pub sdf: S,
+    pub factor: f64,
+}
+
+impl<S: Sdf> Sdf for Scale<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(p / self.factor) * self.factor.abs()
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.sdf.orbit_trap(p / self.factor)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let bounds = self.sdf.bounds();
+        let scale = |interval: &Interval| {
+            let (a, b) = (interval.min * self.factor, interval.max * self.factor);
+            Interval::new(a.min(b), a.max(b))
+        };
+        Aabb::from_intervals(scale(&bounds.x), scale(&bounds.y), scale(&bounds.z))
+    }
+
+    fn lipschitz(&self) -> f64 {
+        self.sdf.lipschitz()
+    }
+}
+
+/// Grows the shape by `radius`, rounding off its edges and corners.
+pub struct Round<S: Sdf> {
+    pub sdf: S,
+    pub radius: f64,
+}
+
+impl<S: Sdf> Sdf for Round<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(p) - self.radius
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.sdf.orbit_trap(p)
+    }
+
+    fn bounds(&self) -> Aabb {
+        expand(&self.sdf.bounds(), self.radius * self.sdf.lipschitz())
+    }
+
+    fn lipschitz(&self) -> f64 {
+        self.sdf.lipschitz()
+    }
+}
+
+/// Hollows the shape into a shell of the given `thickness` around its surface. Nest onions
+/// for concentric shells.
+pub struct Onion<S: Sdf> {
+    pub sdf: S,
+    pub thickness: f64,
+}
+
+impl<S: Sdf> Sdf for Onion<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(p).abs() - self.thickness / 2.0
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.sdf.orbit_trap(p)
+    }
+
+    fn bounds(&self) -> Aabb {
+        expand(&self.sdf.bounds(), self.thickness / 2.0 * self.sdf.lipschitz())
+    }
+
+    fn lipschitz(&self) -> f64 {
+        self.sdf.lipschitz()
+    }
+}
+
+/// Roughens the surface by pushing it in and out by up to `amplitude` with fractal value
+/// noise.
+pub struct Displace<S: Sdf> {
+    pub sdf: S,
+    pub amplitude: f64,
+    pub frequency: f64, // Noise features per unit length at the first octave
+    pub octaves: u32,
+    pub seed: u64,
+}
+
+impl<S: Sdf> Displace<S> {
+    /// Noise in [-1, 1], with each octave at twice the frequency and half the amplitude.
+    fn noise(&self, p: Point3) -> f64 {
+        let mut sum = 0.0;
+        let mut amplitude = 0.5;
+        let mut frequency = self.frequency;
+        for octave in 0..self.octaves {
+            let seed = self.seed.wrapping_add(octave as u64);
+            sum += amplitude * (2.0 * value_noise(p.x * frequency, p.y * frequency, p.z * frequency, seed) - 1.0);
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum
+    }
+}
+
+impl<S: Sdf> Sdf for Displace<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(p) + self.amplitude * self.noise(p)
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.sdf.orbit_trap(p)
+    }
+
+    fn bounds(&self) -> Aabb {
+        expand(&self.sdf.bounds(), self.amplitude * self.sdf.lipschitz())
+    }
+
+    fn lipschitz(&self) -> f64 {
+        // Every octave adds the same slope: half the amplitude at twice the frequency.
+        self.sdf.lipschitz() + self.amplitude * self.frequency * self.octaves as f64 * VALUE_NOISE_LIPSCHITZ
+    }
+}
+
+/// Twists the shape around the y axis by `rate` radians per unit of height.
+pub struct Twist<S: Sdf> {
+    pub sdf: S,
+    pub rate: f64,
+}
+
+impl<S: Sdf> Twist<S> {
+    fn untwist(&self, p: Point3) -> Point3 {
+        let (sin, cos) = (self.rate * p.y).sin_cos();
+        Point3::new(cos * p.x - sin * p.z, p.y, sin * p.x + cos * p.z)
+    }
+}
+
+impl<S: Sdf> Sdf for Twist<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(self.untwist(p))
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.sdf.orbit_trap(self.untwist(p))
+    }
+
+    fn bounds(&self) -> Aabb {
+        // Rotating around the y axis keeps points within the same distance of it.
+        let bounds = self.sdf.bounds();
+        let radius = corner_radius(&bounds.x, &bounds.z);
+        Aabb::from_intervals(Interval::new(-radius, radius), bounds.y, Interval::new(-radius, radius))
+    }
+
+    fn lipschitz(&self) -> f64 {
+        let bounds = self.sdf.bounds();
+        self.sdf.lipschitz() * (1.0 + self.rate.abs() * corner_radius(&bounds.x, &bounds.z))
+    }
+}
+
+/// Bends the shape in the xy plane, turning by `rate` radians per unit along x.
+pub struct Bend<S: Sdf> {
+    pub sdf: S,
+    pub rate: f64,
+}
+
+impl<S: Sdf> Bend<S> {
+    fn unbend(&self, p: Point3) -> Point3 {
+        let (sin, cos) = (self.rate * p.x).sin_cos();
+        Point3::new(cos * p.x - sin * p.y, sin * p.x + cos * p.y, p.z)
+    }
+}
+
+impl<S: Sdf> Sdf for Bend<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(self.unbend(p))
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.sdf.orbit_trap(self.unbend(p))
+    }
+
+    fn bounds(&self) -> Aabb {
+        // The bend rotates points around the z axis, keeping their distance from it.
+        let bounds = self.sdf.bounds();
+        let radius = corner_radius(&bounds.x, &bounds.y);
+        Aabb::from_intervals(Interval::new(-radius, radius), Interval::new(-radius, radius), bounds.z)
+    }
+
+    fn lipschitz(&self) -> f64 {
+        let bounds = self.sdf.bounds();
+        self.sdf.lipschitz() * (1.0 + self.rate.abs() * corner_radius(&bounds.x, &bounds.y))
+    }
+}
+
+/// Repeats the shape forever on a grid with the given `period` per axis; a period of 0 leaves
+/// that axis alone. The shape should fit within one cell around the origin, or the distance
+/// overestimates near cell borders.
+pub struct Repeat<S: Sdf> {
+    pub sdf: S,
+    pub period: Vec3,
+}
+
+impl<S: Sdf> Repeat<S> {
+    fn local(&self, p: Point3) -> Point3 {
+        p - self.period * cell(p, self.period, |i| i)
+    }
+}
+
+impl<S: Sdf> Sdf for Repeat<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(self.local(p))
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.sdf.orbit_trap(self.local(p))
+    }
+
+    fn bounds(&self) -> Aabb {
+        let bounds = self.sdf.bounds();
+        let axis = |interval: Interval, period: f64| if period > 0.0 { Interval::UNIVERSE } else { interval };
+        Aabb::from_intervals(axis(bounds.x, self.period.x), axis(bounds.y, self.period.y), axis(bounds.z, self.period.z))
+    }
+
+    fn lipschitz(&self) -> f64 {
+        self.sdf.lipschitz()
+    }
+}
+
+/// Repeats the shape on a grid with the given `period`, `count` copies either side of the
+/// original along each axis.
+pub struct RepeatLimited<S: Sdf> {
+    pub sdf: S,
+    pub period: Vec3,
+    pub count: [u32; 3],
+}
+
+impl<S: Sdf> RepeatLimited<S> {
+    fn local(&self, p: Point3) -> Point3 {
+        let count = Vec3::new(self.count[0] as f64, self.count[1] as f64, self.count[2] as f64);
+        p - self.period * cell(p, self.period, |i| i.max(-count).min(count))
+    }
+}
+
+impl<S: Sdf> Sdf for RepeatLimited<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        self.sdf.distance(self.local(p))
+    }
+
+    fn orbit_trap(&self, p: Point3) -> f64 {
+        self.sdf.orbit_trap(self.local(p))
+    }
+
+    fn bounds(&self) -> Aabb {
+        let bounds = self.sdf.bounds();
+        let axis = |interval: Interval, period: f64, count: u32| {
+            let reach = period * count as f64;
+            Interval::new(interval.min - reach, interval.max + reach)
+        };
+        Aabb::from_intervals(
+            axis(bounds.x, self.period.x, self.count[0]),
+            axis(bounds.y, self.period.y, self.count[1]),
+            axis(bounds.z, self.period.z, self.count[2]),
+        )
+    }
+
+    fn lipschitz(&self) -> f64 {
+        self.sdf.lipschitz()
+    }
+}
+
+/// Polynomial smooth minimum: the plain minimum where `a` and `b` are further than `k` apart,
+/// blending smoothly in between.
+fn smooth_min(a: f64, b: f64, k: f64) -> f64 {
+    if k <= 0.0 {
+        return a.min(b);
+    }
+    let h = (k - (a - b).abs()).max(0.0) / k;
+    a.min(b) - h * h * k / 4.0
+}
+
+fn expand(bounds: &Aabb, delta: f64) -> Aabb {
+    Aabb::from_intervals(bounds.x.expand(2.0 * delta), bounds.y.expand(2.0 * delta), bounds.z.expand(2.0 * delta))
+}
+
+/// Largest distance from the origin to a corner of the rectangle spanned by two intervals.
+fn corner_radius(a: &Interval, b: &Interval) -> f64 {
+    let a = a.min.abs().max(a.max.abs());
+    let b = b.min.abs().max(b.max.abs());
+    (a * a + b * b).sqrt()
+}
+
+/// Index of the repetition cell holding `p`, passed through `limit`; 0 on axes that do not
+/// repeat.
+fn cell(p: Point3, period: Vec3, limit: impl Fn(Vec3) -> Vec3) -> Vec3 {
+    let round = |x: f64, period: f64| if period > 0.0 { (x / period).round() } else { 0.0 };
+    limit(Vec3::new(round(p.x, period.x), round(p.y, period.y), round(p.z, period.z)))
+}
@@ -0,0 +1,53 @@
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::onb::Onb;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+
+/// Infinite plane through `point`, facing along `normal`. UVs are distances in world units
+/// from `point` along two directions in the plane.
+///
+/// Its bounding box is unbounded, so `BvhNode::new_from_list` keeps it out of the tree and
+/// tests it separately.
+pub struct Plane {
+    pub point: Point3,
+    pub onb: Onb, // w is the normal
+    pub bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: &'static dyn Material) -> Self {
+        Plane { point, onb: Onb::new(normal), bbox: Aabb::universe(), material }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let denom = Vec3::dot_two(self.onb.w, r.direction);
+        if denom.abs() < 1e-8 {
+            return false;
+        }
+        let t = Vec3::dot_two(self.onb.w, self.point - r.origin) / denom;
+        if !interval.contains(t) {
+            return false;
+        }
+
+        rec.t = t;
+        rec.point = r.at(t);
+        let local = self.onb.local(rec.point - self.point);
+        rec.u = local.x;
+        rec.v = local.y;
+        rec.material = Some(self.material);
+        rec.set_face_normal(r, self.onb.w);
+        true
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
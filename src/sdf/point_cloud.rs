@@ -0,0 +1,197 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::color::Color;
+use crate::math::flat_bvh::FlatBvh;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+use crate::utils::common::gamma_to_linear;
+use crate::utils::ply::PlyFile;
+
+/// Particle data in compact arrays, one entry per particle. Radii, colors and normals are
+/// optional: leave them empty to use the radius of the point cloud, white, and discs facing
+/// the ray.
+#[derive(Clone, Debug, Default)]
+pub struct Particles {
+    pub positions: Vec<[f32; 3]>,
+    pub radii: Vec<f32>,
+    pub colors: Vec<[f32; 3]>, // Linear RGB
+    pub normals: Vec<[f32; 3]>, // Orientation of discs
+}
+
+impl Particles {
+    /// Loads particles from a `.ply` file (a `vertex` element with `x`, `y`, `z` and optional
+    /// `radius`, `red`/`green`/`blue` and `nx`/`ny`/`nz` properties) or an `.xyz` file.
+    pub fn load(path: &str) -> io::Result<Self> {
+        match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some("ply") => Self::load_ply(path),
+            Some("xyz") => Self::load_xyz(path),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown point cloud format: {}", path))),
+        }
+    }
+
+    fn load_ply(path: &str) -> io::Result<Self> {
+        let ply = PlyFile::load(path)?;
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message));
+        let vertices = ply.element("vertex").ok_or_else(|| invalid("no vertex element"))?;
+        let to_f32 = |triples: Vec<[f64; 3]>| triples.into_iter().map(|triple| triple.map(|value| value as f32)).collect();
+        Ok(Particles {
+            positions: to_f32(vertices.triples(["x", "y", "z"]).ok_or_else(|| invalid("vertices without positions"))?),
+            radii: vertices.scalar("radius").map_or(Vec::new(), |radii| radii.iter().map(|&radius| radius as f32).collect()),
+            colors: vertices.colors().map_or(Vec::new(), |colors| to_f32(colors.into_iter().map(|color| color.map(gamma_to_linear)).collect())),
+            normals: vertices.triples(["nx", "ny", "nz"]).map_or(Vec::new(), to_f32),
+        })
+    }
+
+    /// Loads lines of `x y z`, optionally followed by `r g b` from 0 to 255 (or from 0 to 1
+    /// when no channel in the file exceeds 1). Blank lines and `#` comments are skipped.
+    fn load_xyz(path: &str) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let mut particles = Particles::default();
+        let mut colors = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values = line.split_whitespace().map(|word| word.parse::<f64>()).collect::<Result<Vec<_>, _>>()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: bad number", path, number + 1)))?;
+            if values.len() < 3 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: expected x y z", path, number + 1)));
+            }
+            particles.positions.push([values[0] as f32, values[1] as f32, values[2] as f32]);
+            if values.len() >= 6 {
+                colors.push([values[3], values[4], values[5]]);
+            }
+        }
+        if colors.len() == particles.positions.len() {
+            let unit = if colors.iter().flatten().any(|&channel| channel > 1.0) { 255.0 } else { 1.0 };
+            particles.colors = colors.into_iter().map(|color| color.map(|channel| gamma_to_linear(channel / unit) as f32)).collect();
+        }
+        Ok(particles)
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    fn position(&self, index: usize) -> Point3 {
+        let [x, y, z] = self.positions[index];
+        Point3::new(x as f64, y as f64, z as f64)
+    }
+}
+
+/// Shape drawn for every particle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParticleShape {
+    Sphere,
+    Disc, // Oriented by the particle normals, or facing the ray without them (splats)
+}
+
+/// Millions of small spheres or discs, such as LIDAR scans or particle simulations, kept in
+/// compact arrays under a hierarchy of their own instead of as separate objects. Particle
+/// colors reach the material through `HitRecord::color` (see `VertexColor`).
+pub struct PointCloud {
+    particles: Particles,
+    pub radius: f64, // For particles without their own radius
+    pub shape: ParticleShape,
+    bvh: FlatBvh,
+    bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+impl PointCloud {
+    pub fn new(particles: Particles, radius: f64, shape: ParticleShape, material: &'static dyn Material) -> Self {
+        let count = particles.len();
+        assert!(particles.radii.is_empty() || particles.radii.len() == count, "particle radii do not match the positions");
+        assert!(particles.colors.is_empty() || particles.colors.len() == count, "particle colors do not match the positions");
+        assert!(particles.normals.is_empty() || particles.normals.len() == count, "particle normals do not match the positions");
+
+        let mut cloud = PointCloud { particles, radius, shape, bvh: FlatBvh::new(&[]), bbox: Aabb::empty(), material };
+        let boxes: Vec<Aabb> = (0..count).map(|index| {
+            let center = cloud.particles.position(index);
+            let radius = cloud.radius_of(index);
+            let extent = Vec3::new(radius, radius, radius);
+            Aabb::from_points(&(center - extent), &(center + extent))
+        }).collect();
+        cloud.bvh = FlatBvh::new(&boxes);
+        cloud.bbox = cloud.bvh.bounds();
+        cloud
+    }
+
+    fn radius_of(&self, index: usize) -> f64 {
+        self.particles.radii.get(index).map_or(self.radius, |&radius| radius as f64)
+    }
+
+    fn hit_particle(&self, index: usize, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let center = self.particles.position(index);
+        let radius = self.radius_of(index);
+        let (t, outward_normal) = match self.shape {
+            ParticleShape::Sphere => {
+                let oc = center - r.origin;
+                let a = r.direction.length_squared();
+                let h = Vec3::dot_two(r.direction, oc);
+                let discriminant = h * h - a * (oc.length_squared() - radius * radius);
+                if discriminant < 0.0 {
+                    return false;
+                }
+                let root = discriminant.sqrt();
+                let Some(t) = [(h - root) / a, (h + root) / a].into_iter().find(|&t| interval.surrounds(t)) else {
+                    return false;
+                };
+                (t, (r.at(t) - center) / radius)
+            }
+            ParticleShape::Disc => {
+                let normal = match self.particles.normals.get(index) {
+                    Some(&[x, y, z]) => Vec3::unit_vector(Vec3::new(x as f64, y as f64, z as f64)),
+                    None => -Vec3::unit_vector(r.direction),
+                };
+                let denominator = Vec3::dot_two(r.direction, normal);
+                if denominator.abs() < 1e-12 {
+                    return false;
+                }
+                let t = Vec3::dot_two(center - r.origin, normal) / denominator;
+                if !interval.surrounds(t) || (r.at(t) - center).length_squared() > radius * radius {
+                    return false;
+                }
+                (t, normal)
+            }
+        };
+
+        rec.t = t;
+        rec.point = r.at(t);
+        rec.u = 0.0;
+        rec.v = 0.0;
+        rec.material = Some(self.material);
+        rec.set_face_normal(r, outward_normal);
+        if let Some(&[red, green, blue]) = self.particles.colors.get(index) {
+            rec.color = Color::new(red as f64, green as f64, blue as f64);
+        }
+        true
+    }
+}
+
+impl Hittable for PointCloud {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        self.bvh.hit(r, interval, |index, interval| self.hit_particle(index, r, interval, rec).then_some(rec.t))
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.bvh.bytes()
+    }
+}
@@ -0,0 +1,92 @@
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+
+/// Flat convex polygon, facing the side from which its vertices run counterclockwise. UVs
+/// span the polygon's extent in its plane, with u along the first edge.
+pub struct Polygon {
+    pub vertices: Vec<Point3>,
+    pub normal: Vec3,
+    pub d: f64,
+    pub bbox: Aabb,
+    pub material: &'static dyn Material,
+    u_axis: Vec3,
+    v_axis: Vec3,
+    uv_min: (f64, f64),
+    uv_size: (f64, f64),
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point3>, material: &'static dyn Material) -> Self {
+        assert!(vertices.len() >= 3, "a polygon needs at least three vertices");
+        // Newell's method averages over all edges, so slightly non-planar input still gets a
+        // sensible normal.
+        let mut n = Vec3::default();
+        for (i, &current) in vertices.iter().enumerate() {
+            let next = vertices[(i + 1) % vertices.len()];
+            n += Vec3::new(
+                (current.y - next.y) * (current.z + next.z),
+                (current.z - next.z) * (current.x + next.x),
+                (current.x - next.x) * (current.y + next.y),
+            );
+        }
+        let normal = Vec3::unit_vector(n);
+        let d = Vec3::dot_two(normal, vertices[0]);
+
+        let u_axis = Vec3::unit_vector(vertices[1] - vertices[0]);
+        let v_axis = Vec3::cross_two(normal, u_axis);
+        let planar = |p: Point3| (Vec3::dot_two(p - vertices[0], u_axis), Vec3::dot_two(p - vertices[0], v_axis));
+        let (mut min, mut max) = ((f64::INFINITY, f64::INFINITY), (f64::NEG_INFINITY, f64::NEG_INFINITY));
+        let mut bbox = Aabb::empty();
+        for &vertex in &vertices {
+            let (u, v) = planar(vertex);
+            min = (min.0.min(u), min.1.min(v));
+            max = (max.0.max(u), max.1.max(v));
+            bbox = Aabb::from_aabb(&bbox, &Aabb::from_points(&vertex, &vertex));
+        }
+        let bbox = Aabb::from_intervals(bbox.x, bbox.y, bbox.z);
+
+        Polygon { vertices, normal, d, bbox, material, u_axis, v_axis, uv_min: min, uv_size: (max.0 - min.0, max.1 - min.1) }
+    }
+}
+
+impl Hittable for Polygon {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let denom = Vec3::dot_two(self.normal, r.direction);
+        if denom.abs() < 1e-8 {
+            return false;
+        }
+        let t = (self.d - Vec3::dot_two(self.normal, r.origin)) / denom;
+        if !interval.contains(t) {
+            return false;
+        }
+
+        // Inside a convex polygon, the point is to the left of every edge.
+        let intersection = r.at(t);
+        for (i, &current) in self.vertices.iter().enumerate() {
+            let next = self.vertices[(i + 1) % self.vertices.len()];
+            let edge_normal = Vec3::cross_two(next - current, intersection - current);
+            if Vec3::dot_two(edge_normal, self.normal) < 0.0 {
+                return false;
+            }
+        }
+
+        let planar_hitpt_vector = intersection - self.vertices[0];
+        rec.t = t;
+        rec.point = intersection;
+        rec.u = (Vec3::dot_two(planar_hitpt_vector, self.u_axis) - self.uv_min.0) / self.uv_size.0;
+        rec.v = (Vec3::dot_two(planar_hitpt_vector, self.v_axis) - self.uv_min.1) / self.uv_size.1;
+        rec.material = Some(self.material);
+        rec.set_face_normal(r, self.normal);
+        true
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
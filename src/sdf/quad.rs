@@ -1,6 +1,7 @@
 use crate::core::hittable::{Hittable, HitRecord};
 use crate::core::hittable_list::HittableList;
 use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
 use crate::math::aabb::Aabb;
 use crate::math::interval::Interval;
 use crate::math::ray::Ray;
@@ -60,6 +61,7 @@ impl Quad {
 
 impl Hittable for Quad {
     fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
         let denom = Vec3::dot_two(self.normal, r.direction);
 
         // No hit if the ray is parallel to the plane.
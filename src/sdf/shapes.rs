@@ -0,0 +1,176 @@
+//! Distance functions for common shapes, after Inigo Quilez's catalogue
+//! <https://iquilezles.org/articles/distfunctions/>. Shapes sit at the origin; place them
+//! with `Translate` and `RotateY` around the `SphereTraced` object.
+
+use crate::math::aabb::Aabb;
+use crate::math::vec3::{Point3, Vec3};
+use crate::sdf::distance::Sdf;
+
+pub struct SphereSdf {
+    pub radius: f64,
+}
+
+impl Sdf for SphereSdf {
+    fn distance(&self, p: Point3) -> f64 {
+        p.length() - self.radius
+    }
+
+    fn bounds(&self) -> Aabb {
+        symmetric_bounds(Vec3::new(self.radius, self.radius, self.radius))
+    }
+
+    fn normal(&self, p: Point3, _epsilon: f64) -> Vec3 {
+        Vec3::unit_vector(p)
+    }
+}
+
+/// Axis-aligned box spanning `-half_extents` to `half_extents`.
+pub struct BoxSdf {
+    pub half_extents: Vec3,
+}
+
+impl Sdf for BoxSdf {
+    fn distance(&self, p: Point3) -> f64 {
+        let q = abs(p) - self.half_extents;
+        Vec3::max(q, Vec3::default()).length() + q.x.max(q.y).max(q.z).min(0.0)
+    }
+
+    fn bounds(&self) -> Aabb {
+        symmetric_bounds(self.half_extents)
+    }
+}
+
+/// Box with edges and corners rounded off by `radius`, within the same extents.
+pub struct RoundBoxSdf {
+    pub half_extents: Vec3,
+    pub radius: f64,
+}
+
+impl Sdf for RoundBoxSdf {
+    fn distance(&self, p: Point3) -> f64 {
+        let q = abs(p) - self.half_extents + Vec3::new(self.radius, self.radius, self.radius);
+        Vec3::max(q, Vec3::default()).length() + q.x.max(q.y).max(q.z).min(0.0) - self.radius
+    }
+
+    fn bounds(&self) -> Aabb {
+        symmetric_bounds(self.half_extents)
+    }
+}
+
+/// Ring around the y axis.
+pub struct TorusSdf {
+    pub major_radius: f64, // From the center to the middle of the tube
+    pub minor_radius: f64, // Of the tube
+}
+
+impl Sdf for TorusSdf {
+    fn distance(&self, p: Point3) -> f64 {
+        length2(length2(p.x, p.z) - self.major_radius, p.y) - self.minor_radius
+    }
+
+    fn bounds(&self) -> Aabb {
+        let outer = self.major_radius + self.minor_radius;
+        symmetric_bounds(Vec3::new(outer, self.minor_radius, outer))
+    }
+}
+
+/// Line segment from `a` to `b` thickened by `radius`.
+pub struct CapsuleSdf {
+    pub a: Point3,
+    pub b: Point3,
+    pub radius: f64,
+}
+
+impl Sdf for CapsuleSdf {
+    fn distance(&self, p: Point3) -> f64 {
+        let pa = p - self.a;
+        let ba = self.b - self.a;
+        let h = (Vec3::dot_two(pa, ba) / Vec3::dot_two(ba, ba)).clamp(0.0, 1.0);
+        (pa - ba * h).length() - self.radius
+    }
+
+    fn bounds(&self) -> Aabb {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::from_points(&(Vec3::min(self.a, self.b) - r), &(Vec3::max(self.a, self.b) + r))
+    }
+}
+
+/// Capped cylinder along the y axis.
+pub struct CylinderSdf {
+    pub half_height: f64,
+    pub radius: f64,
+}
+
+impl Sdf for CylinderSdf {
+    fn distance(&self, p: Point3) -> f64 {
+        let dx = length2(p.x, p.z) - self.radius;
+        let dy = p.y.abs() - self.half_height;
+        dx.max(dy).min(0.0) + length2(dx.max(0.0), dy.max(0.0))
+    }
+
+    fn bounds(&self) -> Aabb {
+        symmetric_bounds(Vec3::new(self.radius, self.half_height, self.radius))
+    }
+}
+
+/// Capped cone along the y axis, from `bottom_radius` at `-half_height` to `top_radius` at
+/// `half_height`. A top radius of 0 gives a pointed cone.
+pub struct ConeSdf {
+    pub half_height: f64,
+    pub bottom_radius: f64,
+    pub top_radius: f64,
+}
+
+impl Sdf for ConeSdf {
+    fn distance(&self, p: Point3) -> f64 {
+        let (h, r1, r2) = (self.half_height, self.bottom_radius, self.top_radius);
+        let (qx, qy) = (length2(p.x, p.z), p.y);
+        let (k2x, k2y) = (r2 - r1, 2.0 * h);
+        // Distance to the caps, and to the slanted side.
+        let cax = qx - qx.min(if qy < 0.0 { r1 } else { r2 });
+        let cay = qy.abs() - h;
+        let t = (((r2 - qx) * k2x + (h - qy) * k2y) / (k2x * k2x + k2y * k2y)).clamp(0.0, 1.0);
+        let cbx = qx - r2 + k2x * t;
+        let cby = qy - h + k2y * t;
+        let sign = if cbx < 0.0 && cay < 0.0 { -1.0 } else { 1.0 };
+        sign * (cax * cax + cay * cay).min(cbx * cbx + cby * cby).sqrt()
+    }
+
+    fn bounds(&self) -> Aabb {
+        let radius = self.bottom_radius.max(self.top_radius);
+        symmetric_bounds(Vec3::new(radius, self.half_height, radius))
+    }
+}
+
+/// Infinite plane with unit `normal`, `offset` from the origin along it. Everything behind
+/// the plane is inside.
+pub struct PlaneSdf {
+    pub normal: Vec3,
+    pub offset: f64,
+}
+
+impl Sdf for PlaneSdf {
+    fn distance(&self, p: Point3) -> f64 {
+        Vec3::dot_two(p, self.normal) - self.offset
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::universe()
+    }
+
+    fn normal(&self, _p: Point3, _epsilon: f64) -> Vec3 {
+        self.normal
+    }
+}
+
+fn symmetric_bounds(half_extents: Vec3) -> Aabb {
+    Aabb::from_points(&-half_extents, &half_extents)
+}
+
+fn abs(v: Vec3) -> Vec3 {
+    Vec3::new(v.x.abs(), v.y.abs(), v.z.abs())
+}
+
+fn length2(x: f64, y: f64) -> f64 {
+    (x * x + y * y).sqrt()
+}
@@ -1,12 +1,15 @@
 use crate::core::hittable::{Hittable, HitRecord};
 use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
 use crate::math::aabb::Aabb;
 use crate::math::interval::Interval;
+use crate::math::keyframes::Track;
 use crate::math::vec3::{Point3, Vec3};
 use crate::math::ray::Ray;
 
 pub struct Sphere {
     pub center: Point3,
+    pub motion: Option<Track<Point3>>, // Center over the shutter interval; overrides `center`
     pub radius: f64,
     pub mat: &'static dyn Material,
     pub bbox: Aabb,
@@ -16,13 +19,33 @@ impl Sphere {
     pub fn new(center: Point3, radius: f64, mat: &'static dyn Material) -> Self {
         let rvec = Vec3::new(radius, radius, radius);
         let bbox = Aabb::from_points(&(center - rvec), &(center + rvec));
-        Sphere { center, radius, mat, bbox }
+        Sphere { center, motion: None, radius, mat, bbox }
+    }
+
+    /// A sphere whose center follows `motion` with ray time. The bounding box encloses the
+    /// sphere at every hull point of the track, and therefore along the whole path.
+    pub fn moving(motion: Track<Point3>, radius: f64, mat: &'static dyn Material) -> Self {
+        let rvec = Vec3::new(radius, radius, radius);
+        let bbox = motion.hull_points().into_iter().fold(Aabb::empty(), |bbox, center| {
+            Aabb::from_aabb(&bbox, &Aabb::from_points(&(center - rvec), &(center + rvec)))
+        });
+        Sphere { center: motion.sample(0.0), motion: Some(motion), radius, mat, bbox }
+    }
+
+    fn fill_record(&self, r: Ray, root: f64, center: Point3, rec: &mut HitRecord) {
+        rec.t = root;
+        rec.point = r.at(rec.t);
+        let outward_normal = Vec3::unit_vector(rec.point - center);
+        rec.set_face_normal(r, outward_normal);
+        rec.material = Some(self.mat);
     }
 }
 
 impl Hittable for Sphere {
     fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
-        let oc = self.center - r.origin;
+        stats::count(Counter::PrimitiveTests);
+        let center = self.motion.as_ref().map_or(self.center, |motion| motion.sample(r.time));
+        let oc = center - r.origin;
         let a = r.direction.length_squared();
         let h = Vec3::dot_two(r.direction, oc);
         let c = oc.length_squared() - self.radius * self.radius;
@@ -41,14 +64,34 @@ impl Hittable for Sphere {
             }
         }
 
-        rec.t = root;
-        rec.point = r.at(rec.t);
-        let outward_normal = Vec3::unit_vector(rec.point - self.center);
-        rec.set_face_normal(r, outward_normal);
-        rec.material = Some(self.mat);
+        self.fill_record(r, root, center, rec);
         true
     }
 
+    fn all_hits(&self, r: Ray, interval: Interval, hits: &mut Vec<HitRecord>) {
+        stats::count(Counter::PrimitiveTests);
+        let center = self.motion.as_ref().map_or(self.center, |motion| motion.sample(r.time));
+        let oc = center - r.origin;
+        let a = r.direction.length_squared();
+        let h = Vec3::dot_two(r.direction, oc);
+        let c = oc.length_squared() - self.radius * self.radius;
+        let discriminant = h * h - a * c;
+
+        // A ray grazing the sphere touches it without entering, so it has no hits to report.
+        if discriminant <= 0.0 {
+            return;
+        }
+
+        let sqrtd = discriminant.sqrt();
+        for root in [(h - sqrtd) / a, (h + sqrtd) / a] {
+            if interval.surrounds(root) {
+                let mut rec = HitRecord::default();
+                self.fill_record(r, root, center, &mut rec);
+                hits.push(rec);
+            }
+        }
+    }
+
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
    }
@@ -0,0 +1,107 @@
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::onb::Onb;
+use crate::math::polynomial::solve_quartic;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+use crate::utils::common::PI;
+
+/// Ring around `axis` through `center`. u is the angle around the axis and v the angle
+/// around the tube, both as fractions of a turn.
+pub struct Torus {
+    pub center: Point3,
+    pub major_radius: f64, // From the center to the middle of the tube
+    pub minor_radius: f64, // Of the tube
+    pub onb: Onb, // w points along the axis
+    pub bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+impl Torus {
+    pub fn new(center: Point3, axis: Vec3, major_radius: f64, minor_radius: f64, material: &'static dyn Material) -> Self {
+        let onb = Onb::new(axis);
+        let extent = |n: f64| major_radius * (1.0 - n * n).max(0.0).sqrt() + minor_radius;
+        let extent = Vec3::new(extent(onb.w.x), extent(onb.w.y), extent(onb.w.z));
+        let bbox = Aabb::from_points(&(center - extent), &(center + extent));
+        Torus { center, major_radius, minor_radius, onb, bbox, material }
+    }
+
+    /// Distances along the ray to every crossing of the surface, unsorted.
+    fn roots(&self, r: Ray) -> Vec<f64> {
+        // Solve along a unit direction from the point of the ray closest to the center,
+        // which keeps the quartic well conditioned however far away the ray starts.
+        let length = r.direction.length();
+        let d = self.onb.local(r.direction) / length;
+        let origin = self.onb.local(r.origin - self.center);
+        let t_closest = -Vec3::dot_two(origin, d);
+        let o = origin + t_closest * d;
+        let bounding_radius = self.major_radius + self.minor_radius;
+        if o.length_squared() > bounding_radius * bounding_radius {
+            return Vec::new();
+        }
+
+        // (|p|² + R² - r²)² = 4R² (x² + y²) with p = o + s d
+        let (major, minor) = (self.major_radius * self.major_radius, self.minor_radius * self.minor_radius);
+        let b = Vec3::dot_two(o, d);
+        let g = o.length_squared() + major - minor;
+        let coefficients = [
+            g * g - 4.0 * major * (o.x * o.x + o.y * o.y),
+            4.0 * b * g - 8.0 * major * (o.x * d.x + o.y * d.y),
+            4.0 * b * b + 2.0 * g - 4.0 * major * (d.x * d.x + d.y * d.y),
+            4.0 * b,
+            1.0,
+        ];
+        solve_quartic(coefficients).into_iter().map(|s| (t_closest + s) / length).collect()
+    }
+
+    fn fill_record(&self, r: Ray, t: f64, rec: &mut HitRecord) {
+        rec.t = t;
+        rec.point = r.at(t);
+        let p = self.onb.local(rec.point - self.center);
+        // Away from the nearest point on the ring through the middle of the tube.
+        let ring_distance = p.x.hypot(p.y);
+        let ring_point = if ring_distance > 0.0 {
+            Vec3::new(p.x, p.y, 0.0) * (self.major_radius / ring_distance)
+        } else {
+            Vec3::new(self.major_radius, 0.0, 0.0)
+        };
+        let outward_normal = Vec3::unit_vector(p - ring_point);
+        rec.u = Onb::azimuth(p);
+        rec.v = ((p.z.atan2(ring_distance - self.major_radius)) / (2.0 * PI)).rem_euclid(1.0);
+        rec.material = Some(self.material);
+        rec.set_face_normal(r, self.onb.transform(outward_normal));
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        if !self.bbox.hit(r, interval.clone()) {
+            return false;
+        }
+        let closest = self.roots(r).into_iter().filter(|&t| interval.surrounds(t)).min_by(f64::total_cmp);
+        let Some(t) = closest else {
+            return false;
+        };
+        self.fill_record(r, t, rec);
+        true
+    }
+
+    fn all_hits(&self, r: Ray, interval: Interval, hits: &mut Vec<HitRecord>) {
+        stats::count(Counter::PrimitiveTests);
+        let mut roots: Vec<f64> = self.roots(r).into_iter().filter(|&t| interval.surrounds(t)).collect();
+        roots.sort_by(f64::total_cmp);
+        for t in roots {
+            let mut rec = HitRecord::default();
+            self.fill_record(r, t, &mut rec);
+            hits.push(rec);
+        }
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
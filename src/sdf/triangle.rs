@@ -0,0 +1,74 @@
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::core::stats::{self, Counter};
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+
+/// Triangle with corners `a`, `b` and `c`, facing the side from which they run
+/// counterclockwise. UVs are the barycentric coordinates of `b` and `c`.
+pub struct Triangle {
+    pub a: Point3,
+    pub b: Point3,
+    pub c: Point3,
+    pub normal: Vec3,
+    pub bbox: Aabb,
+    pub material: &'static dyn Material,
+}
+
+impl Triangle {
+    pub fn new(a: Point3, b: Point3, c: Point3, material: &'static dyn Material) -> Self {
+        let normal = Vec3::unit_vector(Vec3::cross_two(b - a, c - a));
+        let bbox = Aabb::from_points(&Vec3::min(Vec3::min(a, b), c), &Vec3::max(Vec3::max(a, b), c));
+        Triangle { a, b, c, normal, bbox, material }
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        stats::count(Counter::PrimitiveTests);
+        let Some((t, u, v)) = intersect_triangle(r, self.a, self.b, self.c) else {
+            return false;
+        };
+        if !interval.contains(t) {
+            return false;
+        }
+
+        rec.t = t;
+        rec.point = r.at(t);
+        rec.u = u;
+        rec.v = v;
+        rec.material = Some(self.material);
+        rec.set_face_normal(r, self.normal);
+        true
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
+
+/// Möller-Trumbore ray-triangle intersection, solving for the distance along the ray and the
+/// barycentric coordinates of `b` and `c` at once.
+pub fn intersect_triangle(r: Ray, a: Point3, b: Point3, c: Point3) -> Option<(f64, f64, f64)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = Vec3::cross_two(r.direction, edge2);
+    let determinant = Vec3::dot_two(edge1, p);
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let s = r.origin - a;
+    let u = Vec3::dot_two(s, p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = Vec3::cross_two(s, edge1);
+    let v = Vec3::dot_two(r.direction, q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    Some((Vec3::dot_two(edge2, q) * inverse, u, v))
+}
@@ -33,6 +33,11 @@ pub fn linear_to_gamma(linear_component: f64) -> f64 {
     }
 }
 
+/// Inverse of `linear_to_gamma`, for gamma encoded colors read from files.
+pub fn gamma_to_linear(gamma_component: f64) -> f64 {
+    gamma_component * gamma_component
+}
+
 pub fn fmt_samples(samples: u64) -> String {
     if samples >= 1_000_000_000 {
         format!("{:.1}B", samples as f64 / 1_000_000_000.0)
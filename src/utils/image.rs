@@ -0,0 +1,77 @@
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::Path;
+
+use crate::utils::common::gamma_to_linear;
+
+/// Color image read from a PNG or JPEG file, with channel values scaled to [0, 1] as stored
+/// (gamma encoded, for color images). Images without alpha are opaque.
+#[derive(Clone, Debug)]
+pub struct RgbaImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[f32; 4]>, // Row by row, top row first
+}
+
+impl RgbaImage {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::decode(&fs::read(path)?)
+    }
+
+    /// Decodes PNG or JPEG data, recognized by its signature.
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        if bytes.starts_with(b"\x89PNG") {
+            let mut decoder = png::Decoder::new(Cursor::new(bytes));
+            decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+            let mut reader = decoder.read_info().map_err(|error| invalid(error.to_string()))?;
+            let mut buffer = vec![0; reader.output_buffer_size().ok_or_else(|| invalid("PNG image too large".to_string()))?];
+            let info = reader.next_frame(&mut buffer).map_err(|error| invalid(error.to_string()))?;
+            let channels = info.color_type.samples();
+            let (width, height) = (info.width as usize, info.height as usize);
+            let pixels = (0..height).flat_map(|y| {
+                let row = &buffer[y * info.line_size..];
+                (0..width).map(move |x| expand(&row[x * channels..(x + 1) * channels], 255.0))
+            }).collect();
+            return Ok(RgbaImage { width, height, pixels });
+        }
+        if bytes.starts_with(&[0xff, 0xd8]) {
+            let mut decoder = jpeg_decoder::Decoder::new(Cursor::new(bytes));
+            let data = decoder.decode().map_err(|error| invalid(error.to_string()))?;
+            let info = decoder.info().ok_or_else(|| invalid("JPEG image without a frame".to_string()))?;
+            let samples: Vec<f32> = match info.pixel_format {
+                jpeg_decoder::PixelFormat::L8 | jpeg_decoder::PixelFormat::RGB24 => data.iter().map(|&b| b as f32).collect(),
+                jpeg_decoder::PixelFormat::L16 => data.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as f32 / 257.0).collect(),
+                jpeg_decoder::PixelFormat::CMYK32 => return Err(invalid("CMYK JPEG images are not supported".to_string())),
+            };
+            let channels = if info.pixel_format == jpeg_decoder::PixelFormat::RGB24 { 3 } else { 1 };
+            let pixels = samples.chunks_exact(channels).map(|pixel| expand(pixel, 255.0)).collect();
+            return Ok(RgbaImage { width: info.width as usize, height: info.height as usize, pixels });
+        }
+        Err(invalid("not a PNG or JPEG image".to_string()))
+    }
+
+    /// Pixel at column `x` and row `y`, counted from the top.
+    pub fn pixel(&self, x: usize, y: usize) -> [f32; 4] {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Copy with the color channels decoded from gamma to linear values, for images holding
+    /// colors rather than data such as roughness.
+    pub fn linearized(&self) -> Self {
+        let decode = |channel: f32| gamma_to_linear(channel as f64) as f32;
+        let pixels = self.pixels.iter().map(|&[red, green, blue, alpha]| [decode(red), decode(green), decode(blue), alpha]).collect();
+        RgbaImage { width: self.width, height: self.height, pixels }
+    }
+}
+
+/// Gray, gray-alpha, RGB or RGBA samples of one pixel as RGBA in [0, 1].
+fn expand<T: Copy + Into<f32>>(samples: &[T], max: f32) -> [f32; 4] {
+    let value = |index: usize| samples[index].into() / max;
+    match samples.len() {
+        1 => [value(0), value(0), value(0), 1.0],
+        2 => [value(0), value(0), value(0), value(1)],
+        3 => [value(0), value(1), value(2), 1.0],
+        _ => [value(0), value(1), value(2), value(3)],
+    }
+}
@@ -1 +1,6 @@
-pub mod common;
\ No newline at end of file
+pub mod common;
+pub mod image;
+pub mod options;
+pub mod pgm;
+pub mod ply;
+pub mod stl;
@@ -0,0 +1,216 @@
+use std::fs;
+use std::time::Duration;
+
+use crate::core::animation::{numbered_path, FrameRange};
+use crate::core::aperture::{ApertureMask, ApertureShape};
+use crate::core::camera::Camera;
+use crate::core::projection::{CubeFace, FisheyeMapping, Projection};
+use crate::core::shutter::Shutter;
+use crate::core::stereo::{Convergence, Stereo, StereoLayout};
+use crate::core::stats::RenderStats;
+use crate::core::video::VideoFormat;
+
+/// Render settings passed on the command line after the demo name.
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    pub time_budget: Option<Duration>, // --time <seconds>
+    pub target_error: Option<f64>, // --noise <relative error>
+    pub max_samples: Option<u64>, // --max-samples <count>
+    pub stats_path: Option<String>, // --stats <file.json>
+    pub projection: Option<Projection>, // --projection <name>
+    pub stereo_layout: Option<StereoLayout>, // --stereo <side-by-side|top-bottom>
+    pub interocular_distance: Option<f64>, // --interocular <distance>
+    pub convergence: Option<Convergence>, // --convergence <parallel|toe-in|off-axis>
+    pub shutter: Option<Shutter>, // --shutter <open> <close>
+    pub frames: Option<FrameRange>, // --frames <start> <end> [--fps <rate>]
+    pub video_path: Option<String>, // --video <file.y4m|file.png>
+    pub aperture: Option<ApertureShape>, // --aperture-blades <count> or --aperture-mask <file.pgm>
+    pub grid_path: Option<String>, // --grid <file.vol|file.svol>
+    pub heightmap_path: Option<String>, // --heightmap <file.pgm>
+    pub points_path: Option<String>, // --points <file.ply|file.xyz>
+    pub mesh_path: Option<String>, // --mesh <file.ply|file.stl>
+    pub scene_path: Option<String>, // --scene <file.gltf|file.glb|file.pbrt>
+}
+
+impl RenderOptions {
+    /// Parses the flags following the demo name. Returns an error message on bad input.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = RenderOptions::default();
+        let mut fps = 24.0;
+        let mut iter = args.iter();
+        while let Some(flag) = iter.next() {
+            let mut value = || iter.next().ok_or_else(|| format!("Missing value for {}", flag));
+            match flag.as_str() {
+                "--time" => {
+                    let seconds = value()?;
+                    let budget = Duration::try_from_secs_f64(parse_number(flag, seconds)?);
+                    options.time_budget = Some(budget.map_err(|_| format!("Invalid value for {}: {}", flag, seconds))?);
+                }
+                "--noise" => options.target_error = Some(parse_number(flag, value()?)?),
+                "--max-samples" => {
+                    let count = value()?;
+                    match parse_integer(flag, count)? {
+                        0 => return Err(format!("Invalid value for {}: {}", flag, count)),
+                        samples => options.max_samples = Some(samples),
+                    }
+                }
+                "--stats" => options.stats_path = Some(value()?.clone()),
+                "--projection" => options.projection = Some(parse_projection(value()?)?),
+                "--stereo" => options.stereo_layout = Some(parse_stereo_layout(value()?)?),
+                "--shutter" => {
+                    let open = parse_number(flag, value()?)?;
+                    let close = parse_number(flag, value()?)?;
+                    options.shutter = Some(Shutter::new(open, close));
+                }
+                "--frames" => {
+                    let start = parse_integer(flag, value()?)?;
+                    let end = parse_integer(flag, value()?)?;
+                    if start > end {
+                        return Err(format!("Invalid range for {}: {} is after {}", flag, start, end));
+                    }
+                    options.frames = Some(FrameRange { start, end, fps });
+                }
+                "--fps" => {
+                    let rate = value()?;
+                    fps = parse_number(flag, rate)?;
+                    if fps == 0.0 {
+                        return Err(format!("Invalid value for {}: {}", flag, rate));
+                    }
+                }
+                "--video" => {
+                    let path = value()?;
+                    if VideoFormat::from_path(path).is_none() {
+                        return Err(format!("Unknown video format: {} (use .y4m or .png)", path));
+                    }
+                    options.video_path = Some(path.clone());
+                }
+                "--aperture-blades" => {
+                    let count = value()?;
+                    let blades = u32::try_from(parse_integer(flag, count)?).ok().filter(|&blades| blades >= 3)
+                        .ok_or_else(|| format!("Invalid value for {}: {} (at least 3 blades)", flag, count))?;
+                    options.aperture = Some(ApertureShape::Polygon { blades, rotation: 0.0 });
+                }
+                "--aperture-mask" => {
+                    let path = value()?;
+                    let mask = ApertureMask::load(path).map_err(|error| format!("Failed to load {}: {}", path, error))?;
+                    options.aperture = Some(ApertureShape::Mask(mask));
+                }
+                "--grid" => options.grid_path = Some(value()?.clone()),
+                "--heightmap" => options.heightmap_path = Some(value()?.clone()),
+                "--points" => options.points_path = Some(value()?.clone()),
+                "--mesh" => options.mesh_path = Some(value()?.clone()),
+                "--scene" => options.scene_path = Some(value()?.clone()),
+                "--interocular" => options.interocular_distance = Some(parse_number(flag, value()?)?),
+                "--convergence" => options.convergence = Some(parse_convergence(value()?)?),
+                _ => return Err(format!("Unknown option: {}", flag)),
+            }
+        }
+        if let Some(frames) = &mut options.frames {
+            frames.fps = fps;
+        }
+        if options.stereo_layout.is_none() {
+            if options.interocular_distance.is_some() {
+                return Err("--interocular requires --stereo".to_string());
+            }
+            if options.convergence.is_some() {
+                return Err("--convergence requires --stereo".to_string());
+            }
+        }
+        if options.video_path.is_some() && options.frames.is_none() {
+            return Err("--video requires --frames".to_string());
+        }
+        Ok(options)
+    }
+
+    /// Overrides the demo's camera settings with the ones given on the command line.
+    pub fn apply(&self, camera: &mut Camera) {
+        if self.time_budget.is_some() {
+            camera.time_budget = self.time_budget;
+        }
+        if self.target_error.is_some() {
+            camera.target_error = self.target_error;
+        }
+        if self.max_samples.is_some() {
+            camera.max_samples_per_pixel = self.max_samples;
+        }
+        if let Some(projection) = self.projection {
+            // Panoramas only make sense at their native aspect ratio.
+            match projection {
+                Projection::Equirectangular => camera.aspect_ratio = 2.0,
+                Projection::CubeFace(_) => camera.aspect_ratio = 1.0,
+                _ => {}
+            }
+            camera.projection = projection;
+        }
+        if let Some(shutter) = self.shutter {
+            camera.shutter = shutter;
+        }
+        if let Some(shape) = &self.aperture {
+            camera.aperture.shape = shape.clone();
+        }
+        if let Some(layout) = self.stereo_layout {
+            // Converge at the focal plane, with a typical human eye separation in meters.
+            let interocular_distance = self.interocular_distance.unwrap_or(0.064);
+            let convergence = self.convergence.unwrap_or(Convergence::OffAxis);
+            camera.stereo = Some(Stereo::new(interocular_distance, camera.focus_distance, convergence, layout));
+        }
+    }
+
+    /// Writes the render statistics as JSON if a --stats path was given. Frames of a
+    /// sequence get numbered files.
+    pub fn write_stats(&self, stats: &RenderStats, frame: Option<u64>) {
+        let Some(path) = &self.stats_path else { return };
+        let path = match frame {
+            Some(frame) => numbered_path(path, frame),
+            None => path.clone(),
+        };
+        if let Err(error) = fs::write(&path, stats.to_json()) {
+            eprintln!("Failed to write stats to {}: {}", path, error);
+        }
+    }
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(number) if number >= 0.0 && number.is_finite() => Ok(number),
+        _ => Err(format!("Invalid value for {}: {}", flag, value)),
+    }
+}
+
+fn parse_integer(flag: &str, value: &str) -> Result<u64, String> {
+    value.parse::<u64>().map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+fn parse_projection(name: &str) -> Result<Projection, String> {
+    let projection = match name {
+        "perspective" => Projection::Perspective,
+        "equirectangular" => Projection::Equirectangular,
+        "fisheye" => Projection::Fisheye { fov: 180.0, mapping: FisheyeMapping::Equidistant },
+        "fisheye-equisolid" => Projection::Fisheye { fov: 180.0, mapping: FisheyeMapping::Equisolid },
+        "cube-front" => Projection::CubeFace(CubeFace::Front),
+        "cube-back" => Projection::CubeFace(CubeFace::Back),
+        "cube-left" => Projection::CubeFace(CubeFace::Left),
+        "cube-right" => Projection::CubeFace(CubeFace::Right),
+        "cube-up" => Projection::CubeFace(CubeFace::Up),
+        "cube-down" => Projection::CubeFace(CubeFace::Down),
+        _ => return Err(format!("Unknown projection: {}", name)),
+    };
+    Ok(projection)
+}
+
+fn parse_convergence(name: &str) -> Result<Convergence, String> {
+    match name {
+        "parallel" => Ok(Convergence::Parallel),
+        "toe-in" => Ok(Convergence::ToeIn),
+        "off-axis" => Ok(Convergence::OffAxis),
+        _ => Err(format!("Unknown convergence: {}", name)),
+    }
+}
+
+fn parse_stereo_layout(name: &str) -> Result<StereoLayout, String> {
+    match name {
+        "side-by-side" => Ok(StereoLayout::SideBySide),
+        "top-bottom" => Ok(StereoLayout::TopBottom),
+        _ => Err(format!("Unknown stereo layout: {}", name)),
+    }
+}
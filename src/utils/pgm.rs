@@ -0,0 +1,65 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Grayscale image read from a PGM file, with values scaled to [0, 1].
+#[derive(Clone, Debug)]
+pub struct GrayImage {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f64>, // Row by row, top row first
+}
+
+impl GrayImage {
+    /// Loads a plain ("P2") or binary ("P5") PGM image. Binary images with a max value above
+    /// 255 hold 16-bit big-endian samples, as is common for elevation maps.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+        // Header: magic, width, height and max value separated by whitespace, with # comments.
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        while fields.len() < 4 {
+            while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if pos < bytes.len() && bytes[pos] == b'#' {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+                continue;
+            }
+            let start = pos;
+            while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if start == pos {
+                return Err(invalid("truncated PGM header"));
+            }
+            fields.push(String::from_utf8_lossy(&bytes[start..pos]).into_owned());
+        }
+        let number = |field: &str| field.parse::<usize>().map_err(|_| invalid("bad PGM header"));
+        let (width, height, max_value) = (number(&fields[1])?, number(&fields[2])?, number(&fields[3])?);
+        if max_value == 0 || max_value > 65535 {
+            return Err(invalid("unsupported PGM max value"));
+        }
+        let pixel_count = width.checked_mul(height).ok_or_else(|| invalid("PGM image too large"))?;
+
+        let data = &bytes[(pos + 1).min(bytes.len())..];
+        let raw: Vec<usize> = match fields[0].as_str() {
+            "P2" => String::from_utf8_lossy(&bytes[pos..])
+                .split_ascii_whitespace()
+                .map(number)
+                .collect::<io::Result<_>>()?,
+            "P5" if max_value > 255 => data.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as usize).collect(),
+            "P5" => data.iter().map(|&b| b as usize).collect(),
+            _ => return Err(invalid("not a PGM image")),
+        };
+        if raw.len() < pixel_count {
+            return Err(invalid("truncated PGM data"));
+        }
+        let values = raw[..pixel_count].iter().map(|&v| v as f64 / max_value as f64).collect();
+        Ok(GrayImage { width, height, values })
+    }
+}
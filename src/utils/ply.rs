@@ -0,0 +1,371 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Contents of a PLY file: its elements (such as `vertex` and `face`) with their properties
+/// read into columns. ASCII and both binary encodings are supported.
+#[derive(Clone, Debug)]
+pub struct PlyFile {
+    pub elements: Vec<PlyElement>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlyElement {
+    pub name: String,
+    pub count: usize,
+    pub properties: Vec<PlyProperty>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlyProperty {
+    pub name: String,
+    pub data_type: PlyType, // Type of the values, or of the items of a list
+    pub values: PlyValues,
+}
+
+/// One value per element, or a list of values per element (such as the vertex indices of a
+/// face).
+#[derive(Clone, Debug)]
+pub enum PlyValues {
+    Scalar(Vec<f64>),
+    List(Vec<Vec<f64>>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlyType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl PlyType {
+    fn parse(name: &str) -> Option<Self> {
+        let data_type = match name {
+            "char" | "int8" => PlyType::Int8,
+            "uchar" | "uint8" => PlyType::UInt8,
+            "short" | "int16" => PlyType::Int16,
+            "ushort" | "uint16" => PlyType::UInt16,
+            "int" | "int32" => PlyType::Int32,
+            "uint" | "uint32" => PlyType::UInt32,
+            "float" | "float32" => PlyType::Float32,
+            "double" | "float64" => PlyType::Float64,
+            _ => return None,
+        };
+        Some(data_type)
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyType::Int8 | PlyType::UInt8 => 1,
+            PlyType::Int16 | PlyType::UInt16 => 2,
+            PlyType::Int32 | PlyType::UInt32 | PlyType::Float32 => 4,
+            PlyType::Float64 => 8,
+        }
+    }
+
+    /// Largest value of unsigned integer types, which color channels are divided by; 1 for
+    /// the others.
+    pub fn unit(self) -> f64 {
+        match self {
+            PlyType::UInt8 => 255.0,
+            PlyType::UInt16 => 65535.0,
+            PlyType::UInt32 => u32::MAX as f64,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Encoding {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+/// A property as declared in the header: its name, value type, and list count type if any.
+struct Declaration {
+    name: String,
+    data_type: PlyType,
+    count_type: Option<PlyType>,
+}
+
+impl PlyFile {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::decode(&fs::read(path)?)
+    }
+
+    /// Parses the contents of a PLY file.
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        // Header: text lines up to `end_header`.
+        let mut pos = 0;
+        let mut next_line = || {
+            let start = pos;
+            while pos < bytes.len() && bytes[pos] != b'\n' {
+                pos += 1;
+            }
+            pos = (pos + 1).min(bytes.len());
+            (start < bytes.len()).then(|| String::from_utf8_lossy(&bytes[start..pos]).trim().to_string())
+        };
+        if next_line().as_deref() != Some("ply") {
+            return Err(invalid("not a PLY file".to_string()));
+        }
+        let mut encoding = None;
+        let mut declarations: Vec<(String, usize, Vec<Declaration>)> = Vec::new();
+        loop {
+            let line = next_line().ok_or_else(|| invalid("missing end_header".to_string()))?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["end_header"] => break,
+                ["format", format, _] => {
+                    encoding = Some(match *format {
+                        "ascii" => Encoding::Ascii,
+                        "binary_little_endian" => Encoding::LittleEndian,
+                        "binary_big_endian" => Encoding::BigEndian,
+                        _ => return Err(invalid(format!("unknown PLY format {}", format))),
+                    });
+                }
+                ["element", name, count] => {
+                    let count = count.parse().map_err(|_| invalid(format!("bad element count: {}", line)))?;
+                    declarations.push((name.to_string(), count, Vec::new()));
+                }
+                ["property", "list", count_type, data_type, name] => {
+                    let (Some(count_type), Some(data_type)) = (PlyType::parse(count_type), PlyType::parse(data_type)) else {
+                        return Err(invalid(format!("unknown property type: {}", line)));
+                    };
+                    let (_, _, properties) = declarations.last_mut().ok_or_else(|| invalid("property before element".to_string()))?;
+                    properties.push(Declaration { name: name.to_string(), data_type, count_type: Some(count_type) });
+                }
+                ["property", data_type, name] => {
+                    let data_type = PlyType::parse(data_type).ok_or_else(|| invalid(format!("unknown property type: {}", line)))?;
+                    let (_, _, properties) = declarations.last_mut().ok_or_else(|| invalid("property before element".to_string()))?;
+                    properties.push(Declaration { name: name.to_string(), data_type, count_type: None });
+                }
+                ["comment", ..] | ["obj_info", ..] | [] => {}
+                _ => return Err(invalid(format!("unexpected PLY header line: {}", line))),
+            }
+        }
+        let encoding = encoding.ok_or_else(|| invalid("missing PLY format".to_string()))?;
+
+        let mut reader = BodyReader { bytes, pos, encoding };
+        let mut elements = Vec::with_capacity(declarations.len());
+        for (name, count, declared) in declarations {
+            // Each entry takes at least a byte per property in ASCII, or the size of each value
+            // or list length in binary, which bounds the count by the rest of the file.
+            let entry_bytes = declared.iter().map(|declaration| match encoding {
+                Encoding::Ascii => 1,
+                _ => declaration.count_type.unwrap_or(declaration.data_type).size(),
+            }).sum::<usize>();
+            if count.checked_mul(entry_bytes).is_none_or(|bytes| bytes > reader.bytes.len() - reader.pos) {
+                return Err(invalid(format!("element {} has more entries than the file holds", name)));
+            }
+            if declared.is_empty() && count > 0 {
+                return Err(invalid(format!("element {} has entries but no properties", name)));
+            }
+            let mut properties: Vec<PlyProperty> = declared.iter().map(|declaration| PlyProperty {
+                name: declaration.name.clone(),
+                data_type: declaration.data_type,
+                values: match declaration.count_type {
+                    Some(_) => PlyValues::List(Vec::with_capacity(count)),
+                    None => PlyValues::Scalar(Vec::with_capacity(count)),
+                },
+            }).collect();
+            for _ in 0..count {
+                for (declaration, property) in declared.iter().zip(&mut properties) {
+                    let truncated = || invalid(format!("truncated PLY data in element {}", name));
+                    match (&mut property.values, declaration.count_type) {
+                        (PlyValues::List(lists), Some(count_type)) => {
+                            let length = reader.read(count_type).ok_or_else(truncated)? as usize;
+                            let list = (0..length).map(|_| reader.read(declaration.data_type)).collect::<Option<_>>().ok_or_else(truncated)?;
+                            lists.push(list);
+                        }
+                        (PlyValues::Scalar(values), _) => values.push(reader.read(declaration.data_type).ok_or_else(truncated)?),
+                        _ => unreachable!(),
+                    }
+                }
+            }
+            elements.push(PlyElement { name, count, properties });
+        }
+        Ok(PlyFile { elements })
+    }
+
+    pub fn element(&self, name: &str) -> Option<&PlyElement> {
+        self.elements.iter().find(|element| element.name == name)
+    }
+}
+
+impl PlyElement {
+    pub fn property(&self, name: &str) -> Option<&PlyProperty> {
+        self.properties.iter().find(|property| property.name == name)
+    }
+
+    /// Values of a scalar property.
+    pub fn scalar(&self, name: &str) -> Option<&[f64]> {
+        match &self.property(name)?.values {
+            PlyValues::Scalar(values) => Some(values),
+            PlyValues::List(_) => None,
+        }
+    }
+
+    /// Values of a list property.
+    pub fn list(&self, name: &str) -> Option<&[Vec<f64>]> {
+        match &self.property(name)?.values {
+            PlyValues::List(lists) => Some(lists),
+            PlyValues::Scalar(_) => None,
+        }
+    }
+
+    /// Three scalar properties read together, such as `x`, `y` and `z`, if all are present.
+    pub fn triples(&self, names: [&str; 3]) -> Option<Vec<[f64; 3]>> {
+        let [a, b, c] = names.map(|name| self.scalar(name));
+        let (a, b, c) = (a?, b?, c?);
+        Some((0..self.count).map(|i| [a[i], b[i], c[i]]).collect())
+    }
+
+    /// Per-element colors in [0, 1] from `red`, `green` and `blue` (or `r`, `g`, `b`)
+    /// properties, with integer channels scaled by their range. The values are as stored,
+    /// usually gamma encoded.
+    pub fn colors(&self) -> Option<Vec<[f64; 3]>> {
+        let names = if self.property("red").is_some() { ["red", "green", "blue"] } else { ["r", "g", "b"] };
+        let unit = self.property(names[0])?.data_type.unit();
+        let colors = self.triples(names)?;
+        Some(colors.into_iter().map(|color| color.map(|channel| channel / unit)).collect())
+    }
+}
+
+/// Reads values one at a time from the body of a PLY file.
+struct BodyReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    encoding: Encoding,
+}
+
+impl BodyReader<'_> {
+    fn read(&mut self, data_type: PlyType) -> Option<f64> {
+        if self.encoding == Encoding::Ascii {
+            while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            let start = self.pos;
+            while self.pos < self.bytes.len() && !self.bytes[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            return std::str::from_utf8(&self.bytes[start..self.pos]).ok()?.parse().ok();
+        }
+
+        let size = data_type.size();
+        let raw = self.bytes.get(self.pos..self.pos + size)?;
+        self.pos += size;
+        let mut word = [0u8; 8];
+        word[..size].copy_from_slice(raw);
+        if self.encoding == Encoding::BigEndian {
+            word[..size].reverse();
+        }
+        let value = match data_type {
+            PlyType::Int8 => word[0] as i8 as f64,
+            PlyType::UInt8 => word[0] as f64,
+            PlyType::Int16 => i16::from_le_bytes([word[0], word[1]]) as f64,
+            PlyType::UInt16 => u16::from_le_bytes([word[0], word[1]]) as f64,
+            PlyType::Int32 => i32::from_le_bytes([word[0], word[1], word[2], word[3]]) as f64,
+            PlyType::UInt32 => u32::from_le_bytes([word[0], word[1], word[2], word[3]]) as f64,
+            PlyType::Float32 => f32::from_le_bytes([word[0], word[1], word[2], word[3]]) as f64,
+            PlyType::Float64 => f64::from_le_bytes(word),
+        };
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "element vertex 3\nproperty float x\nproperty float y\nproperty float z\nproperty uchar red\nproperty uchar green\nproperty uchar blue\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n";
+
+    fn binary(format: &str, endian: fn(&[u8]) -> Vec<u8>) -> Vec<u8> {
+        let mut bytes = format!("ply\nformat {} 1.0\ncomment made by hand\n{}", format, HEADER).into_bytes();
+        for (i, position) in [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, -1.5]].iter().enumerate() {
+            for coordinate in position {
+                bytes.extend(endian(&coordinate.to_le_bytes()));
+            }
+            bytes.extend_from_slice(&[255, 0, 51 * i as u8]);
+        }
+        bytes.push(3);
+        for index in [0i32, 1, 2] {
+            bytes.extend(endian(&index.to_le_bytes()));
+        }
+        bytes
+    }
+
+    fn check_triangle(ply: &PlyFile) {
+        let vertex = ply.element("vertex").unwrap();
+        assert_eq!(vertex.count, 3);
+        assert_eq!(vertex.triples(["x", "y", "z"]).unwrap(), [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, -1.5]]);
+        assert_eq!(vertex.colors().unwrap()[2], [1.0, 0.0, 0.4]);
+        assert_eq!(vertex.property("red").unwrap().data_type, PlyType::UInt8);
+        assert_eq!(ply.element("face").unwrap().list("vertex_indices").unwrap(), [vec![0.0, 1.0, 2.0]]);
+        assert!(vertex.list("x").is_none());
+        assert!(ply.element("edge").is_none());
+    }
+
+    #[test]
+    fn reads_ascii() {
+        let text = format!("ply\r\nformat ascii 1.0\r\n{}0 0 0 255 0 0\n1 0 0 255 0 51\n0 2 -1.5 255 0 102\n3 0 1 2\n", HEADER);
+        check_triangle(&PlyFile::decode(text.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn reads_both_binary_byte_orders() {
+        check_triangle(&PlyFile::decode(&binary("binary_little_endian", |bytes| bytes.to_vec())).unwrap());
+        check_triangle(&PlyFile::decode(&binary("binary_big_endian", |bytes| bytes.iter().rev().copied().collect())).unwrap());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let bytes = binary("binary_little_endian", |bytes| bytes.to_vec());
+        let error = PlyFile::decode(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let text = format!("ply\nformat ascii 1.0\n{}0 0 0 255 0 0\n", HEADER);
+        assert!(PlyFile::decode(text.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn rejects_counts_larger_than_the_file() {
+        for count in ["99999999999999999999", "18446744073709551615", "4611686018427387904", "1000"] {
+            let text = format!("ply\nformat binary_little_endian 1.0\nelement vertex {}\nproperty double x\nend_header\n", count);
+            let error = PlyFile::decode(text.as_bytes()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn rejects_entries_without_properties() {
+        for format in ["ascii", "binary_little_endian"] {
+            let text = format!("ply\nformat {} 1.0\nelement marker 18446744073709551615\nend_header\n", format);
+            let error = PlyFile::decode(text.as_bytes()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+        let text = "ply\nformat ascii 1.0\nelement marker 0\nelement vertex 1\nproperty float x\nend_header\n1\n";
+        assert!(PlyFile::decode(text.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        for text in [
+            "PLY\nformat ascii 1.0\nend_header\n",
+            "ply\nformat ascii 1.0\nelement vertex 1\n",
+            "ply\nelement vertex 0\nend_header\n",
+            "ply\nformat utf8 1.0\nend_header\n",
+            "ply\nformat ascii 1.0\nproperty float x\nend_header\n",
+            "ply\nformat ascii 1.0\nelement vertex 1\nproperty half x\nend_header\n",
+            "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nunknown line\nend_header\n",
+        ] {
+            assert_eq!(PlyFile::decode(text.as_bytes()).unwrap_err().kind(), io::ErrorKind::InvalidData, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::math::vec3::Point3;
+
+/// Triangles of an STL file, ASCII or binary. The facet normals stored in the file are
+/// skipped: exporters often leave them zero, and the corner order gives the same facing.
+#[derive(Clone, Debug)]
+pub struct StlFile {
+    pub triangles: Vec<[Point3; 3]>,
+}
+
+impl StlFile {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::decode(&fs::read(path)?)
+    }
+
+    /// Parses the contents of an STL file.
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+        // Binary files start with an 80-byte header that may itself begin with "solid", so
+        // tell the formats apart by whether the size matches the triangle count.
+        if bytes.len() >= 84 {
+            let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+            if bytes.len() == 84 + count * 50 {
+                let float = |offset: usize| f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as f64;
+                let point = |offset: usize| Point3::new(float(offset), float(offset + 4), float(offset + 8));
+                let triangles = (0..count).map(|i| {
+                    // Each record: normal, three corners, and a 2-byte attribute.
+                    let start = 84 + i * 50 + 12;
+                    [point(start), point(start + 12), point(start + 24)]
+                }).collect();
+                return Ok(StlFile { triangles });
+            }
+        }
+
+        let text = String::from_utf8_lossy(bytes);
+        if !text.trim_start().starts_with("solid") {
+            return Err(invalid("not an STL file"));
+        }
+        let mut corners = Vec::new();
+        for line in text.lines() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if let ["vertex", x, y, z] = words.as_slice() {
+                let coordinate = |word: &str| word.parse::<f64>().map_err(|_| invalid("bad STL vertex"));
+                corners.push(Point3::new(coordinate(x)?, coordinate(y)?, coordinate(z)?));
+            }
+        }
+        if corners.len() % 3 != 0 {
+            return Err(invalid("STL facets without three vertices"));
+        }
+        Ok(StlFile { triangles: corners.chunks_exact(3).map(|corners| [corners[0], corners[1], corners[2]]).collect() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORNERS: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.5]];
+
+    fn binary(header: &[u8], count: u32) -> Vec<u8> {
+        let mut bytes = header.to_vec();
+        bytes.resize(80, 0);
+        bytes.extend_from_slice(&count.to_le_bytes());
+        for _ in 0..count {
+            bytes.extend_from_slice(&[0; 12]); // Normal
+            for coordinate in CORNERS.as_flattened() {
+                bytes.extend_from_slice(&coordinate.to_le_bytes());
+            }
+            bytes.extend_from_slice(&[0; 2]);
+        }
+        bytes
+    }
+
+    fn expected() -> [Point3; 3] {
+        CORNERS.map(|[x, y, z]| Point3::new(x as f64, y as f64, z as f64))
+    }
+
+    #[test]
+    fn reads_ascii() {
+        let text = "solid part\n  facet normal 0 0 1\n    outer loop\n      vertex 0 0 0\n      vertex 1 0 0\n      vertex 0 1 0.5\n    endloop\n  endfacet\nendsolid part\n";
+        assert_eq!(StlFile::decode(text.as_bytes()).unwrap().triangles, [expected()]);
+    }
+
+    #[test]
+    fn reads_binary_even_with_a_solid_header() {
+        assert_eq!(StlFile::decode(&binary(b"exported", 2)).unwrap().triangles, [expected(), expected()]);
+        assert_eq!(StlFile::decode(&binary(b"solid exported", 1)).unwrap().triangles, [expected()]);
+        assert!(StlFile::decode(&binary(b"solid empty", 0)).unwrap().triangles.is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let truncated = binary(b"exported", 2);
+        let malformed: [&[u8]; 4] = [
+            &truncated[..truncated.len() - 1],
+            b"solid part\nvertex 0 0 0\nvertex 1 0 0\nendsolid\n",
+            b"solid part\nvertex 0 zero 0\nendsolid\n",
+            b"",
+        ];
+        for bytes in malformed {
+            assert_eq!(StlFile::decode(bytes).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
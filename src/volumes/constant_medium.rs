@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use crate::core::hittable::{HitRecord, Hittable};
+use crate::core::material::Material;
+use crate::math::aabb::Aabb;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vec3;
+use crate::utils::common::{random, INFINITY};
+
+/// A volume of uniform density filling a closed boundary, such as fog or smoke.
+///
+/// A ray passing through the volume scatters at an exponentially distributed distance. The
+/// scattering event is reported as an ordinary hit with the phase function as its material, so
+/// the camera keeps bouncing rays off it like off any surface. The boundary must be convex.
+pub struct ConstantMedium {
+    pub boundary: Arc<dyn Hittable + Send + Sync>,
+    pub neg_inv_density: f64,
+    pub phase_function: &'static dyn Material, // Usually `Isotropic`
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Arc<dyn Hittable + Send + Sync>, density: f64, phase_function: &'static dyn Material) -> Self {
+        ConstantMedium { boundary, neg_inv_density: -1.0 / density, phase_function }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        // Find where the ray enters and leaves the boundary, even if it starts inside.
+        let mut rec1 = HitRecord::default();
+        let mut rec2 = HitRecord::default();
+        if !self.boundary.hit(r, Interval::UNIVERSE, &mut rec1) {
+            return false;
+        }
+        if !self.boundary.hit(r, Interval::new(rec1.t + 0.0001, INFINITY), &mut rec2) {
+            return false;
+        }
+
+        let t_enter = rec1.t.max(interval.min).max(0.0);
+        let t_exit = rec2.t.min(interval.max);
+        if t_enter >= t_exit {
+            return false;
+        }
+
+        let ray_length = r.direction.length();
+        let distance_inside_boundary = (t_exit - t_enter) * ray_length;
+        let hit_distance = self.neg_inv_density * random().ln();
+        if hit_distance > distance_inside_boundary {
+            return false;
+        }
+
+        rec.t = t_enter + hit_distance / ray_length;
+        rec.point = r.at(rec.t);
+        rec.normal = Vec3::new(1.0, 0.0, 0.0); // Arbitrary
+        rec.front_face = true; // Also arbitrary
+        rec.clear_primitive_data();
+        rec.material = Some(self.phase_function);
+        true
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        self.boundary.bounding_box()
+    }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.boundary.acceleration_bytes()
+    }
+}
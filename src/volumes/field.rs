@@ -0,0 +1,249 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::math::aabb::Aabb;
+use crate::math::ray::Ray;
+use crate::math::vec3::Point3;
+use crate::volumes::majorant::{MajorantGrid, MajorantSegment};
+use crate::volumes::sparse_grid::SparseGrid;
+
+/// Voxels per majorant grid cell along each axis.
+pub const MAJORANT_CELL_VOXELS: usize = 8;
+
+/// A scalar field over space, such as the density or temperature of a medium.
+pub trait DensityField: Send + Sync {
+    /// Value at a point; 0 outside the field.
+    fn density(&self, p: Point3) -> f64;
+
+    /// Upper bound of `density` everywhere, used as the majorant for tracking.
+    fn max_density(&self) -> f64;
+
+    /// Bounds of the density along the ray between `t_min` and `t_max`, in order. Fields
+    /// with a majorant grid return tighter local bounds than `max_density`.
+    fn majorants(&self, _r: Ray, t_min: f64, t_max: f64) -> Vec<MajorantSegment> {
+        vec![MajorantSegment { t_min, t_max, majorant: self.max_density() }]
+    }
+}
+
+/// Voxel values on a regular grid spanning `bounds`, sampled at voxel centers and
+/// interpolated trilinearly in between.
+pub struct DenseGrid {
+    pub bounds: Aabb,
+    pub resolution: [usize; 3],
+    values: Vec<f32>, // x fastest, then y, then z
+    majorants: MajorantGrid,
+}
+
+impl DenseGrid {
+    pub fn new(bounds: Aabb, resolution: [usize; 3], values: Vec<f32>) -> Self {
+        assert_eq!(values.len(), resolution.iter().product::<usize>(), "grid values do not match its resolution");
+        // Majorant cells cover whole blocks of voxels, overhanging the grid where its resolution
+        // is not a multiple of the block size.
+        let cells = resolution.map(|n| n.div_ceil(MAJORANT_CELL_VOXELS));
+        let overhang = |axis: usize| {
+            let interval = bounds.axis_interval(axis);
+            interval.min + interval.size() * (cells[axis] * MAJORANT_CELL_VOXELS) as f64 / resolution[axis] as f64
+        };
+        let majorant_bounds = Aabb::from_points(
+            &Point3::new(bounds.x.min, bounds.y.min, bounds.z.min),
+            &Point3::new(overhang(0), overhang(1), overhang(2)),
+        );
+        let majorants = MajorantGrid::from_fn(majorant_bounds, cells, 0.0, |i, j, k| {
+            // Interpolation reaches one voxel past the cell on each side.
+            let range = |cell: usize, n: usize| {
+                (cell * MAJORANT_CELL_VOXELS).saturating_sub(1)..((cell + 1) * MAJORANT_CELL_VOXELS + 1).min(n)
+            };
+            let mut max = 0.0f64;
+            for z in range(k, resolution[2]) {
+                for y in range(j, resolution[1]) {
+                    for x in range(i, resolution[0]) {
+                        max = max.max(values[(z * resolution[1] + y) * resolution[0] + x] as f64);
+                    }
+                }
+            }
+            max
+        });
+        DenseGrid { bounds, resolution, values, majorants }
+    }
+
+    /// Loads a dense grid in the Mitsuba `.vol` layout: the bytes `VOL` and version 3, then
+    /// little-endian 32-bit fields: encoding (1 for float32), resolution x, y and z, channel
+    /// count, bounds (min x, y, z, max x, y, z), and finally the float32 voxel values with x
+    /// varying fastest. Only the first channel is kept.
+    pub fn load(path: &str) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message));
+        if bytes.len() < 48 || &bytes[0..3] != b"VOL" || bytes[3] != 3 {
+            return Err(invalid("not a version 3 VOL file"));
+        }
+        let int = |offset: usize| i32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let float = |offset: usize| f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        if int(4) != 1 {
+            return Err(invalid("only float32 encoding is supported"));
+        }
+        let (nx, ny, nz, channels) = (int(8), int(12), int(16), int(20));
+        if nx <= 0 || ny <= 0 || nz <= 0 || channels <= 0 {
+            return Err(invalid("invalid resolution"));
+        }
+        let resolution = [nx as usize, ny as usize, nz as usize];
+        let channels = channels as usize;
+        let voxel_count = resolution.iter().try_fold(1usize, |count, &n| count.checked_mul(n));
+        let data_bytes = voxel_count.and_then(|count| count.checked_mul(channels)?.checked_mul(4));
+        let (Some(voxel_count), Some(data_bytes)) = (voxel_count, data_bytes) else {
+            return Err(invalid("resolution too large"));
+        };
+        if bytes.len() - 48 < data_bytes {
+            return Err(invalid("truncated voxel data"));
+        }
+
+        let bounds = Aabb::from_points(
+            &Point3::new(float(24) as f64, float(28) as f64, float(32) as f64),
+            &Point3::new(float(36) as f64, float(40) as f64, float(44) as f64),
+        );
+        let values = (0..voxel_count).map(|index| float(48 + index * channels * 4)).collect();
+        Ok(DenseGrid::new(bounds, resolution, values))
+    }
+
+    /// Fills a grid by evaluating `f` at the center of every voxel.
+    pub fn from_fn(bounds: Aabb, resolution: [usize; 3], f: impl Fn(Point3) -> f64) -> Self {
+        let [nx, ny, nz] = resolution;
+        let mut values = Vec::with_capacity(nx * ny * nz);
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    let p = Point3::new(
+                        bounds.x.min + (i as f64 + 0.5) / nx as f64 * bounds.x.size(),
+                        bounds.y.min + (j as f64 + 0.5) / ny as f64 * bounds.y.size(),
+                        bounds.z.min + (k as f64 + 0.5) / nz as f64 * bounds.z.size(),
+                    );
+                    values.push(f(p) as f32);
+                }
+            }
+        }
+        DenseGrid::new(bounds, resolution, values)
+    }
+
+    /// Value of a voxel, with indices clamped to the grid.
+    pub fn voxel(&self, i: i64, j: i64, k: i64) -> f64 {
+        let [nx, ny, nz] = self.resolution;
+        let i = i.clamp(0, nx as i64 - 1) as usize;
+        let j = j.clamp(0, ny as i64 - 1) as usize;
+        let k = k.clamp(0, nz as i64 - 1) as usize;
+        self.values[(k * ny + j) * nx + i] as f64
+    }
+}
+
+impl DensityField for DenseGrid {
+    fn density(&self, p: Point3) -> f64 {
+        if !self.bounds.x.contains(p.x) || !self.bounds.y.contains(p.y) || !self.bounds.z.contains(p.z) {
+            return 0.0;
+        }
+        // Continuous voxel coordinates, with voxel centers at whole numbers.
+        let [nx, ny, nz] = self.resolution;
+        let gx = (p.x - self.bounds.x.min) / self.bounds.x.size() * nx as f64 - 0.5;
+        let gy = (p.y - self.bounds.y.min) / self.bounds.y.size() * ny as f64 - 0.5;
+        let gz = (p.z - self.bounds.z.min) / self.bounds.z.size() * nz as f64 - 0.5;
+        trilinear(gx, gy, gz, |i, j, k| self.voxel(i, j, k))
+    }
+
+    fn max_density(&self) -> f64 {
+        self.majorants.max()
+    }
+
+    fn majorants(&self, r: Ray, t_min: f64, t_max: f64) -> Vec<MajorantSegment> {
+        self.majorants.segments(r, t_min, t_max)
+    }
+}
+
+/// Procedural puffy density: fractal value noise fading out towards the edge of a sphere.
+pub struct NoiseField {
+    pub center: Point3,
+    pub radius: f64,
+    pub frequency: f64, // Noise features per unit length at the first octave
+    pub octaves: u32,
+    pub seed: u64,
+}
+
+impl DensityField for NoiseField {
+    fn density(&self, p: Point3) -> f64 {
+        let falloff = 1.0 - (p - self.center).length() / self.radius;
+        if falloff <= 0.0 {
+            return 0.0;
+        }
+        let mut sum = 0.0;
+        let mut amplitude = 0.5;
+        let mut frequency = self.frequency;
+        for octave in 0..self.octaves {
+            let seed = self.seed.wrapping_add(octave as u64);
+            sum += amplitude * value_noise(p.x * frequency, p.y * frequency, p.z * frequency, seed);
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        // Sharpen the noise so the edges of puffs thin out instead of fading linearly.
+        (2.0 * sum * falloff.sqrt() - 0.3).clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f64 {
+        1.0
+    }
+}
+
+/// Loads a voxel grid, choosing the format by extension: `.vol` for `DenseGrid` and `.svol`
+/// for `SparseGrid`. Also returns the extent of the grid.
+pub fn load_grid(path: &str) -> io::Result<(Arc<dyn DensityField>, Aabb)> {
+    match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("vol") => {
+            let grid = DenseGrid::load(path)?;
+            let bounds = grid.bounds.clone();
+            Ok((Arc::new(grid), bounds))
+        }
+        Some("svol") => {
+            let grid = SparseGrid::load(path)?;
+            let bounds = grid.bounds().clone();
+            Ok((Arc::new(grid), bounds))
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown grid format: {}", path))),
+    }
+}
+
+/// Interpolates the lattice values `value(i, j, k)` at continuous coordinates.
+pub fn trilinear(x: f64, y: f64, z: f64, value: impl Fn(i64, i64, i64) -> f64) -> f64 {
+    let (i, j, k) = (x.floor(), y.floor(), z.floor());
+    let (fx, fy, fz) = (x - i, y - j, z - k);
+    let (i, j, k) = (i as i64, j as i64, k as i64);
+    let mut sum = 0.0;
+    for (di, wx) in [(0, 1.0 - fx), (1, fx)] {
+        for (dj, wy) in [(0, 1.0 - fy), (1, fy)] {
+            for (dk, wz) in [(0, 1.0 - fz), (1, fz)] {
+                sum += wx * wy * wz * value(i + di, j + dj, k + dk);
+            }
+        }
+    }
+    sum
+}
+
+/// Smoothly interpolated pseudo-random values in [0, 1] at integer lattice points.
+pub fn value_noise(x: f64, y: f64, z: f64, seed: u64) -> f64 {
+    let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+    let (i, j, k) = (x.floor(), y.floor(), z.floor());
+    trilinear(
+        i + smooth(x - i),
+        j + smooth(y - j),
+        k + smooth(z - k),
+        |i, j, k| lattice_value(i, j, k, seed),
+    )
+}
+
+fn lattice_value(i: i64, j: i64, k: i64, seed: u64) -> f64 {
+    // SplitMix64 finalizer over the combined coordinates.
+    let mut h = seed
+        ^ (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ (j as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f)
+        ^ (k as u64).wrapping_mul(0x1656_67b1_9e37_79f9);
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^= h >> 31;
+    (h >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,229 @@
+use std::sync::{Arc, OnceLock};
+
+use crate::core::hittable::{HitRecord, Hittable, MediumSegment};
+use crate::core::material::Material;
+use crate::materials::henyey_greenstein::HenyeyGreenstein;
+use crate::math::aabb::Aabb;
+use crate::math::color::Color;
+use crate::math::interval::Interval;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+use crate::utils::common::{random, INFINITY};
+use crate::volumes::field::DensityField;
+use crate::volumes::majorant::MajorantSegment;
+
+/// How rays are traced through a medium.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tracking {
+    Delta, // Rays collide with the medium and scatter through its phase function
+    Ratio, // Rays pass straight through, dimmed by absorption and picking up emission
+}
+
+/// Light emitted by a medium: `color` scaled by the value of `field`, e.g. a temperature.
+#[derive(Clone)]
+pub struct Emission {
+    pub field: Arc<dyn DensityField>,
+    pub color: Color,
+}
+
+impl Emission {
+    pub fn radiance(&self, p: Point3) -> Color {
+        self.color * self.field.density(p)
+    }
+}
+
+/// A medium whose density varies through space, filling a closed convex boundary.
+///
+/// Scattering media use delta tracking: tentative collisions are sampled against the
+/// majorant (the density bound, local where the field has a majorant grid) and accepted with
+/// probability density / majorant, which samples real collisions without bias. A collision
+/// is reported as a hit, like `ConstantMedium`. Purely absorbing media use ratio tracking
+/// instead: rays always pass through, weighted by an unbiased transmittance estimate, which
+/// avoids the noise of randomly terminated paths.
+pub struct HeterogeneousMedium {
+    pub boundary: Arc<dyn Hittable + Send + Sync>,
+    pub density: Arc<dyn DensityField>,
+    pub extinction: f64, // Extinction coefficient where the density is 1
+    pub tracking: Tracking,
+    phase: HenyeyGreenstein,
+    emission: Option<Emission>,
+    material: OnceLock<&'static CollisionMaterial>, // Built at the first collision
+}
+
+impl HeterogeneousMedium {
+    /// A medium that scatters light through `phase`, tracked with delta tracking.
+    pub fn scattering(
+        boundary: Arc<dyn Hittable + Send + Sync>,
+        density: Arc<dyn DensityField>,
+        extinction: f64,
+        phase: HenyeyGreenstein,
+    ) -> Self {
+        HeterogeneousMedium { boundary, density, extinction, tracking: Tracking::Delta, phase, emission: None, material: OnceLock::new() }
+    }
+
+    /// A medium that only absorbs (and optionally emits) light, tracked with ratio tracking.
+    pub fn absorbing(boundary: Arc<dyn Hittable + Send + Sync>, density: Arc<dyn DensityField>, extinction: f64) -> Self {
+        let phase = HenyeyGreenstein { albedo: Color::black(), g: 0.0 };
+        HeterogeneousMedium { boundary, density, extinction, tracking: Tracking::Ratio, phase, emission: None, material: OnceLock::new() }
+    }
+
+    /// Makes the medium glow, e.g. for fire. Emission is weighted by the absorbed fraction of
+    /// each collision, so a medium that scatters everything does not glow.
+    pub fn with_emission(mut self, emission: Emission) -> Self {
+        self.emission = Some(emission);
+        self
+    }
+
+    /// Where the ray enters and leaves the boundary within the interval, even if it starts inside.
+    fn boundary_interval(&self, r: Ray, interval: Interval) -> Option<(f64, f64)> {
+        let mut rec1 = HitRecord::default();
+        let mut rec2 = HitRecord::default();
+        if !self.boundary.hit(r, Interval::UNIVERSE, &mut rec1) {
+            return None;
+        }
+        if !self.boundary.hit(r, Interval::new(rec1.t + 0.0001, INFINITY), &mut rec2) {
+            return None;
+        }
+        let t_enter = rec1.t.max(interval.min).max(0.0);
+        let t_exit = rec2.t.min(interval.max);
+        if t_enter >= t_exit {
+            return None;
+        }
+        Some((t_enter, t_exit))
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, r: Ray, interval: Interval, rec: &mut HitRecord) -> bool {
+        if self.tracking != Tracking::Delta {
+            return false;
+        }
+        let Some((t_enter, t_exit)) = self.boundary_interval(r, interval) else {
+            return false;
+        };
+
+        // Delta tracking: step between tentative collisions until one is real.
+        let mut tracker = CollisionTracker::new(r, self.density.majorants(r, t_enter, t_exit), self.extinction);
+        loop {
+            let Some((t, majorant)) = tracker.next() else {
+                return false;
+            };
+            let point = r.at(t);
+            if random() * majorant < self.extinction * self.density.density(point) {
+                rec.t = t;
+                rec.point = point;
+                rec.normal = Vec3::new(1.0, 0.0, 0.0); // Arbitrary
+                rec.front_face = true; // Also arbitrary
+                rec.clear_primitive_data();
+                rec.material = Some(*self.material.get_or_init(|| {
+                    Box::leak(Box::new(CollisionMaterial { phase: self.phase, emission: self.emission.clone() }))
+                }));
+                return true;
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        self.boundary.bounding_box()
+    }
+
+    fn acceleration_bytes(&self) -> u64 {
+        self.boundary.acceleration_bytes()
+    }
+
+    fn contains_media(&self) -> bool {
+        self.tracking == Tracking::Ratio
+    }
+
+    fn media_segments(&self, r: Ray, interval: Interval, segments: &mut Vec<MediumSegment>) {
+        if self.tracking != Tracking::Ratio {
+            return;
+        }
+        let Some((t_enter, t_exit)) = self.boundary_interval(r, interval) else {
+            return;
+        };
+
+        // Ratio tracking: every tentative collision scales the transmittance by the chance it
+        // was a null collision. Emission is gathered at the same points, weighted by the
+        // transmittance in front of them and the chance of a real collision.
+        let mut tracker = CollisionTracker::new(r, self.density.majorants(r, t_enter, t_exit), self.extinction);
+        let mut transmittance = 1.0;
+        let mut emission = Color::default();
+        while let Some((t, majorant)) = tracker.next() {
+            let point = r.at(t);
+            let real_fraction = (self.extinction * self.density.density(point) / majorant).min(1.0);
+            if let Some(medium_emission) = &self.emission {
+                emission += medium_emission.radiance(point) * (transmittance * real_fraction);
+            }
+            transmittance *= 1.0 - real_fraction;
+
+            // Russian roulette once little light gets through, keeping the estimate unbiased.
+            if transmittance < 0.1 {
+                if random() < 0.5 {
+                    transmittance = 0.0;
+                    break;
+                }
+                transmittance *= 2.0;
+            }
+        }
+        segments.push(MediumSegment { t: t_enter, transmittance: Color::new(transmittance, transmittance, transmittance), emission });
+    }
+}
+
+/// Samples tentative collisions along a ray against piecewise-constant majorants: exponential
+/// steps in optical depth, carried over from one segment to the next.
+struct CollisionTracker {
+    segments: Vec<MajorantSegment>,
+    segment: usize,
+    t: f64,
+    ray_length: f64,
+    extinction: f64,
+}
+
+impl CollisionTracker {
+    fn new(r: Ray, segments: Vec<MajorantSegment>, extinction: f64) -> Self {
+        let t = segments.first().map_or(0.0, |segment| segment.t_min);
+        CollisionTracker { segments, segment: 0, t, ray_length: r.direction.length(), extinction }
+    }
+
+    /// Distance along the ray of the next tentative collision and the majorant extinction
+    /// there, or None once the ray has left the last segment.
+    fn next(&mut self) -> Option<(f64, f64)> {
+        let mut optical_depth = -(1.0 - random()).ln();
+        while let Some(segment) = self.segments.get(self.segment) {
+            let majorant = segment.majorant * self.extinction;
+            let t = self.t.max(segment.t_min);
+            if majorant > 0.0 {
+                let t_collision = t + optical_depth / (majorant * self.ray_length);
+                if t_collision < segment.t_max {
+                    self.t = t_collision;
+                    return Some((t_collision, majorant));
+                }
+                optical_depth -= majorant * (segment.t_max - t).max(0.0) * self.ray_length;
+            }
+            self.t = t.max(segment.t_max);
+            self.segment += 1;
+        }
+        None
+    }
+}
+
+/// Material reported at real collisions: scatters through the phase function and emits the
+/// absorbed share of the medium's emission (the collision estimator of emission).
+struct CollisionMaterial {
+    phase: HenyeyGreenstein,
+    emission: Option<Emission>,
+}
+
+impl Material for CollisionMaterial {
+    fn emitted(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        match &self.emission {
+            Some(emission) => (Color::white() - self.phase.albedo) * emission.radiance(p),
+            None => Color::default(),
+        }
+    }
+
+    fn scatter(&self, r_in: Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+        self.phase.scatter(r_in, rec, attenuation, scattered)
+    }
+}
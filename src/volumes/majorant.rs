@@ -0,0 +1,120 @@
+use crate::math::aabb::Aabb;
+use crate::math::ray::Ray;
+
+/// Part of a ray, between `t_min` and `t_max`, over which the density never exceeds `majorant`.
+#[derive(Clone, Copy, Debug)]
+pub struct MajorantSegment {
+    pub t_min: f64,
+    pub t_max: f64,
+    pub majorant: f64,
+}
+
+/// Coarse grid of density bounds over a volume. Tracking against local bounds instead of the
+/// global maximum skips empty and thin regions in a few large steps.
+pub struct MajorantGrid {
+    pub bounds: Aabb,
+    pub resolution: [usize; 3],
+    values: Vec<f64>, // x fastest, then y, then z
+    pub outside: f64, // Bound outside `bounds`
+}
+
+impl MajorantGrid {
+    /// Builds the grid by asking `bound(i, j, k)` for the bound of every cell.
+    pub fn from_fn(bounds: Aabb, resolution: [usize; 3], outside: f64, bound: impl Fn(usize, usize, usize) -> f64) -> Self {
+        let [nx, ny, nz] = resolution;
+        let mut values = Vec::with_capacity(nx * ny * nz);
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    values.push(bound(i, j, k));
+                }
+            }
+        }
+        MajorantGrid { bounds, resolution, values, outside }
+    }
+
+    pub fn cell(&self, i: usize, j: usize, k: usize) -> f64 {
+        let [nx, ny, _] = self.resolution;
+        self.values[(k * ny + j) * nx + i]
+    }
+
+    pub fn max(&self) -> f64 {
+        self.values.iter().fold(self.outside, |max, &value| max.max(value))
+    }
+
+    /// Splits the ray between `t_min` and `t_max` at cell boundaries, in order along the ray,
+    /// walking the cells with a 3D DDA.
+    pub fn segments(&self, r: Ray, t_min: f64, t_max: f64) -> Vec<MajorantSegment> {
+        let mut segments = Vec::new();
+
+        // Clip the ray to the grid bounds.
+        let (mut t_enter, mut t_exit) = (t_min, t_max);
+        for axis in 0..3 {
+            let interval = self.bounds.axis_interval(axis);
+            let (origin, direction) = (r.origin[axis], r.direction[axis]);
+            if direction == 0.0 {
+                if origin < interval.min || origin > interval.max {
+                    t_exit = t_enter;
+                }
+                continue;
+            }
+            let t0 = (interval.min - origin) / direction;
+            let t1 = (interval.max - origin) / direction;
+            t_enter = t_enter.max(t0.min(t1));
+            t_exit = t_exit.min(t0.max(t1));
+        }
+        if t_enter >= t_exit {
+            segments.push(MajorantSegment { t_min, t_max, majorant: self.outside });
+            return segments;
+        }
+        if t_enter > t_min {
+            segments.push(MajorantSegment { t_min, t_max: t_enter, majorant: self.outside });
+        }
+
+        let mut cell = [0i64; 3];
+        let mut step = [0i64; 3];
+        let mut t_next = [f64::INFINITY; 3];
+        let mut t_delta = [f64::INFINITY; 3];
+        let p = r.at(t_enter);
+        for axis in 0..3 {
+            let interval = self.bounds.axis_interval(axis);
+            let cells = self.resolution[axis] as f64;
+            let cell_size = interval.size() / cells;
+            let position = ((p[axis] - interval.min) / cell_size).floor();
+            cell[axis] = position.clamp(0.0, cells - 1.0) as i64;
+
+            let direction = r.direction[axis];
+            if direction > 0.0 {
+                step[axis] = 1;
+                let boundary = interval.min + (cell[axis] + 1) as f64 * cell_size;
+                t_next[axis] = t_enter + (boundary - p[axis]) / direction;
+                t_delta[axis] = cell_size / direction;
+            } else if direction < 0.0 {
+                step[axis] = -1;
+                let boundary = interval.min + cell[axis] as f64 * cell_size;
+                t_next[axis] = t_enter + (boundary - p[axis]) / direction;
+                t_delta[axis] = -cell_size / direction;
+            }
+        }
+
+        let mut t = t_enter;
+        loop {
+            let axis = (0..3).fold(0, |best, axis| if t_next[axis] < t_next[best] { axis } else { best });
+            let end = t_next[axis].min(t_exit);
+            let majorant = self.cell(cell[0] as usize, cell[1] as usize, cell[2] as usize);
+            segments.push(MajorantSegment { t_min: t, t_max: end, majorant });
+            if end >= t_exit {
+                break;
+            }
+            // Rounding can step past the last cell just before the ray leaves the grid.
+            cell[axis] = (cell[axis] + step[axis]).clamp(0, self.resolution[axis] as i64 - 1);
+            t = end;
+            t_next[axis] += t_delta[axis];
+        }
+
+        if t_exit < t_max {
+            segments.push(MajorantSegment { t_min: t_exit, t_max, majorant: self.outside });
+        }
+        segments
+    }
+}
@@ -0,0 +1,5 @@
+pub mod constant_medium;
+pub mod field;
+pub mod heterogeneous_medium;
+pub mod majorant;
+pub mod sparse_grid;
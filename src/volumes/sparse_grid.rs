@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use crate::math::aabb::Aabb;
+use crate::math::ray::Ray;
+use crate::math::vec3::{Point3, Vec3};
+use crate::volumes::field::{trilinear, DensityField};
+use crate::volumes::majorant::{MajorantGrid, MajorantSegment};
+
+/// Most majorant cells a loaded grid may span; the cells cover the bounding box of the
+/// stored blocks, so scattered blocks would otherwise need a huge majorant grid.
+const MAX_MAJORANT_CELLS: usize = 1 << 24;
+
+/// Voxel grid that only stores the blocks holding data, in the spirit of NanoVDB leaf nodes.
+/// Voxels outside the stored blocks read as `background`. Each block also serves as one
+/// majorant grid cell.
+pub struct SparseGrid {
+    pub origin: Point3, // World position of the minimum corner of voxel (0, 0, 0)
+    pub voxel_size: Vec3,
+    pub block_size: usize, // Voxels per block along each axis
+    pub background: f64,
+    blocks: HashMap<[i64; 3], usize>, // Block coordinates to block index
+    values: Vec<f32>, // Blocks one after another, each with x varying fastest
+    majorants: MajorantGrid,
+}
+
+impl SparseGrid {
+    pub fn new(origin: Point3, voxel_size: Vec3, block_size: usize, background: f64, blocks: Vec<([i64; 3], Vec<f32>)>) -> Self {
+        let block_voxels = block_size.checked_pow(3).expect("block size too large");
+        let mut index = HashMap::with_capacity(blocks.len());
+        let mut values = Vec::with_capacity(blocks.len() * block_voxels);
+        let mut block_max = Vec::with_capacity(blocks.len());
+        let (mut min, mut max) = ([i64::MAX; 3], [i64::MIN; 3]);
+        for (coordinates, block) in blocks {
+            assert_eq!(block.len(), block_voxels, "block values do not match the block size");
+            index.insert(coordinates, block_max.len());
+            block_max.push(block.iter().fold(background, |max, &value| max.max(value as f64)));
+            values.extend_from_slice(&block);
+            for axis in 0..3 {
+                min[axis] = min[axis].min(coordinates[axis]);
+                max[axis] = max[axis].max(coordinates[axis]);
+            }
+        }
+        if index.is_empty() {
+            (min, max) = ([0; 3], [0; 3]);
+        }
+
+        // Interpolation near a block edge reads the neighboring block, so each cell is bounded
+        // by the maximum over the block and its 26 neighbors.
+        let block_extent = voxel_size * block_size as f64;
+        let corner = |block: [i64; 3]| {
+            origin + Vec3::new(block[0] as f64 * block_extent.x, block[1] as f64 * block_extent.y, block[2] as f64 * block_extent.z)
+        };
+        let bounds = Aabb::from_points(&corner(min), &corner(max.map(|coordinate| coordinate + 1)));
+        let resolution = [0, 1, 2].map(|axis| (max[axis] - min[axis] + 1) as usize);
+        let majorants = MajorantGrid::from_fn(bounds, resolution, background, |i, j, k| {
+            let mut bound = background;
+            for dz in -1..=1 {
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        let block = [min[0] + i as i64 + dx, min[1] + j as i64 + dy, min[2] + k as i64 + dz];
+                        if let Some(&block_index) = index.get(&block) {
+                            bound = bound.max(block_max[block_index]);
+                        }
+                    }
+                }
+            }
+            bound
+        });
+
+        SparseGrid { origin, voxel_size, block_size, background, blocks: index, values, majorants }
+    }
+
+    /// Loads a sparse block grid. All fields are little-endian: the bytes `SVOL`, version 1
+    /// (u32), block size (u32), origin (3 x f32), voxel size (3 x f32), background value (f32),
+    /// block count (u32), then for every block its coordinates in blocks (3 x i32) followed by
+    /// block size cubed float32 values with x varying fastest.
+    pub fn load(path: &str) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message));
+        const HEADER_BYTES: usize = 44;
+        if bytes.len() < HEADER_BYTES || &bytes[0..4] != b"SVOL" {
+            return Err(invalid("not a sparse volume file"));
+        }
+        let word = |offset: usize| -> [u8; 4] { bytes[offset..offset + 4].try_into().unwrap() };
+        let float = |offset: usize| f32::from_le_bytes(word(offset)) as f64;
+        if u32::from_le_bytes(word(4)) != 1 {
+            return Err(invalid("unsupported version"));
+        }
+        let block_size = u32::from_le_bytes(word(8)) as usize;
+        if block_size == 0 {
+            return Err(invalid("invalid block size"));
+        }
+        let origin = Point3::new(float(12), float(16), float(20));
+        let voxel_size = Vec3::new(float(24), float(28), float(32));
+        if ![voxel_size.x, voxel_size.y, voxel_size.z].iter().all(|&size| size > 0.0 && size.is_finite()) {
+            return Err(invalid("invalid voxel size"));
+        }
+        let background = float(36);
+        let block_count = u32::from_le_bytes(word(40)) as usize;
+
+        let block_voxels = block_size.checked_pow(3).ok_or_else(|| invalid("invalid block size"))?;
+        let block_bytes = block_voxels.checked_mul(4).and_then(|bytes| bytes.checked_add(12));
+        let data_bytes = block_bytes.and_then(|block_bytes| block_count.checked_mul(block_bytes));
+        let (Some(block_bytes), Some(data_bytes)) = (block_bytes, data_bytes) else {
+            return Err(invalid("block count too large"));
+        };
+        if bytes.len() - HEADER_BYTES < data_bytes {
+            return Err(invalid("truncated block data"));
+        }
+        let blocks = (0..block_count).map(|block| {
+            let start = HEADER_BYTES + block * block_bytes;
+            let coordinates = [0, 1, 2].map(|axis| i32::from_le_bytes(word(start + axis * 4)) as i64);
+            let values = (0..block_voxels).map(|voxel| f32::from_le_bytes(word(start + 12 + voxel * 4))).collect();
+            (coordinates, values)
+        }).collect::<Vec<([i64; 3], Vec<f32>)>>();
+        let span = |axis: usize| {
+            let min = blocks.iter().map(|(block, _)| block[axis]).min().unwrap_or(0);
+            let max = blocks.iter().map(|(block, _)| block[axis]).max().unwrap_or(0);
+            (max - min + 1) as usize
+        };
+        let cells = span(0).checked_mul(span(1)).and_then(|cells| cells.checked_mul(span(2)));
+        if cells.is_none_or(|cells| cells > MAX_MAJORANT_CELLS) {
+            return Err(invalid("blocks spread too far apart"));
+        }
+        Ok(SparseGrid::new(origin, voxel_size, block_size, background, blocks))
+    }
+
+    /// Value of the voxel with global index (i, j, k).
+    pub fn voxel(&self, i: i64, j: i64, k: i64) -> f64 {
+        let size = self.block_size as i64;
+        let block = [i.div_euclid(size), j.div_euclid(size), k.div_euclid(size)];
+        let Some(&block_index) = self.blocks.get(&block) else {
+            return self.background;
+        };
+        let (x, y, z) = (i.rem_euclid(size), j.rem_euclid(size), k.rem_euclid(size));
+        let offset = block_index * self.block_size.pow(3) + ((z * size + y) * size + x) as usize;
+        self.values[offset] as f64
+    }
+
+    /// Extent of the stored blocks.
+    pub fn bounds(&self) -> &Aabb {
+        &self.majorants.bounds
+    }
+
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+}
+
+impl DensityField for SparseGrid {
+    fn density(&self, p: Point3) -> f64 {
+        let bounds = &self.majorants.bounds;
+        if !bounds.x.contains(p.x) || !bounds.y.contains(p.y) || !bounds.z.contains(p.z) {
+            return self.background;
+        }
+        // Continuous voxel coordinates, with voxel centers at whole numbers.
+        let gx = (p.x - self.origin.x) / self.voxel_size.x - 0.5;
+        let gy = (p.y - self.origin.y) / self.voxel_size.y - 0.5;
+        let gz = (p.z - self.origin.z) / self.voxel_size.z - 0.5;
+        trilinear(gx, gy, gz, |i, j, k| self.voxel(i, j, k))
+    }
+
+    fn max_density(&self) -> f64 {
+        self.majorants.max()
+    }
+
+    fn majorants(&self, r: Ray, t_min: f64, t_max: f64) -> Vec<MajorantSegment> {
+        self.majorants.segments(r, t_min, t_max)
+    }
+}